@@ -1,10 +1,14 @@
-use crate::lang::errors::{to_crush_error, CrushResult};
+use crate::lang::errors::{mandate, to_crush_error, CrushResult};
+use crate::lang::stream::CrushStream;
+use crate::lang::table::{ColumnType, Row};
+use crate::lang::value::{Value, ValueType};
+use chrono::Duration;
 use crossbeam::{bounded, Receiver, Sender};
 use std::cmp::min;
 use std::collections::VecDeque;
 use std::fmt::{Debug, Formatter};
 use std::fs::File;
-use std::io::{Error, Read, Write};
+use std::io::{BufReader, Error, Read, Write};
 use std::path::PathBuf;
 
 struct ChannelReader {
@@ -79,12 +83,14 @@ pub trait BinaryReader: Read + Debug + Send + Sync {
 }
 
 struct FileReader {
-    file: File,
+    file: BufReader<File>,
 }
 
 impl FileReader {
     pub fn new(file: File) -> FileReader {
-        FileReader { file }
+        FileReader {
+            file: BufReader::new(file),
+        }
     }
 }
 
@@ -102,9 +108,7 @@ impl Read for FileReader {
 
 impl BinaryReader for FileReader {
     fn clone(&self) -> Box<dyn BinaryReader + Send + Sync> {
-        Box::from(FileReader {
-            file: self.file.try_clone().unwrap(),
-        })
+        Box::from(FileReader::new(self.file.get_ref().try_clone().unwrap()))
     }
 }
 
@@ -213,3 +217,64 @@ impl Debug for VecReader {
         f.write_str("<vec reader>")
     }
 }
+
+/// A table stream over the bytes of a binary, with one row per byte. Rows are created as
+/// they are read, not up front.
+pub struct ByteReader {
+    bytes: Vec<u8>,
+    idx: usize,
+    types: Vec<ColumnType>,
+}
+
+impl ByteReader {
+    pub fn new(bytes: Vec<u8>) -> ByteReader {
+        ByteReader {
+            bytes,
+            idx: 0,
+            types: vec![
+                ColumnType::new("offset", ValueType::Integer),
+                ColumnType::new("byte", ValueType::Integer),
+            ],
+        }
+    }
+}
+
+impl CrushStream for ByteReader {
+    fn read(&mut self) -> CrushResult<Row> {
+        let byte = *mandate(self.bytes.get(self.idx), "EOF")?;
+        self.idx += 1;
+        Ok(Row::new(vec![
+            Value::Integer(self.idx as i128 - 1),
+            Value::Integer(byte as i128),
+        ]))
+    }
+
+    fn read_timeout(
+        &mut self,
+        _timeout: Duration,
+    ) -> Result<Row, crate::lang::stream::RecvTimeoutError> {
+        match self.read() {
+            Ok(r) => Ok(r),
+            Err(_) => Err(crate::lang::stream::RecvTimeoutError::Disconnected),
+        }
+    }
+
+    fn types(&self) -> &[ColumnType] {
+        &self.types
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_reader() {
+        let mut reader = ByteReader::new(vec![7, 255]);
+        let first = reader.read().unwrap();
+        assert!(first.cells() == &vec![Value::Integer(0), Value::Integer(7)]);
+        let second = reader.read().unwrap();
+        assert!(second.cells() == &vec![Value::Integer(1), Value::Integer(255)]);
+        assert!(reader.read().is_err());
+    }
+}
@@ -54,28 +54,86 @@ impl Dict {
         }
     }
 
+    /// Check that a value can be used as a key in this dict.
+    pub fn check_key(&self, key: &Value) -> CrushResult<()> {
+        if !self.key_type.is(key) {
+            return argument_error(
+                format!(
+                    "Invalid key type, expected {}, got {}",
+                    self.key_type.to_string(),
+                    key.value_type().to_string()
+                )
+                .as_str(),
+            );
+        }
+        key.check_hashable()
+    }
+
+    pub fn contains(&self, key: &Value) -> bool {
+        if key.check_hashable().is_err() {
+            return false;
+        }
+        let entries = self.entries.lock().unwrap();
+        entries.contains_key(key)
+    }
+
     pub fn get(&self, key: &Value) -> Option<Value> {
+        key.check_hashable().ok()?;
         let entries = self.entries.lock().unwrap();
         entries.get(key).map(|c| c.clone())
     }
 
     pub fn remove(&self, key: &Value) -> Option<Value> {
+        key.check_hashable().ok()?;
         let mut entries = self.entries.lock().unwrap();
         entries.remove(key)
     }
 
-    pub fn insert(&self, key: Value, value: Value) -> CrushResult<()> {
-        let mut entries = self.entries.lock().unwrap();
-        if !self.key_type.is(&key) {
-            return argument_error("Invalid key type");
+    fn check_entry(&self, key: &Value, value: &Value) -> CrushResult<()> {
+        self.check_key(key)?;
+        if let Value::Float(f) = key {
+            if f.is_nan() {
+                return argument_error("NaN can't be used as a dict key");
+            }
         }
-        if !self.value_type.is(&value) {
-            return argument_error("Invalid value type");
+        self.check_value(value)
+    }
+
+    /// Check that a value can be stored in this dict.
+    pub fn check_value(&self, value: &Value) -> CrushResult<()> {
+        if self.value_type.is(value) {
+            Ok(())
+        } else {
+            argument_error(
+                format!(
+                    "Invalid value type, expected {}, got {}",
+                    self.value_type.to_string(),
+                    value.value_type().to_string()
+                )
+                .as_str(),
+            )
         }
+    }
+
+    pub fn insert(&self, key: Value, value: Value) -> CrushResult<()> {
+        let mut entries = self.entries.lock().unwrap();
+        self.check_entry(&key, &value)?;
         entries.insert(key, value);
         Ok(())
     }
 
+    /// Insert a mapping unless the key is already present, and return the value the key
+    /// ends up being mapped to.
+    pub fn set_default(&self, key: Value, value: Value) -> CrushResult<Value> {
+        let mut entries = self.entries.lock().unwrap();
+        self.check_entry(&key, &value)?;
+        if let Some(current) = entries.get(&key) {
+            return Ok(current.clone());
+        }
+        entries.insert(key, value.clone());
+        Ok(value)
+    }
+
     pub fn key_type(&self) -> ValueType {
         self.key_type.clone()
     }
@@ -98,17 +156,17 @@ impl Dict {
             .collect()
     }
 
-    pub fn materialize(self) -> Dict {
+    pub fn materialize(self) -> CrushResult<Dict> {
         let mut entries = self.entries.lock().unwrap();
         let map = entries
             .drain()
-            .map(|(k, v)| (k.materialize(), v.materialize()))
-            .collect();
-        Dict {
+            .map(|(k, v)| Ok((k.materialize()?, v.materialize()?)))
+            .collect::<CrushResult<OrderedMap<Value, Value>>>()?;
+        Ok(Dict {
             key_type: self.key_type.materialize(),
             value_type: self.value_type.materialize(),
             entries: Arc::new(Mutex::new(map)),
-        }
+        })
     }
 }
 
@@ -215,3 +273,69 @@ impl CrushStream for DictReader {
         &self.types
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::list::List;
+
+    #[test]
+    fn test_float_keys() {
+        let dict = Dict::new(ValueType::Float, ValueType::String);
+        dict.insert(Value::Float(1.5), Value::string("a")).unwrap();
+        dict.insert(Value::Float(0.0), Value::string("b")).unwrap();
+        assert!(dict.get(&Value::Float(1.5)) == Some(Value::string("a")));
+        dict.insert(Value::Float(-0.0), Value::string("c")).unwrap();
+        assert_eq!(dict.len(), 2);
+        assert!(dict.get(&Value::Float(0.0)) == Some(Value::string("c")));
+        assert!(dict
+            .insert(Value::Float(f64::NAN), Value::string("d"))
+            .is_err());
+        assert_eq!(dict.len(), 2);
+    }
+
+    #[test]
+    fn test_unhashable_keys() {
+        let dict = Dict::new(ValueType::Any, ValueType::Integer);
+        let key = Value::List(List::new(ValueType::Integer, vec![]));
+        assert!(dict.insert(key.clone(), Value::Integer(1)).is_err());
+        assert!(dict.get(&key).is_none());
+        assert!(dict.remove(&key).is_none());
+        assert!(!dict.contains(&key));
+        assert!(dict.check_key(&key).is_err());
+        assert_eq!(dict.len(), 0);
+    }
+
+    #[test]
+    fn test_set_default() {
+        let dict = Dict::new(ValueType::String, ValueType::Integer);
+        assert!(
+            dict.set_default(Value::string("a"), Value::Integer(1))
+                .unwrap()
+                == Value::Integer(1)
+        );
+        assert!(
+            dict.set_default(Value::string("a"), Value::Integer(2))
+                .unwrap()
+                == Value::Integer(1)
+        );
+        assert_eq!(dict.len(), 1);
+        assert!(dict
+            .set_default(Value::string("b"), Value::string("x"))
+            .is_err());
+        assert!(dict
+            .set_default(Value::Integer(1), Value::Integer(1))
+            .is_err());
+        assert_eq!(dict.len(), 1);
+    }
+
+    #[test]
+    fn test_contains() {
+        let dict = Dict::new(ValueType::String, ValueType::Integer);
+        dict.insert(Value::string("a"), Value::Integer(1)).unwrap();
+        assert!(dict.contains(&Value::string("a")));
+        assert!(!dict.contains(&Value::string("b")));
+        assert!(dict.check_key(&Value::string("b")).is_ok());
+        assert!(dict.check_key(&Value::Integer(1)).is_err());
+    }
+}
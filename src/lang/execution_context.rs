@@ -1,4 +1,5 @@
 use crate::lang::argument::Argument;
+use crate::lang::binary::BinaryReader;
 use crate::lang::command::Command;
 use crate::lang::dict::Dict;
 use crate::lang::errors::{argument_error, error, CrushResult};
@@ -25,6 +26,10 @@ pub trait ArgumentVector {
     fn float(&mut self, idx: usize) -> CrushResult<f64>;
     fn field(&mut self, idx: usize) -> CrushResult<Vec<String>>;
     fn file(&mut self, idx: usize) -> CrushResult<PathBuf>;
+    fn binary(&mut self, idx: usize) -> CrushResult<Vec<u8>>;
+    fn duration(&mut self, idx: usize) -> CrushResult<Duration>;
+    fn time(&mut self, idx: usize) -> CrushResult<DateTime<Local>>;
+    fn list(&mut self, idx: usize) -> CrushResult<List>;
     fn command(&mut self, idx: usize) -> CrushResult<Command>;
     fn r#type(&mut self, idx: usize) -> CrushResult<ValueType>;
     fn value(&mut self, idx: usize) -> CrushResult<Value>;
@@ -133,6 +138,10 @@ impl ArgumentVector for Vec<Argument> {
     argument_getter!(r#struct, Struct, Struct, "struct");
     argument_getter!(bool, bool, Bool, "bool");
     argument_getter!(file, PathBuf, File, "file");
+    argument_getter!(binary, Vec<u8>, Binary, "binary");
+    argument_getter!(duration, Duration, Duration, "duration");
+    argument_getter!(time, DateTime<Local>, Time, "time");
+    argument_getter!(list, List, List, "list");
 
     fn value(&mut self, idx: usize) -> CrushResult<Value> {
         if idx < self.len() {
@@ -310,6 +319,7 @@ pub trait This {
     fn table(self) -> CrushResult<Table>;
     fn table_stream(self) -> CrushResult<InputStream>;
     fn binary(self) -> CrushResult<Vec<u8>>;
+    fn binary_stream(self) -> CrushResult<Box<dyn BinaryReader + Send + Sync>>;
     fn scope(self) -> CrushResult<Scope>;
 }
 
@@ -351,6 +361,12 @@ impl This for Option<Value> {
     this_method!(time, DateTime<Local>, Time, "time");
     this_method!(scope, Scope, Scope, "scope");
     this_method!(table_stream, InputStream, TableStream, "table_stream");
+    this_method!(
+        binary_stream,
+        Box<dyn BinaryReader + Send + Sync>,
+        BinaryStream,
+        "binary_stream"
+    );
 
     fn re(mut self) -> CrushResult<(String, Regex)> {
         match self.take() {
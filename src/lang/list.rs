@@ -1,4 +1,4 @@
-use crate::lang::errors::{argument_error, error, mandate, CrushResult};
+use crate::lang::errors::{argument_error, error, CrushResult};
 use crate::lang::stream::CrushStream;
 use crate::lang::{table::ColumnType, table::Row, value::Field, value::Value, value::ValueType};
 use crate::util::identity_arc::Identity;
@@ -8,6 +8,16 @@ use std::collections::HashSet;
 use std::hash::Hasher;
 use std::sync::{Arc, Mutex};
 
+pub fn index_error<T>(idx: i128, len: usize) -> CrushResult<T> {
+    argument_error(
+        format!(
+            "Index {} is out of bounds for a list of length {}",
+            idx, len
+        )
+        .as_str(),
+    )
+}
+
 #[derive(Clone)]
 pub struct List {
     cell_type: ValueType,
@@ -70,7 +80,10 @@ impl List {
 
     pub fn get(&self, idx: usize) -> CrushResult<Value> {
         let cells = self.cells.lock().unwrap();
-        Ok(mandate(cells.get(idx), "Index out of bounds")?.clone())
+        match cells.get(idx) {
+            Some(v) => Ok(v.clone()),
+            None => index_error(idx as i128, cells.len()),
+        }
     }
 
     pub fn set(&self, idx: usize, value: Value) -> CrushResult<()> {
@@ -79,7 +92,7 @@ impl List {
         }
         let mut cells = self.cells.lock().unwrap();
         if idx >= cells.len() {
-            return error("Index out of range");
+            return index_error(idx as i128, cells.len());
         }
 
         cells[idx] = value;
@@ -90,9 +103,7 @@ impl List {
     pub fn append(&self, new_cells: &mut Vec<Value>) -> CrushResult<()> {
         let mut cells = self.cells.lock().unwrap();
         for v in new_cells.iter() {
-            if !self.cell_type.is(v) {
-                return argument_error("Invalid argument type");
-            }
+            self.check_element_type(v)?;
         }
         cells.append(new_cells);
         Ok(())
@@ -104,6 +115,15 @@ impl List {
         res
     }
 
+    /// Replace all elements of the list, e.g. with a sorted copy of them.
+    pub fn replace_cells(&self, new_cells: Vec<Value>) -> CrushResult<()> {
+        for v in new_cells.iter() {
+            self.check_element_type(v)?;
+        }
+        *self.cells.lock().unwrap() = new_cells;
+        Ok(())
+    }
+
     pub fn pop(&self) -> Option<Value> {
         let mut cells = self.cells.lock().unwrap();
         cells.pop()
@@ -114,22 +134,20 @@ impl List {
         cells.clear();
     }
 
-    pub fn remove(&self, idx: usize) -> CrushResult<()> {
+    /// Remove the element at the specified index and return it.
+    pub fn remove(&self, idx: usize) -> CrushResult<Value> {
         let mut cells = self.cells.lock().unwrap();
         if idx >= cells.len() {
-            return argument_error("Index out of bounds");
+            return index_error(idx as i128, cells.len());
         }
-        cells.remove(idx);
-        Ok(())
+        Ok(cells.remove(idx))
     }
 
     pub fn insert(&self, idx: usize, value: Value) -> CrushResult<()> {
+        self.check_element_type(&value)?;
         let mut cells = self.cells.lock().unwrap();
-        if !self.cell_type.is(&value) {
-            return argument_error("Invalid argument type");
-        }
         if idx > cells.len() {
-            return argument_error("Index out of bounds");
+            return index_error(idx as i128, cells.len());
         }
         cells.insert(idx, value);
         Ok(())
@@ -142,7 +160,22 @@ impl List {
 
     pub fn peek(&self) -> Option<Value> {
         let cells = self.cells.lock().unwrap();
-        cells.get(cells.len() - 1).map(|v| v.clone())
+        cells.last().cloned()
+    }
+
+    fn check_element_type(&self, value: &Value) -> CrushResult<()> {
+        if self.cell_type.is(value) {
+            Ok(())
+        } else {
+            argument_error(
+                format!(
+                    "Can't add a value of type {} to a list of {}",
+                    value.value_type().to_string(),
+                    self.cell_type.to_string()
+                )
+                .as_str(),
+            )
+        }
     }
 
     pub fn element_type(&self) -> ValueType {
@@ -153,13 +186,16 @@ impl List {
         ValueType::List(Box::from(self.cell_type.clone()))
     }
 
-    pub fn materialize(self) -> List {
+    pub fn materialize(self) -> CrushResult<List> {
         let mut cells = self.cells.lock().unwrap();
-        let vec: Vec<Value> = cells.drain(..).map(|c| c.materialize()).collect();
-        List {
+        let vec = cells
+            .drain(..)
+            .map(|c| c.materialize())
+            .collect::<CrushResult<Vec<Value>>>()?;
+        Ok(List {
             cell_type: self.cell_type.materialize(),
             cells: Arc::new(Mutex::from(vec)),
-        }
+        })
     }
 
     pub fn copy(&self) -> List {
@@ -5,7 +5,7 @@ use crate::lang::scope::Scope;
 lalrpop_mod!(pub lalrparser, "/lang/lalrparser.rs");
 
 pub fn parse_name(s: &str) -> Option<Vec<String>> {
-    let res = s.split('/').collect::<Vec<&str>>();
+    let res = s.split(':').collect::<Vec<&str>>();
     for i in res.iter() {
         if i.is_empty() {
             return None;
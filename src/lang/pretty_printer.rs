@@ -9,6 +9,7 @@ use crate::lang::table::TableReader;
 use crate::lang::value::Alignment;
 use crate::lang::value::Value;
 use crate::lang::value::ValueType;
+use crate::util::hex;
 use std::cmp::max;
 use std::io::{BufReader, Read};
 use std::thread;
@@ -34,13 +35,6 @@ pub struct PrettyPrinter {
     printer: Printer,
 }
 
-pub fn hex(v: u8) -> String {
-    let arr = vec![
-        "0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "a", "b", "c", "d", "e", "f",
-    ];
-    format!("{}{}", arr[(v >> 4) as usize], arr[(v & 15) as usize])
-}
-
 fn is_printable(v: u8) -> bool {
     v >= 0x20 && v <= 0x7e
 }
@@ -54,7 +48,7 @@ fn printable(v: u8) -> String {
 }
 
 fn format_binary_chunk(c: &[u8]) -> String {
-    let hex = c.iter().map(|u| hex(*u)).collect::<Vec<String>>().join("");
+    let hex = hex::encode(c);
     let printable = c
         .iter()
         .map(|u| printable(*u))
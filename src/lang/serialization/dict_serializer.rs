@@ -20,6 +20,9 @@ impl Serializable<Dict> for Dict {
                     let key_type = ValueType::deserialize(d.key_type as usize, elements, state)?;
                     let value_type =
                         ValueType::deserialize(d.value_type as usize, elements, state)?;
+                    if !key_type.is_hashable() {
+                        return error("Invalid dict key type");
+                    }
                     let dict = Dict::new(key_type, value_type);
                     state.dicts.insert(id, dict.clone());
 
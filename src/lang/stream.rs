@@ -5,6 +5,8 @@ use crate::lang::value::Value;
 use chrono::Duration;
 use crossbeam::{bounded, unbounded, Receiver, Sender};
 use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 pub type RecvTimeoutError = crossbeam::channel::RecvTimeoutError;
 
@@ -57,6 +59,17 @@ impl ValueReceiver {
 #[derive(Clone)]
 pub struct OutputStream {
     sender: Sender<Row>,
+    panicked: Arc<AtomicBool>,
+}
+
+/// A closed channel looks the same whether the sending command finished or crashed, so
+/// remember if the output was dropped while unwinding from a panic.
+impl Drop for OutputStream {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.panicked.store(true, Ordering::SeqCst);
+        }
+    }
 }
 
 impl OutputStream {
@@ -73,6 +86,7 @@ impl OutputStream {
 pub struct InputStream {
     receiver: Receiver<Row>,
     types: Vec<ColumnType>,
+    panicked: Arc<AtomicBool>,
 }
 
 impl InputStream {
@@ -92,7 +106,25 @@ impl InputStream {
     }
 
     pub fn recv(&self) -> CrushResult<Row> {
-        self.validate(to_crush_error(self.receiver.recv()))
+        match self.recv_optional()? {
+            Some(row) => Ok(row),
+            None => error("End of stream"),
+        }
+    }
+
+    /// Like recv, but returns None when the stream has ended normally and an error if the
+    /// command producing the stream crashed or sent an invalid row.
+    pub fn recv_optional(&self) -> CrushResult<Option<Row>> {
+        match self.receiver.recv() {
+            Ok(row) => self.validate(row).map(Some),
+            Err(_) => {
+                if self.panicked.load(Ordering::SeqCst) {
+                    error("The command producing this stream crashed")
+                } else {
+                    Ok(None)
+                }
+            }
+        }
     }
 
     pub fn recv_timeout(&self, timeout: Duration) -> Result<Row, RecvTimeoutError> {
@@ -103,29 +135,24 @@ impl InputStream {
         &self.types
     }
 
-    fn validate(&self, res: CrushResult<Row>) -> CrushResult<Row> {
-        match &res {
-            Ok(row) => {
-                if row.cells().len() != self.types.len() {
-                    return error("Wrong number of columns in io");
-                }
-                for (c, ct) in row.cells().iter().zip(self.types.iter()) {
-                    if !ct.cell_type.is(c) {
-                        return error(
-                            format!(
-                                "Wrong cell type in io column {:?}, expected {:?}, got {:?}",
-                                ct.name,
-                                c.value_type(),
-                                ct.cell_type
-                            )
-                            .as_str(),
-                        );
-                    }
-                }
-                res
+    fn validate(&self, row: Row) -> CrushResult<Row> {
+        if row.cells().len() != self.types.len() {
+            return error("Wrong number of columns in io");
+        }
+        for (c, ct) in row.cells().iter().zip(self.types.iter()) {
+            if !ct.cell_type.is(c) {
+                return error(
+                    format!(
+                        "Wrong cell type in io column {:?}, expected {:?}, got {:?}",
+                        ct.name,
+                        c.value_type(),
+                        ct.cell_type
+                    )
+                    .as_str(),
+                );
             }
-            Err(_) => res,
         }
+        Ok(row)
     }
 }
 
@@ -139,22 +166,32 @@ pub fn channels() -> (ValueSender, ValueReceiver) {
 
 pub fn streams(signature: Vec<ColumnType>) -> (OutputStream, InputStream) {
     let (output, input) = bounded(128);
+    let panicked = Arc::new(AtomicBool::new(false));
     (
-        OutputStream { sender: output },
+        OutputStream {
+            sender: output,
+            panicked: panicked.clone(),
+        },
         InputStream {
             receiver: input,
             types: signature,
+            panicked,
         },
     )
 }
 
 pub fn unlimited_streams(signature: Vec<ColumnType>) -> (OutputStream, InputStream) {
     let (output, input) = unbounded();
+    let panicked = Arc::new(AtomicBool::new(false));
     (
-        OutputStream { sender: output },
+        OutputStream {
+            sender: output,
+            panicked: panicked.clone(),
+        },
         InputStream {
             receiver: input,
             types: signature,
+            panicked,
         },
     )
 }
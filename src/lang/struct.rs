@@ -1,3 +1,4 @@
+use crate::lang::errors::CrushResult;
 use crate::lang::table::ColumnType;
 use crate::lang::table::Row;
 use crate::lang::value::Value;
@@ -187,9 +188,9 @@ impl Struct {
         }
     }
 
-    pub fn materialize(&self) -> Struct {
+    pub fn materialize(&self) -> CrushResult<Struct> {
         let data = self.data.lock().unwrap();
-        Struct {
+        Ok(Struct {
             data: Arc::new(Mutex::new(StructData {
                 parent: data.parent.clone(),
                 lookup: data.lookup.clone(),
@@ -197,9 +198,13 @@ impl Struct {
                     .cells
                     .iter()
                     .map(|value| value.clone().materialize())
-                    .collect(),
+                    .collect::<CrushResult<Vec<Value>>>()?,
             })),
-        }
+        })
+    }
+
+    pub fn parent(&self) -> Option<Struct> {
+        self.data.lock().unwrap().parent.clone()
     }
 
     pub fn set_parent(&self, parent: Option<Struct>) {
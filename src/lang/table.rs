@@ -2,6 +2,7 @@ use crate::lang::errors::{argument_error, error, CrushError, CrushResult};
 use crate::lang::stream::CrushStream;
 use crate::lang::value::ValueType;
 use crate::lang::{r#struct::Struct, value::Value};
+use crate::util::distance::closest;
 use crate::util::replace::Replace;
 use time::Duration;
 
@@ -16,11 +17,15 @@ impl Table {
         Table { types, rows }
     }
 
-    pub fn materialize(mut self) -> Table {
-        Table {
+    pub fn materialize(mut self) -> CrushResult<Table> {
+        Ok(Table {
             types: ColumnType::materialize(&self.types),
-            rows: self.rows.drain(..).map(|r| r.materialize()).collect(),
-        }
+            rows: self
+                .rows
+                .drain(..)
+                .map(|r| r.materialize())
+                .collect::<CrushResult<Vec<Row>>>()?,
+        })
     }
 
     pub fn types(&self) -> &[ColumnType] {
@@ -109,10 +114,14 @@ impl Row {
         self.cells.len()
     }
 
-    pub fn materialize(mut self) -> Row {
-        Row {
-            cells: self.cells.drain(..).map(|c| c.materialize()).collect(),
-        }
+    pub fn materialize(mut self) -> CrushResult<Row> {
+        Ok(Row {
+            cells: self
+                .cells
+                .drain(..)
+                .map(|c| c.materialize())
+                .collect::<CrushResult<Vec<Value>>>()?,
+        })
     }
 }
 
@@ -159,17 +168,7 @@ impl ColumnVec for &[ColumnType] {
                 return Ok(idx);
             }
         }
-        argument_error(
-            format!(
-                "Unknown column {}, available columns are {}",
-                needle,
-                self.iter()
-                    .map(|t| t.name.to_string())
-                    .collect::<Vec<String>>()
-                    .join(", "),
-            )
-            .as_str(),
-        )
+        argument_error(unknown_column(self, needle).as_str())
     }
 
     fn find(&self, needle_vec: &[String]) -> CrushResult<usize> {
@@ -183,17 +182,48 @@ impl ColumnVec for &[ColumnType] {
                 }
             }
 
-            error(
-                format!(
-                    "Unknown column {}, available columns are {}",
-                    needle,
-                    self.iter()
-                        .map(|t| t.name.to_string())
-                        .collect::<Vec<String>>()
-                        .join(", "),
-                )
-                .as_str(),
-            )
+            error(unknown_column(self, needle).as_str())
         }
     }
 }
+
+fn unknown_column(columns: &[ColumnType], needle: &str) -> String {
+    let available = columns
+        .iter()
+        .map(|t| t.name.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+    match closest(columns.iter().map(|t| t.name.as_str()), needle) {
+        Some(name) => format!(
+            "Unknown column {}, did you mean {}? Available columns are {}",
+            needle, name, available
+        ),
+        None => format!(
+            "Unknown column {}, available columns are {}",
+            needle, available
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_column_suggestion() {
+        let columns = vec![
+            ColumnType::new("user", ValueType::String),
+            ColumnType::new("file", ValueType::File),
+        ];
+        let columns = columns.as_slice();
+        assert_eq!(columns.find_str("file").unwrap(), 1);
+        assert_eq!(
+            unknown_column(columns, "size"),
+            "Unknown column size, available columns are user, file"
+        );
+        assert_eq!(
+            unknown_column(columns, "usr"),
+            "Unknown column usr, did you mean user? Available columns are user, file"
+        );
+    }
+}
@@ -3,21 +3,26 @@ mod value_type;
 
 use std::cmp::Ordering;
 use std::hash::Hasher;
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
 
 use chrono::{DateTime, Local};
 use regex::Regex;
 
 use crate::lang::errors::{argument_error, mandate, CrushResult};
+use crate::lang::parser::parse_name;
 use crate::lang::r#struct::Struct;
 use crate::lang::scope::Scope;
 use crate::lang::stream::{streams, InputStream, Stream};
 use crate::lang::{
-    binary::BinaryReader, dict::Dict, dict::DictReader, list::List, list::ListReader,
-    table::ColumnType, table::TableReader,
+    binary::BinaryReader, binary::ByteReader, dict::Dict, dict::DictReader, list::List,
+    list::ListReader, table::ColumnType, table::Row, table::TableReader,
+};
+use crate::util::bytes::{bytes_to_integer, integer_to_bytes};
+use crate::util::time::{
+    duration_format, duration_from_seconds, duration_seconds, epoch_seconds, parse_duration,
+    parse_time, time_from_epoch, time_from_epoch_seconds,
 };
-use crate::util::time::duration_format;
 use crate::{
     lang::errors::{error, to_crush_error},
     lang::table::Table,
@@ -61,6 +66,10 @@ pub enum Value {
     Type(ValueType),
 }
 
+/// The number of bytes of a binary that are shown when it is converted to a string, so that
+/// printing a huge binary doesn't flood the terminal.
+pub const BINARY_DISPLAY_LIMIT: usize = 64;
+
 impl ToString for Value {
     fn to_string(&self) -> String {
         match self {
@@ -77,7 +86,17 @@ impl ToString for Value {
             Value::Bool(v) => (if *v { "true" } else { "false" }).to_string(),
             Value::Dict(d) => d.to_string(),
             Value::Float(f) => f.to_string(),
-            Value::Binary(v) => format_buffer(v, true),
+            Value::Binary(v) => {
+                if v.len() > BINARY_DISPLAY_LIMIT {
+                    format!(
+                        "{}… ({} bytes)",
+                        format_buffer(&v[0..BINARY_DISPLAY_LIMIT], true),
+                        v.len()
+                    )
+                } else {
+                    format_buffer(v, true)
+                }
+            }
             Value::Type(t) => t.to_string(),
             Value::Struct(s) => s.to_string(),
             _ => format!("<{}>", self.value_type().to_string()),
@@ -99,7 +118,12 @@ impl Value {
 
     pub fn field(&self, name: &str) -> CrushResult<Option<Value>> {
         Ok(match self {
-            Value::Struct(s) => s.get(name),
+            Value::Struct(s) => s.get(name).or_else(|| {
+                self.value_type()
+                    .fields()
+                    .get(name)
+                    .map(|m| Value::Command(m.as_ref().copy()))
+            }),
             Value::Scope(subenv) => subenv.get(name)?.or_else(|| {
                 self.value_type()
                     .fields()
@@ -169,10 +193,25 @@ impl Value {
             Value::Table(r) => Some(Box::from(TableReader::new(r.clone()))),
             Value::List(l) => Some(Box::from(ListReader::new(l.clone(), "value"))),
             Value::Dict(d) => Some(Box::from(DictReader::new(d.clone()))),
+            Value::Binary(b) => Some(Box::from(ByteReader::new(b.clone()))),
             _ => None,
         }
     }
 
+    /// Check that this value can be hashed, i.e. used as a dict key or compared by hash.
+    /// Hashing a mutable value panics, so this must be called before hashing any value that
+    /// isn't already known to have a hashable type.
+    pub fn check_hashable(&self) -> CrushResult<()> {
+        let value_type = self.value_type();
+        if value_type.is_hashable() {
+            Ok(())
+        } else {
+            argument_error(
+                format!("Values of type {} can't be hashed", value_type.to_string()).as_str(),
+            )
+        }
+    }
+
     pub fn value_type(&self) -> ValueType {
         match self {
             Value::String(_) => ValueType::String,
@@ -233,25 +272,69 @@ impl Value {
         }
     }
 
-    pub fn materialize(self) -> Value {
-        match self {
+    pub fn materialize(self) -> CrushResult<Value> {
+        Ok(match self {
             Value::TableStream(output) => {
                 let mut rows = Vec::new();
-                while let Ok(r) = output.recv() {
-                    rows.push(r.materialize());
+                while let Some(r) = output.recv_optional()? {
+                    rows.push(r.materialize()?);
                 }
                 Value::Table(Table::new(ColumnType::materialize(output.types()), rows))
             }
             Value::BinaryStream(mut s) => {
                 let mut vec = Vec::new();
-                std::io::copy(s.as_mut(), &mut vec).unwrap();
+                to_crush_error(std::io::copy(s.as_mut(), &mut vec))?;
                 Value::Binary(vec)
             }
-            Value::Table(r) => Value::Table(r.materialize()),
-            Value::Dict(d) => Value::Dict(d.materialize()),
-            Value::Struct(r) => Value::Struct(r.materialize()),
-            Value::List(l) => Value::List(l.materialize()),
+            Value::Table(r) => Value::Table(r.materialize()?),
+            Value::Dict(d) => Value::Dict(d.materialize()?),
+            Value::Struct(r) => Value::Struct(r.materialize()?),
+            Value::List(l) => Value::List(l.materialize()?),
             _ => self,
+        })
+    }
+
+    /// The string representation of a scalar value that `Value::from_string` can turn back
+    /// into an equal value. Returns None for values that have no such representation.
+    pub fn to_canonical_string(&self) -> Option<String> {
+        match self {
+            Value::String(s) => Some(s.clone()),
+            Value::File(p) => p.to_str().map(|s| s.to_string()),
+            Value::Glob(g) => Some(g.to_string()),
+            Value::Regex(s, _) => Some(s.clone()),
+            Value::Field(f) => Some(f.join(":")),
+            Value::Binary(b) => String::from_utf8(b.clone()).ok(),
+            Value::Integer(_)
+            | Value::Float(_)
+            | Value::Bool(_)
+            | Value::Time(_)
+            | Value::Duration(_) => Some(self.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Parse a string into a value of the specified type.
+    pub fn from_string(s: &str, t: ValueType) -> CrushResult<Value> {
+        match t {
+            ValueType::String => Ok(Value::string(s)),
+            ValueType::Integer => to_crush_error(s.parse::<i128>()).map(Value::Integer),
+            ValueType::Field => Ok(Value::Field(mandate(
+                parse_name(s),
+                "Invalid field, expected one or more names separated by ':'",
+            )?)),
+            ValueType::Glob => Ok(Value::Glob(Glob::new(s))),
+            ValueType::Regex => Ok(Value::Regex(s.to_string(), to_crush_error(Regex::new(s))?)),
+            ValueType::File => Ok(Value::File(PathBuf::from(s))),
+            ValueType::Float => Ok(Value::Float(to_crush_error(s.parse::<f64>())?)),
+            ValueType::Binary => Ok(Value::Binary(s.bytes().collect())),
+            ValueType::Bool => Ok(Value::Bool(match s.to_lowercase().as_str() {
+                "true" => true,
+                "false" => false,
+                _ => return error(format!("Can't convert value '{}' to boolean", s)),
+            })),
+            ValueType::Time => Ok(Value::Time(parse_time(s)?)),
+            ValueType::Duration => Ok(Value::Duration(parse_duration(s)?)),
+            t => error(format!("Can't parse a string as a {}", t.to_string())),
         }
     }
 
@@ -260,47 +343,212 @@ impl Value {
             return Ok(self);
         }
 
-        match (&self, &new_type) {
-            (Value::Integer(i), ValueType::Bool) => return Ok(Value::Bool(*i != 0)),
-            (Value::Float(f), ValueType::Integer) => return Ok(Value::Integer(*f as i128)),
-            _ => {}
-        }
-
-        let str_val = self.to_string();
-
-        match new_type {
-            ValueType::File => Ok(Value::File(PathBuf::from(str_val.as_str()))),
-            ValueType::Glob => Ok(Value::Glob(Glob::new(str_val.as_str()))),
-            ValueType::Integer => to_crush_error(str_val.parse::<i128>()).map(Value::Integer),
-            ValueType::Field => Ok(Value::Field(vec![str_val])),
-            ValueType::Regex => {
-                to_crush_error(Regex::new(str_val.as_str()).map(|v| Value::Regex(str_val, v)))
+        match (self, &new_type) {
+            (Value::Integer(i), ValueType::Bool) => Ok(Value::Bool(i != 0)),
+            (Value::Bool(b), ValueType::Integer) => Ok(Value::Integer(if b { 1 } else { 0 })),
+            (Value::Integer(i), ValueType::Float) => Ok(Value::Float(i as f64)),
+            (Value::Float(f), ValueType::Integer) => Ok(Value::Integer(f as i128)),
+            (Value::Time(t), ValueType::Integer) => Ok(Value::Integer(t.timestamp() as i128)),
+            (Value::Time(t), ValueType::Float) => Ok(Value::Float(epoch_seconds(&t))),
+            (Value::Integer(i), ValueType::Time) => {
+                if i.abs() > i64::MAX as i128 {
+                    return argument_error(
+                        format!("The epoch time {} is out of range", i).as_str(),
+                    );
+                }
+                Ok(Value::Time(time_from_epoch(i as i64, 0)?))
             }
-            ValueType::Binary => Ok(Value::Binary(str_val.bytes().collect())),
-            ValueType::Float => Ok(Value::Float(to_crush_error(f64::from_str(&str_val))?)),
-            ValueType::Bool => Ok(Value::Bool(match str_val.as_str() {
-                "true" => true,
-                "false" => false,
-                _ => {
-                    return error(format!("Can't convert value '{}' to boolean", str_val).as_str());
+            (Value::Float(f), ValueType::Time) => Ok(Value::Time(time_from_epoch_seconds(f)?)),
+            (Value::Duration(d), ValueType::Float) => Ok(Value::Float(duration_seconds(&d))),
+            (Value::Duration(d), ValueType::Integer) => {
+                Ok(Value::Integer(d.num_milliseconds() as i128))
+            }
+            (Value::Float(f), ValueType::Duration) => {
+                Ok(Value::Duration(duration_from_seconds(f)?))
+            }
+            (Value::Integer(i), ValueType::Duration) => {
+                if i.abs() > (i64::MAX / 1000) as i128 {
+                    return argument_error(format!("The duration {} is out of range", i).as_str());
                 }
-            })),
-            ValueType::String => Ok(Value::String(str_val)),
-            ValueType::Time => error("invalid convert"),
-            ValueType::Duration => Ok(Value::Duration(Duration::seconds(to_crush_error(
-                i64::from_str(&str_val),
-            )?))),
-            ValueType::Command => error("invalid convert"),
-            ValueType::TableStream(_) => error("invalid convert"),
-            ValueType::Table(_) => error("invalid convert"),
-            ValueType::Struct => error("invalid convert"),
-            ValueType::List(_) => error("invalid convert"),
-            ValueType::Dict(_, _) => error("invalid convert"),
-            ValueType::Scope => error("Invalid convert"),
-            ValueType::Empty => error("Invalid convert"),
-            ValueType::Any => error("Invalid convert"),
-            ValueType::BinaryStream => error("invalid convert"),
-            ValueType::Type => error("invalid convert"),
+                Ok(Value::Duration(Duration::milliseconds(i as i64)))
+            }
+            (Value::Integer(i), ValueType::Binary) => {
+                Ok(Value::Binary(integer_to_bytes(i, None, false)?))
+            }
+            (Value::Binary(b), ValueType::Integer) => {
+                Ok(Value::Integer(bytes_to_integer(&b, false)?))
+            }
+            (Value::BinaryStream(mut s), ValueType::Binary) => {
+                let mut vec = Vec::new();
+                to_crush_error(s.read_to_end(&mut vec))?;
+                Ok(Value::Binary(vec))
+            }
+            (Value::BinaryStream(mut s), ValueType::String) => {
+                let mut vec = Vec::new();
+                to_crush_error(s.read_to_end(&mut vec))?;
+                Ok(Value::String(to_crush_error(String::from_utf8(vec))?))
+            }
+            (Value::TableStream(s), ValueType::List(_)) => {
+                if s.types().len() != 1 {
+                    return argument_error(
+                        "Only table streams with exactly one column can be converted to a list",
+                    );
+                }
+                let element_type = s.types()[0].cell_type.clone();
+                let mut elements = Vec::new();
+                while let Ok(row) = s.recv() {
+                    elements.push(row.into_vec().remove(0));
+                }
+                Ok(Value::List(List::new(element_type, elements)))
+            }
+            (Value::String(s), ValueType::List(element_type))
+                if **element_type == ValueType::String =>
+            {
+                Ok(Value::List(List::new(
+                    ValueType::String,
+                    s.split_whitespace().map(Value::string).collect(),
+                )))
+            }
+            (Value::Field(f), ValueType::File) => Ok(Value::File(f.iter().collect())),
+            (Value::Field(f), ValueType::List(element_type))
+                if **element_type == ValueType::String =>
+            {
+                Ok(Value::List(List::new(
+                    ValueType::String,
+                    f.into_iter().map(Value::String).collect(),
+                )))
+            }
+            (Value::Glob(g), ValueType::Regex) => {
+                let pattern = g.to_regex();
+                let re = to_crush_error(Regex::new(&pattern))?;
+                Ok(Value::Regex(pattern, re))
+            }
+            (Value::Regex(_, _), ValueType::Glob) => {
+                argument_error("Regular expressions can't be converted to globs")
+            }
+            (Value::TableStream(s), ValueType::Table(_)) => Value::TableStream(s).materialize(),
+            (Value::Table(t), ValueType::List(element_type))
+                if **element_type == ValueType::Struct =>
+            {
+                Ok(Value::List(List::new(
+                    ValueType::Struct,
+                    t.rows()
+                        .iter()
+                        .map(|r| Value::Struct(r.clone().into_struct(t.types())))
+                        .collect(),
+                )))
+            }
+            (Value::List(l), ValueType::Table(types)) => {
+                let mut elements = l.dump().into_iter();
+                let first = match elements.next() {
+                    None => return Ok(Value::Table(Table::new(types.clone(), vec![]))),
+                    Some(Value::Struct(s)) => s,
+                    Some(_) => {
+                        return argument_error("Only lists of structs can be converted to a table")
+                    }
+                };
+                let types = first.local_signature();
+                let mut rows = vec![first.to_row()];
+                for element in elements {
+                    match element {
+                        Value::Struct(s) if s.local_signature() == types => rows.push(s.to_row()),
+                        _ => return argument_error(
+                            "All structs in the list must have the same fields as the first one",
+                        ),
+                    }
+                }
+                Ok(Value::Table(Table::new(types, rows)))
+            }
+            (Value::Struct(s), ValueType::Dict(_, _)) => {
+                let elements = s.local_elements();
+                let value_type = match elements.first() {
+                    Some((_, first))
+                        if elements
+                            .iter()
+                            .all(|(_, v)| v.value_type() == first.value_type()) =>
+                    {
+                        first.value_type()
+                    }
+                    _ => ValueType::Any,
+                };
+                let dict = Dict::new(ValueType::String, value_type);
+                for (name, value) in elements {
+                    dict.insert(Value::String(name), value)?;
+                }
+                Ok(Value::Dict(dict))
+            }
+            (Value::Dict(d), ValueType::Struct) => {
+                let mut fields = Vec::with_capacity(d.len());
+                for (key, value) in d.elements() {
+                    match key {
+                        Value::String(name) => fields.push((name, value)),
+                        k => {
+                            return argument_error(
+                                format!(
+                                    "Only dicts with string keys can be converted to a struct, found a key of type {}",
+                                    k.value_type().to_string()
+                                )
+                                .as_str(),
+                            )
+                        }
+                    }
+                }
+                Ok(Value::Struct(Struct::new(fields, None)))
+            }
+            (Value::Dict(d), ValueType::Table(_)) => Ok(Value::Table(Table::new(
+                vec![
+                    ColumnType::new("key", d.key_type()),
+                    ColumnType::new("value", d.value_type()),
+                ],
+                d.elements()
+                    .into_iter()
+                    .map(|(k, v)| Row::new(vec![k, v]))
+                    .collect(),
+            ))),
+            (Value::Table(t), ValueType::Dict(_, _)) => {
+                if t.types().len() != 2 {
+                    return argument_error(
+                        "Only tables with exactly two columns can be converted to a dict",
+                    );
+                }
+                let key_type = t.types()[0].cell_type.clone();
+                if !key_type.is_hashable() {
+                    return argument_error(
+                        format!("Can't use values of type {} as keys", key_type.to_string())
+                            .as_str(),
+                    );
+                }
+                let dict = Dict::new(key_type, t.types()[1].cell_type.clone());
+                for row in t.rows() {
+                    let mut cells = row.clone().into_vec();
+                    let value = cells.remove(1);
+                    let key = cells.remove(0);
+                    if !key.value_type().is_hashable() {
+                        return argument_error(
+                            format!(
+                                "Can't use values of type {} as keys",
+                                key.value_type().to_string()
+                            )
+                            .as_str(),
+                        );
+                    }
+                    if dict.get(&key).is_some() {
+                        return argument_error(
+                            format!("Duplicate key {}", key.to_string()).as_str(),
+                        );
+                    }
+                    dict.insert(key, value)?;
+                }
+                Ok(Value::Dict(dict))
+            }
+            (v, _) => match v.to_canonical_string() {
+                Some(s) => Value::from_string(&s, new_type),
+                None => error(format!(
+                    "Can't convert a {} to a {}",
+                    v.value_type().to_string(),
+                    new_type.to_string()
+                )),
+            },
         }
     }
 }
@@ -333,20 +581,6 @@ impl Clone for Value {
     }
 }
 
-fn integer_decode(val: f64) -> (u64, i16, i8) {
-    let bits: u64 = unsafe { std::mem::transmute(val) };
-    let sign: i8 = if bits >> 63 == 0 { 1 } else { -1 };
-    let mut exponent: i16 = ((bits >> 52) & 0x7ff) as i16;
-    let mantissa = if exponent == 0 {
-        (bits & 0xf_ffff_ffff_ffff) << 1
-    } else {
-        (bits & 0xf_ffff_ffff_ffff) | 0x10_0000_0000_0000
-    };
-
-    exponent -= 1023 + 52;
-    (mantissa, exponent, sign)
-}
-
 impl std::hash::Hash for Value {
     fn hash<H: Hasher>(&self, state: &mut H) {
         if !self.value_type().is_hashable() {
@@ -371,12 +605,14 @@ impl std::hash::Hash for Value {
             | Value::List(_)
             | Value::TableStream(_)
             | Value::BinaryStream(_) => panic!("Can't hash output"),
-            Value::Float(v) => {
-                let (m, x, s) = integer_decode(*v);
-                m.hash(state);
-                x.hash(state);
-                s.hash(state);
+            // Integral floats compare equal to integers, so they must hash the same way
+            Value::Float(v)
+                if v.fract() == 0.0 && *v >= i128::MIN as f64 && *v < i128::MAX as f64 =>
+            {
+                (*v as i128).hash(state)
             }
+            Value::Float(v) if v.is_nan() => f64::NAN.to_bits().hash(state),
+            Value::Float(v) => v.to_bits().hash(state),
             Value::Empty() => {}
             Value::Type(v) => v.to_string().hash(state),
         }
@@ -412,6 +648,12 @@ impl std::cmp::PartialEq for Value {
             (Value::Dict(val1), Value::Dict(val2)) => val1 == val2,
             (Value::Bool(val1), Value::Bool(val2)) => val1 == val2,
             (Value::Float(val1), Value::Float(val2)) => val1 == val2,
+            (Value::Integer(val1), Value::Float(val2)) => {
+                compare_integer_float(*val1, *val2) == Some(Ordering::Equal)
+            }
+            (Value::Float(val1), Value::Integer(val2)) => {
+                compare_integer_float(*val2, *val1) == Some(Ordering::Equal)
+            }
             (Value::Binary(val1), Value::Binary(val2)) => val1 == val2,
             _ => false,
         }
@@ -423,8 +665,92 @@ pub enum Alignment {
     Right,
 }
 
+/// Compare an integer with a float exactly. Converting the integer to a float would round
+/// integers beyond 2^53 and make distinct numbers compare as equal.
+fn compare_integer_float(i: i128, f: f64) -> Option<Ordering> {
+    // 2^127, the smallest float that is larger than every i128
+    let limit = 170141183460469231731687303715884105728.0f64;
+    if f.is_nan() {
+        None
+    } else if f >= limit {
+        Some(Ordering::Less)
+    } else if f < -limit {
+        Some(Ordering::Greater)
+    } else {
+        let whole = f.trunc();
+        // Exact, since whole is integral and within the range of i128
+        Some(i.cmp(&(whole as i128)).then_with(|| {
+            if f > whole {
+                Ordering::Less
+            } else if f < whole {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        }))
+    }
+}
+
+/// The position of a value's type in the total order used by Value::total_cmp.
+fn type_rank(value: &Value) -> u8 {
+    match value {
+        Value::Empty() => 0,
+        Value::Bool(_) => 1,
+        Value::Integer(_) | Value::Float(_) => 2,
+        Value::String(_) => 3,
+        Value::Time(_) => 4,
+        Value::Duration(_) => 5,
+        Value::Field(_) => 6,
+        Value::Glob(_) => 7,
+        Value::Regex(_, _) => 8,
+        Value::File(_) => 9,
+        Value::Binary(_) => 10,
+        Value::List(_) => 11,
+        Value::Dict(_) => 12,
+        Value::Struct(_) => 13,
+        Value::Table(_) => 14,
+        Value::Type(_) => 15,
+        Value::Scope(_) => 16,
+        Value::Command(_) => 17,
+        Value::TableStream(_) => 18,
+        Value::BinaryStream(_) => 19,
+    }
+}
+
+impl Value {
+    /// A total order over all values, for sorting columns that may contain mixed types.
+    ///
+    /// Values are first ordered by type, with empty values first. Integers and floats are
+    /// compared numerically with each other, and NaN sorts after every other number. Values
+    /// of the same type are ordered like partial_cmp, except that values it can't order,
+    /// like commands and streams, compare as equal so that a stable sort keeps them in
+    /// their original order.
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        type_rank(self)
+            .cmp(&type_rank(other))
+            .then_with(|| match (self, other) {
+                (Value::Float(a), Value::Float(b)) if a.is_nan() || b.is_nan() => {
+                    a.is_nan().cmp(&b.is_nan())
+                }
+                (Value::Float(a), _) if a.is_nan() => Ordering::Greater,
+                (_, Value::Float(b)) if b.is_nan() => Ordering::Less,
+                _ => self.partial_cmp(other).unwrap_or(Ordering::Equal),
+            })
+    }
+}
+
 impl std::cmp::PartialOrd for Value {
     fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Integer(val1), Value::Float(val2)) => {
+                return compare_integer_float(*val1, *val2)
+            }
+            (Value::Float(val1), Value::Integer(val2)) => {
+                return compare_integer_float(*val2, *val1).map(Ordering::reverse)
+            }
+            _ => {}
+        }
+
         let t1 = self.value_type();
         let t2 = other.value_type();
         if t1 != t2 {
@@ -483,6 +809,9 @@ impl Help for Value {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::time::{
+        duration_format_opts, parse_duration, parse_time_with_format, DurationStyle,
+    };
 
     #[test]
     fn text_casts() {
@@ -501,6 +830,540 @@ mod tests {
             Value::string("fad").convert(ValueType::Field).is_err(),
             false
         );
+        assert!(Value::string("true").convert(ValueType::Bool).is_ok());
+        assert!(Value::string("False").convert(ValueType::Bool).is_ok());
+        assert!(Value::string("1").convert(ValueType::Bool).is_err());
+        assert!(Value::string("").convert(ValueType::Bool).is_err());
+    }
+
+    #[test]
+    fn numeric_and_bool_casts() {
+        match Value::Integer(7).convert(ValueType::Float) {
+            Ok(Value::Float(f)) => assert!((f - 7.0).abs() < f64::EPSILON),
+            _ => panic!("Expected a float"),
+        }
+        match Value::Float(-3.7).convert(ValueType::Integer) {
+            Ok(Value::Integer(i)) => assert_eq!(i, -3),
+            _ => panic!("Expected an integer"),
+        }
+        match Value::Bool(true).convert(ValueType::String) {
+            Ok(Value::String(s)) => assert_eq!(s, "true"),
+            _ => panic!("Expected a string"),
+        }
+        match Value::Bool(false).convert(ValueType::String) {
+            Ok(Value::String(s)) => assert_eq!(s, "false"),
+            _ => panic!("Expected a string"),
+        }
+        assert!(Value::Float(1.5).convert(ValueType::Bool).is_err());
+    }
+
+    #[test]
+    fn string_mediated_casts() {
+        match Value::Field(vec!["a".to_string(), "b".to_string()]).convert(ValueType::String) {
+            Ok(Value::String(s)) => assert_eq!(s, "a:b"),
+            _ => panic!("Expected a string"),
+        }
+        match Value::string("a:b").convert(ValueType::Field) {
+            Ok(Value::Field(f)) => assert_eq!(f, vec!["a".to_string(), "b".to_string()]),
+            _ => panic!("Expected a field"),
+        }
+        assert!(Value::Float(1.5).convert(ValueType::Glob).is_ok());
+        assert!(Value::Integer(3).convert(ValueType::Regex).is_ok());
+        assert!(Value::string("a::b").convert(ValueType::Field).is_err());
+        assert!(Value::List(List::new(ValueType::Integer, vec![]))
+            .convert(ValueType::Integer)
+            .is_err());
+    }
+
+    #[test]
+    fn bool_casts() {
+        match Value::Bool(true).convert(ValueType::Integer) {
+            Ok(Value::Integer(i)) => assert_eq!(i, 1),
+            _ => panic!("Expected an integer"),
+        }
+        match Value::Bool(false).convert(ValueType::Integer) {
+            Ok(Value::Integer(i)) => assert_eq!(i, 0),
+            _ => panic!("Expected an integer"),
+        }
+        match Value::Integer(-4).convert(ValueType::Bool) {
+            Ok(Value::Bool(b)) => assert!(b),
+            _ => panic!("Expected a bool"),
+        }
+        match Value::Integer(0).convert(ValueType::Bool) {
+            Ok(Value::Bool(b)) => assert!(!b),
+            _ => panic!("Expected a bool"),
+        }
+        match Value::string("TRUE").convert(ValueType::Bool) {
+            Ok(Value::Bool(b)) => assert!(b),
+            _ => panic!("Expected a bool"),
+        }
+        for b in &[true, false] {
+            match Value::Bool(*b)
+                .convert(ValueType::String)
+                .and_then(|v| v.convert(ValueType::Bool))
+            {
+                Ok(Value::Bool(res)) => assert_eq!(res, *b),
+                _ => panic!("Expected a bool"),
+            }
+        }
+        assert!(Value::string("yes").convert(ValueType::Bool).is_err());
+    }
+
+    #[test]
+    fn integer_binary_casts() {
+        match Value::Integer(-129).convert(ValueType::Binary) {
+            Ok(Value::Binary(b)) => assert_eq!(b, vec![0xff, 0x7f]),
+            _ => panic!("Expected a binary"),
+        }
+        match Value::Binary(vec![0x01, 0x00]).convert(ValueType::Integer) {
+            Ok(Value::Integer(i)) => assert_eq!(i, 256),
+            _ => panic!("Expected an integer"),
+        }
+        assert!(Value::Binary(vec![0; 17])
+            .convert(ValueType::Integer)
+            .is_err());
+    }
+
+    #[test]
+    fn mixed_numeric_comparisons() {
+        assert!(Value::Integer(2) < Value::Float(2.5));
+        assert!(Value::Float(1.5) < Value::Integer(2));
+        assert!(Value::Integer(2) == Value::Float(2.0));
+        assert!(Value::Float(2.0) == Value::Integer(2));
+        assert!(Value::Integer(2) != Value::Float(2.5));
+        assert_eq!(Value::Integer(2).partial_cmp(&Value::Float(f64::NAN)), None);
+        assert_eq!(Value::Float(f64::NAN).partial_cmp(&Value::Integer(2)), None);
+    }
+
+    #[test]
+    fn total_order() {
+        let mut values = [
+            Value::string("a"),
+            Value::Float(f64::NAN),
+            Value::Integer(3),
+            Value::Empty(),
+            Value::Float(1.5),
+            Value::Bool(true),
+        ];
+        values.sort_by(|a, b| a.total_cmp(b));
+        let types: Vec<ValueType> = values.iter().map(|v| v.value_type()).collect();
+        assert!(
+            types
+                == [
+                    ValueType::Empty,
+                    ValueType::Bool,
+                    ValueType::Float,
+                    ValueType::Integer,
+                    ValueType::Float,
+                    ValueType::String,
+                ]
+        );
+        assert!(values[2] == Value::Float(1.5));
+        assert!(values[3] == Value::Integer(3));
+        match values[4] {
+            Value::Float(f) => assert!(f.is_nan()),
+            _ => panic!("Expected NaN"),
+        }
+        assert_eq!(
+            Value::Float(f64::NAN).total_cmp(&Value::Float(f64::NAN)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn exact_numeric_comparisons() {
+        let big = (1i128 << 53) + 1;
+        let neighbor = (1i128 << 53) as f64;
+        assert!(Value::Integer(big) != Value::Float(neighbor));
+        assert!(Value::Integer(big) > Value::Float(neighbor));
+        assert!(Value::Float(neighbor) < Value::Integer(big));
+        assert!(Value::Integer(big - 1) == Value::Float(neighbor));
+        assert!(Value::Integer(-2) < Value::Float(-1.5));
+        assert!(Value::Integer(-1) > Value::Float(-1.5));
+        assert!(Value::Integer(i128::MAX) < Value::Float(i128::MAX as f64));
+        assert!(Value::Integer(i128::MIN) == Value::Float(i128::MIN as f64));
+        assert!(Value::Integer(i128::MIN) > Value::Float(f64::NEG_INFINITY));
+        assert!(Value::Integer(0) == Value::Float(-0.0));
+    }
+
+    /// A reader that returns some data and then fails.
+    #[derive(Debug)]
+    struct FailingReader {
+        done: bool,
+    }
+
+    impl Read for FailingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.done {
+                Err(std::io::Error::other("broken"))
+            } else {
+                self.done = true;
+                buf[0] = 1;
+                Ok(1)
+            }
+        }
+    }
+
+    impl BinaryReader for FailingReader {
+        fn clone(&self) -> Box<dyn BinaryReader + Send + Sync> {
+            Box::from(FailingReader { done: self.done })
+        }
+    }
+
+    #[test]
+    fn materialize_surfaces_read_errors() {
+        let failing = || Value::BinaryStream(Box::from(FailingReader { done: false }));
+        assert!(failing().materialize().is_err());
+        assert!(failing().convert(ValueType::Binary).is_err());
+        assert!(failing().convert(ValueType::String).is_err());
+    }
+
+    #[test]
+    fn materialize_surfaces_crashed_streams() {
+        let (output, input) = streams(vec![ColumnType::new("value", ValueType::Integer)]);
+        let _ = std::thread::spawn(move || {
+            output.send(Row::new(vec![Value::Integer(1)])).unwrap();
+            panic!("crash");
+        })
+        .join();
+        assert!(Value::TableStream(input).materialize().is_err());
+
+        let (output, input) = streams(vec![ColumnType::new("value", ValueType::Integer)]);
+        output.send(Row::new(vec![Value::Integer(1)])).unwrap();
+        drop(output);
+        match Value::TableStream(input).materialize() {
+            Ok(Value::Table(t)) => assert_eq!(t.rows().len(), 1),
+            _ => panic!("Expected a table"),
+        }
+    }
+
+    #[test]
+    fn binary_display_is_truncated() {
+        let short = Value::Binary(b"hello".to_vec());
+        assert_eq!(short.to_string(), "hello");
+        let long = Value::Binary(vec![b'a'; 1000]);
+        assert_eq!(
+            long.to_string(),
+            format!("{}… (1000 bytes)", "a".repeat(BINARY_DISPLAY_LIMIT))
+        );
+    }
+
+    #[test]
+    fn binary_stream_casts() {
+        let data = vec![104u8, 105];
+        match Value::BinaryStream(<dyn BinaryReader>::vec(&data)).convert(ValueType::Binary) {
+            Ok(Value::Binary(b)) => assert_eq!(b, data),
+            _ => panic!("Expected a binary"),
+        }
+        match Value::BinaryStream(<dyn BinaryReader>::vec(&data)).convert(ValueType::String) {
+            Ok(Value::String(s)) => assert_eq!(s, "hi"),
+            _ => panic!("Expected a string"),
+        }
+    }
+
+    #[test]
+    fn text_list_casts() {
+        match Value::string(" a  b\tc\n").convert(ValueType::List(Box::from(ValueType::String))) {
+            Ok(Value::List(l)) => assert!(
+                l.dump() == vec![Value::string("a"), Value::string("b"), Value::string("c")]
+            ),
+            _ => panic!("Expected a list"),
+        }
+        match Value::string("").convert(ValueType::List(Box::from(ValueType::String))) {
+            Ok(Value::List(l)) => assert_eq!(l.len(), 0),
+            _ => panic!("Expected a list"),
+        }
+    }
+
+    #[test]
+    fn table_list_casts() {
+        let types = vec![
+            ColumnType::new("name", ValueType::String),
+            ColumnType::new("size", ValueType::Integer),
+        ];
+        let table = Table::new(
+            types.clone(),
+            vec![
+                Row::new(vec![Value::string("a"), Value::Integer(1)]),
+                Row::new(vec![Value::string("b"), Value::Integer(2)]),
+            ],
+        );
+        let list = match Value::Table(table).convert(ValueType::List(Box::from(ValueType::Struct)))
+        {
+            Ok(Value::List(l)) => l,
+            _ => panic!("Expected a list"),
+        };
+        assert_eq!(list.len(), 2);
+        match Value::List(list).convert(ValueType::Table(vec![])) {
+            Ok(Value::Table(t)) => {
+                assert_eq!(t.types(), &types[..]);
+                assert_eq!(t.rows().len(), 2);
+            }
+            _ => panic!("Expected a table"),
+        }
+
+        let mismatched = List::new(
+            ValueType::Struct,
+            vec![
+                Value::Struct(Struct::new(
+                    vec![("a".to_string(), Value::Integer(1))],
+                    None,
+                )),
+                Value::Struct(Struct::new(
+                    vec![("b".to_string(), Value::Integer(1))],
+                    None,
+                )),
+            ],
+        );
+        assert!(Value::List(mismatched)
+            .convert(ValueType::Table(vec![]))
+            .is_err());
+    }
+
+    #[test]
+    fn dict_table_casts() {
+        let dict_type = ValueType::Dict(Box::from(ValueType::Any), Box::from(ValueType::Any));
+
+        let empty = Dict::new(ValueType::String, ValueType::Integer);
+        let table = match Value::Dict(empty).convert(ValueType::Table(vec![])) {
+            Ok(Value::Table(t)) => t,
+            _ => panic!("Expected a table"),
+        };
+        assert!(table.rows().is_empty());
+        match Value::Table(table).convert(dict_type.clone()) {
+            Ok(Value::Dict(d)) => {
+                assert_eq!(d.len(), 0);
+                assert_eq!(
+                    d.dict_type(),
+                    ValueType::Dict(Box::from(ValueType::String), Box::from(ValueType::Integer))
+                );
+            }
+            _ => panic!("Expected a dict"),
+        }
+
+        let types = vec![
+            ColumnType::new("name", ValueType::String),
+            ColumnType::new("size", ValueType::Integer),
+        ];
+        let duplicates = Table::new(
+            types,
+            vec![
+                Row::new(vec![Value::string("a"), Value::Integer(1)]),
+                Row::new(vec![Value::string("a"), Value::Integer(2)]),
+            ],
+        );
+        assert!(Value::Table(duplicates).convert(dict_type).is_err());
+    }
+
+    #[test]
+    fn struct_dict_casts() {
+        let s = Struct::new(
+            vec![
+                ("a".to_string(), Value::Integer(1)),
+                ("b".to_string(), Value::Integer(2)),
+            ],
+            None,
+        );
+        let d = match Value::Struct(s.clone()).convert(ValueType::Dict(
+            Box::from(ValueType::String),
+            Box::from(ValueType::Any),
+        )) {
+            Ok(Value::Dict(d)) => d,
+            _ => panic!("Expected a dict"),
+        };
+        assert_eq!(
+            d.dict_type(),
+            ValueType::Dict(Box::from(ValueType::String), Box::from(ValueType::Integer))
+        );
+        assert!(d.get(&Value::string("b")) == Some(Value::Integer(2)));
+        match Value::Dict(d).convert(ValueType::Struct) {
+            Ok(Value::Struct(res)) => assert!(res == s),
+            _ => panic!("Expected a struct"),
+        }
+
+        let d = Dict::new(ValueType::Integer, ValueType::Integer);
+        d.insert(Value::Integer(1), Value::Integer(2)).unwrap();
+        assert!(Value::Dict(d).convert(ValueType::Struct).is_err());
+    }
+
+    #[test]
+    fn float_hashing() {
+        let d = Dict::new(ValueType::Float, ValueType::String);
+        d.insert(Value::Float(1.5), Value::string("a")).unwrap();
+        d.insert(Value::Float(-0.0), Value::string("b")).unwrap();
+        d.insert(Value::Float(f64::INFINITY), Value::string("c"))
+            .unwrap();
+        assert!(d.get(&Value::Float(1.5)) == Some(Value::string("a")));
+        assert!(d.get(&Value::Float(0.0)) == Some(Value::string("b")));
+        assert!(d.get(&Value::Float(f64::INFINITY)) == Some(Value::string("c")));
+        assert!(d.get(&Value::Float(2.5)).is_none());
+        assert_eq!(d.len(), 3);
+    }
+
+    #[test]
+    fn duration_numeric_casts() {
+        match Value::Duration(Duration::milliseconds(1500)).convert(ValueType::Float) {
+            Ok(Value::Float(f)) => assert!((f - 1.5).abs() < f64::EPSILON),
+            _ => panic!("Expected a float"),
+        }
+        match Value::Duration(Duration::microseconds(-1500)).convert(ValueType::Float) {
+            Ok(Value::Float(f)) => assert!((f + 0.0015).abs() < f64::EPSILON),
+            _ => panic!("Expected a float"),
+        }
+        match Value::Duration(Duration::microseconds(1999)).convert(ValueType::Integer) {
+            Ok(Value::Integer(i)) => assert_eq!(i, 1),
+            _ => panic!("Expected an integer"),
+        }
+        match Value::Duration(Duration::microseconds(-1999)).convert(ValueType::Integer) {
+            Ok(Value::Integer(i)) => assert_eq!(i, -1),
+            _ => panic!("Expected an integer"),
+        }
+        match Value::Integer(-2500).convert(ValueType::Duration) {
+            Ok(Value::Duration(d)) => assert_eq!(d, Duration::milliseconds(-2500)),
+            _ => panic!("Expected a duration"),
+        }
+        match Value::Float(-2.25).convert(ValueType::Duration) {
+            Ok(Value::Duration(d)) => assert_eq!(d, Duration::milliseconds(-2250)),
+            _ => panic!("Expected a duration"),
+        }
+        assert!(Value::Float(f64::INFINITY)
+            .convert(ValueType::Duration)
+            .is_err());
+    }
+
+    #[test]
+    fn glob_regex_casts() {
+        match Value::Glob(Glob::new("%.rs")).convert(ValueType::Regex) {
+            Ok(Value::Regex(_, re)) => {
+                assert!(re.is_match("main.rs"));
+                assert!(!re.is_match("src/main.rs"));
+            }
+            _ => panic!("Expected a regex"),
+        }
+        assert!(Value::Regex(".*".to_string(), Regex::new(".*").unwrap())
+            .convert(ValueType::Glob)
+            .is_err());
+    }
+
+    #[test]
+    fn field_casts() {
+        let field = vec!["a".to_string(), "b".to_string()];
+        match Value::string("a").convert(ValueType::Field) {
+            Ok(Value::Field(f)) => assert_eq!(f, vec!["a".to_string()]),
+            _ => panic!("Expected a field"),
+        }
+        match Value::Field(field.clone()).convert(ValueType::File) {
+            Ok(Value::File(p)) => assert_eq!(p, PathBuf::from("a").join("b")),
+            _ => panic!("Expected a file"),
+        }
+        match Value::Field(field).convert(ValueType::List(Box::from(ValueType::String))) {
+            Ok(Value::List(l)) => assert!(l.dump() == vec![Value::string("a"), Value::string("b")]),
+            _ => panic!("Expected a list"),
+        }
+        assert!(Value::string("").convert(ValueType::Field).is_err());
+    }
+
+    #[test]
+    fn text_to_duration_casts() {
+        let durations = vec![
+            Duration::microseconds(0),
+            Duration::microseconds(1),
+            Duration::microseconds(100),
+            Duration::milliseconds(1),
+            Duration::milliseconds(1000),
+            Duration::milliseconds(1000 * 61),
+            Duration::milliseconds(1000 * 3601),
+            Duration::milliseconds(1000 * (3600 * 24 * 3 + 1)),
+            Duration::milliseconds(1000 * (3600 * 24 * 365 * 10 + 1)),
+            Duration::milliseconds(-1000 * 3601),
+        ];
+        for d in durations {
+            match Value::String(duration_format(&d)).convert(ValueType::Duration) {
+                Ok(Value::Duration(res)) => assert_eq!(res, d),
+                _ => panic!("Expected a duration"),
+            }
+        }
+
+        let suffixed = vec![
+            ("90s", Duration::seconds(90)),
+            ("5m", Duration::minutes(5)),
+            ("2h", Duration::hours(2)),
+            ("1d", Duration::days(1)),
+            ("-1d", Duration::days(-1)),
+            ("90", Duration::seconds(90)),
+            ("500ms", Duration::milliseconds(500)),
+            ("1h30m", Duration::minutes(90)),
+            ("1d2h3m4s5ms", Duration::milliseconds(93_784_005)),
+            ("-2m30s", Duration::seconds(-150)),
+        ];
+        for (s, d) in suffixed {
+            match Value::string(s).convert(ValueType::Duration) {
+                Ok(Value::Duration(res)) => assert_eq!(res, d),
+                _ => panic!("Expected a duration"),
+            }
+        }
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("1:2:3:4").is_err());
+        assert!(parse_duration("5w").is_err());
+        assert!(parse_duration("1h30").is_err());
+        assert!(parse_duration("h30m").is_err());
+        assert!(parse_duration("1h-30m").is_err());
+        assert!(parse_duration("1hm").is_err());
+        assert!(parse_duration("1.0000000001").is_err());
+    }
+
+    #[test]
+    fn time_epoch_casts() {
+        let t = match Value::Integer(1_600_000_000).convert(ValueType::Time) {
+            Ok(Value::Time(t)) => t,
+            _ => panic!("Expected a time"),
+        };
+        match Value::Time(t).convert(ValueType::Integer) {
+            Ok(Value::Integer(i)) => assert_eq!(i, 1_600_000_000),
+            _ => panic!("Expected an integer"),
+        }
+
+        let t = match Value::Float(1_600_000_000.123456).convert(ValueType::Time) {
+            Ok(Value::Time(t)) => t,
+            _ => panic!("Expected a time"),
+        };
+        assert_eq!(t.timestamp_subsec_micros(), 123456);
+        match Value::Time(t).convert(ValueType::Float) {
+            Ok(Value::Float(f)) => assert!((f - 1_600_000_000.123456).abs() < 0.000001),
+            _ => panic!("Expected a float"),
+        }
+
+        match Value::Float(-1.5).convert(ValueType::Time) {
+            Ok(Value::Time(t)) => {
+                assert_eq!(t.timestamp(), -2);
+                assert_eq!(t.timestamp_subsec_micros(), 500000);
+            }
+            _ => panic!("Expected a time"),
+        }
+        assert!(Value::Float(f64::NAN).convert(ValueType::Time).is_err());
+    }
+
+    #[test]
+    fn text_to_time_casts() {
+        assert!(Value::string("2021-03-01").convert(ValueType::Time).is_ok());
+        assert!(Value::string("2021-03-01 12:34:56")
+            .convert(ValueType::Time)
+            .is_ok());
+        assert!(Value::string("2021-03-01T12:34:56+02:00")
+            .convert(ValueType::Time)
+            .is_ok());
+        assert!(Value::string("2021-02-30")
+            .convert(ValueType::Time)
+            .is_err());
+        assert!(Value::string("2021-03-01T25:34:56+02:00")
+            .convert(ValueType::Time)
+            .is_err());
+        assert!(Value::string("2021-03-01T12:34:56")
+            .convert(ValueType::Time)
+            .is_err());
+        assert!(parse_time_with_format("24/12/2020 18:30", "%d/%m/%Y %H:%M").is_ok());
+        assert!(parse_time_with_format("2020-12-24", "%d/%m/%Y").is_err());
     }
 
     #[test]
@@ -544,5 +1407,39 @@ mod tests {
             )),
             "10y0d0:00:01".to_string()
         );
+        assert_eq!(
+            duration_format(&Duration::hours(-1)),
+            "-1:00:00".to_string()
+        );
+        assert_eq!(
+            duration_format(&Duration::milliseconds(-1500)),
+            "-1.5".to_string()
+        );
+        assert_eq!(
+            duration_format(&Duration::seconds(-(3600 * 24 * 3 + 1))),
+            "-3d0:00:01".to_string()
+        );
+    }
+
+    #[test]
+    fn test_duration_format_opts() {
+        let cases = [
+            (Duration::seconds(0), "0", "PT0S", "0"),
+            (Duration::minutes(90), "1:30:00", "PT1H30M", "5400"),
+            (Duration::minutes(-90), "-1:30:00", "-PT1H30M", "-5400"),
+            (Duration::milliseconds(-1500), "-1.5", "-PT1.5S", "-1.5"),
+            (Duration::days(2), "2d0:00:00", "P2D", "172800"),
+            (
+                Duration::days(366) + Duration::nanoseconds(1),
+                "1y1d0:00:00",
+                "P366DT0.000000001S",
+                "31622400.000000001",
+            ),
+        ];
+        for (d, human, iso, seconds) in cases.iter() {
+            assert_eq!(duration_format_opts(d, DurationStyle::Human), *human);
+            assert_eq!(duration_format_opts(d, DurationStyle::Iso), *iso);
+            assert_eq!(duration_format_opts(d, DurationStyle::Seconds), *seconds);
+        }
     }
 }
@@ -1,13 +1,9 @@
 use crate::lang::command::Command;
-use crate::lang::errors::{error, mandate, to_crush_error, CrushResult};
 use crate::lang::help::Help;
-use crate::lang::parser::parse_name;
 use crate::lang::{table::ColumnType, value::Value};
 use crate::lib::types;
-use crate::util::glob::Glob;
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
-use regex::Regex;
 use std::cmp::max;
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
@@ -56,7 +52,9 @@ impl ValueType {
             ValueType::Table(_) => &types::table::METHODS,
             ValueType::TableStream(_) => &types::table_stream::METHODS,
             ValueType::Binary => &types::binary::METHODS,
+            ValueType::BinaryStream => &types::binary_stream::METHODS,
             ValueType::Scope => &types::scope::METHODS,
+            ValueType::Struct => &types::r#struct::METHODS,
             _ => &EMPTY_METHODS,
         }
     }
@@ -111,23 +109,6 @@ impl ValueType {
     pub fn is_comparable(&self) -> bool {
         self.is_hashable()
     }
-
-    pub fn parse(&self, s: &str) -> CrushResult<Value> {
-        match self {
-            ValueType::String => Ok(Value::string(s)),
-            ValueType::Integer => match s.parse::<i128>() {
-                Ok(n) => Ok(Value::Integer(n)),
-                Err(e) => error(e.to_string().as_str()),
-            },
-            ValueType::Field => Ok(Value::Field(mandate(parse_name(s), "Invalid field name")?)),
-            ValueType::Glob => Ok(Value::Glob(Glob::new(s))),
-            ValueType::Regex => Ok(Value::Regex(s.to_string(), to_crush_error(Regex::new(s))?)),
-            ValueType::File => Ok(Value::string(s)),
-            ValueType::Float => Ok(Value::Float(to_crush_error(s.parse::<f64>())?)),
-            ValueType::Bool => Ok(Value::Bool(to_crush_error(s.parse::<bool>())?)),
-            _ => error("Failed to parse cell"),
-        }
-    }
 }
 
 impl Help for ValueType {
@@ -6,47 +6,52 @@ use crate::lang::value::Value;
 use crate::lang::value::ValueType;
 use std::cmp::Ordering;
 
+/// The comparison operators understood by compare.
+pub const OPERATORS: [&str; 6] = ["==", "!=", "<", "<=", ">", ">="];
+
+/// Compare two values using one of the comparison operators. Equality works for values of
+/// any type, the other operators fail if the values can't be ordered.
+pub fn compare(operator: &str, l: &Value, r: &Value) -> CrushResult<bool> {
+    match operator {
+        "==" => Ok(l.eq(r)),
+        "!=" => Ok(!l.eq(r)),
+        _ => match l.partial_cmp(r) {
+            Some(ordering) => match operator {
+                "<" => Ok(ordering == Ordering::Less),
+                "<=" => Ok(ordering != Ordering::Greater),
+                ">" => Ok(ordering == Ordering::Greater),
+                ">=" => Ok(ordering != Ordering::Less),
+                _ => argument_error(format!("Unknown comparison operator {}", operator).as_str()),
+            },
+            None => argument_error(
+                format!(
+                    "Values of type {} and {} can't be compared with each other",
+                    l.value_type().to_string(),
+                    r.value_type().to_string(),
+                )
+                .as_str(),
+            ),
+        },
+    }
+}
+
 macro_rules! cmp {
     ($name:ident, $op:expr) => {
         pub fn $name(mut context: ExecutionContext) -> CrushResult<()> {
             context.arguments.check_len(2)?;
             let l = context.arguments.value(0)?;
             let r = context.arguments.value(1)?;
-            match l.partial_cmp(&r) {
-                Some(ordering) => context.output.send(Value::Bool($op(ordering))),
-                None => {
-                    return argument_error(
-                        format!(
-                            "Values of type {} and {} can't be compared with each other",
-                            l.value_type().to_string(),
-                            r.value_type().to_string(),
-                        )
-                        .as_str(),
-                    )
-                }
-            }
+            context.output.send(Value::Bool(compare($op, &l, &r)?))
         }
     };
 }
 
-cmp!(gt, |o| o == Ordering::Greater);
-cmp!(lt, |o| o == Ordering::Less);
-cmp!(gte, |o| o != Ordering::Less);
-cmp!(lte, |o| o != Ordering::Greater);
-
-pub fn eq(mut context: ExecutionContext) -> CrushResult<()> {
-    context.arguments.check_len(2)?;
-    let l = context.arguments.value(0)?;
-    let r = context.arguments.value(1)?;
-    context.output.send(Value::Bool(l.eq(&r)))
-}
-
-pub fn neq(mut context: ExecutionContext) -> CrushResult<()> {
-    context.arguments.check_len(2)?;
-    let l = context.arguments.value(0)?;
-    let r = context.arguments.value(1)?;
-    context.output.send(Value::Bool(!l.eq(&r)))
-}
+cmp!(gt, ">");
+cmp!(lt, "<");
+cmp!(gte, ">=");
+cmp!(lte, "<=");
+cmp!(eq, "==");
+cmp!(neq, "!=");
 
 pub fn not(mut context: ExecutionContext) -> CrushResult<()> {
     context.arguments.check_len(1)?;
@@ -127,3 +132,22 @@ pub fn declare(root: &Scope) -> CrushResult<()> {
     )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let one = Value::Integer(1);
+        let two = Value::Integer(2);
+        assert!(compare("<", &one, &two).unwrap());
+        assert!(compare("<=", &two, &two).unwrap());
+        assert!(!compare(">", &one, &two).unwrap());
+        assert!(compare(">=", &two, &one).unwrap());
+        assert!(compare("!=", &one, &two).unwrap());
+        assert!(!compare("==", &one, &Value::string("1")).unwrap());
+        assert!(compare("<", &one, &Value::string("1")).is_err());
+        assert!(compare("=~", &one, &two).is_err());
+    }
+}
@@ -40,6 +40,32 @@ struct From {
     trim: Option<char>,
 }
 
+/// Split a line into cells and parse each one according to the type of its column. Cells are
+/// parsed like strings converted to the column type, so e.g. file columns contain files and
+/// booleans are case insensitive.
+fn parse_line(
+    line: &str,
+    separator: char,
+    trim: Option<char>,
+    columns: &[ColumnType],
+) -> CrushResult<Row> {
+    let split: Vec<&str> = line
+        .split(separator)
+        .map(|s| trim.map(|c| s.trim_matches(c)).unwrap_or(s))
+        .collect();
+
+    if split.len() != columns.len() {
+        return error("csv: Wrong number of columns in CSV file");
+    }
+
+    split
+        .iter()
+        .zip(columns.iter())
+        .map(|(s, t)| Value::from_string(s, t.cell_type.clone()))
+        .collect::<Result<Vec<Value>, CrushError>>()
+        .map(Row::new)
+}
+
 fn from(context: ExecutionContext) -> CrushResult<()> {
     let cfg: From = From::parse(context.arguments, &context.printer)?;
     let columns = cfg
@@ -68,32 +94,7 @@ fn from(context: ExecutionContext) -> CrushResult<()> {
             continue;
         }
         let line_without_newline = &line[0..line.len() - 1];
-        let mut split: Vec<&str> = line_without_newline
-            .split(separator)
-            .map(|s| trim.map(|c| s.trim_matches(c)).unwrap_or(s))
-            .collect();
-
-        if split.len() != columns.len() {
-            return error("csv: Wrong number of columns in CSV file");
-        }
-
-        if let Some(trim) = trim {
-            split = split.iter().map(|s| s.trim_matches(trim)).collect();
-        }
-
-        match split
-            .iter()
-            .zip(columns.iter())
-            .map(|(s, t)| t.cell_type.parse(*s))
-            .collect::<Result<Vec<Value>, CrushError>>()
-        {
-            Ok(cells) => {
-                let _ = output.send(Row::new(cells));
-            }
-            Err(err) => {
-                return Err(err);
-            }
-        }
+        let _ = output.send(parse_line(line_without_newline, separator, trim, &columns)?);
     }
     Ok(())
 }
@@ -108,3 +109,24 @@ pub fn declare(root: &mut ScopeLoader) -> CrushResult<()> {
     )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_line() {
+        let columns = vec![
+            ColumnType::new("name", ValueType::String),
+            ColumnType::new("path", ValueType::File),
+            ColumnType::new("active", ValueType::Bool),
+        ];
+        let row = parse_line("a, /tmp ,TRUE", ',', Some(' '), &columns).unwrap();
+        assert!(row.cells()[0] == Value::string("a"));
+        assert!(matches!(&row.cells()[1], Value::File(p) if p == &PathBuf::from("/tmp")));
+        assert!(row.cells()[2] == Value::Bool(true));
+        assert!(parse_line("a,/tmp", ',', None, &columns).is_err());
+        assert!(parse_line("a,/tmp,maybe", ',', None, &columns).is_err());
+    }
+}
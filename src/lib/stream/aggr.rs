@@ -0,0 +1,152 @@
+use crate::lang::errors::{argument_error, error, mandate, CrushResult};
+use crate::lang::execution_context::ExecutionContext;
+use crate::lang::stream::Stream;
+use crate::lang::{value::Value, value::ValueType};
+use crate::lib::stream::sum_avg::parse;
+use crate::util::time::{duration_from_nanos, duration_nanos};
+use std::cmp::Ordering;
+
+/// Integers and floats are averaged as floats, and durations as durations. The average of an
+/// empty stream is an error.
+fn avg_column(mut s: Stream, column: usize) -> CrushResult<Value> {
+    let cell_type = s.types()[column].cell_type.clone();
+    match cell_type {
+        ValueType::Integer | ValueType::Float | ValueType::Duration => {}
+        t => {
+            return argument_error(
+                format!(
+                    "Can't calculate average of elements of type {}",
+                    t.to_string()
+                )
+                .as_str(),
+            )
+        }
+    }
+    let mut sum = 0.0;
+    let mut nanos: i128 = 0;
+    let mut count: i128 = 0;
+    while let Ok(row) = s.read() {
+        count += 1;
+        match &row.cells()[column] {
+            Value::Integer(i) => sum += *i as f64,
+            Value::Float(f) => sum += *f,
+            Value::Duration(d) => nanos += duration_nanos(d),
+            _ => return error("Invalid cell value"),
+        }
+    }
+    if count == 0 {
+        return argument_error("Can't calculate the average of an empty stream");
+    }
+    match cell_type {
+        ValueType::Duration => Ok(Value::Duration(mandate(
+            duration_from_nanos(nanos / count),
+            "Average duration out of range",
+        )?)),
+        _ => Ok(Value::Float(sum / (count as f64))),
+    }
+}
+
+pub fn avg(context: ExecutionContext) -> CrushResult<()> {
+    match context.input.recv()?.stream() {
+        Some(input) => {
+            let column = parse(input.types(), &context.arguments)?;
+            context.output.send(avg_column(input, column)?)
+        }
+        _ => error("Expected a stream"),
+    }
+}
+
+/// Pick the cell that compares as the extreme in the specified direction. The result has the
+/// same type as the column. Incomparable cells, like NaN, are skipped.
+fn pick_column(mut s: Stream, column: usize, wanted: Ordering, name: &str) -> CrushResult<Value> {
+    match &s.types()[column].cell_type {
+        ValueType::Integer | ValueType::Float | ValueType::Duration | ValueType::Time => {}
+        t => {
+            return argument_error(
+                format!("Can't pick {} of elements of type {}", name, t.to_string()).as_str(),
+            )
+        }
+    }
+    let mut res: Option<Value> = None;
+    while let Ok(row) = s.read() {
+        let value = row.into_vec().swap_remove(column);
+        if value.partial_cmp(&value).is_none() {
+            continue;
+        }
+        res = match res {
+            Some(current) if value.partial_cmp(&current) != Some(wanted) => Some(current),
+            _ => Some(value),
+        };
+    }
+    mandate(
+        res,
+        format!("Can't pick {} of an empty stream", name).as_str(),
+    )
+}
+
+pub fn min(context: ExecutionContext) -> CrushResult<()> {
+    match context.input.recv()?.stream() {
+        Some(input) => {
+            let column = parse(input.types(), &context.arguments)?;
+            context
+                .output
+                .send(pick_column(input, column, Ordering::Less, "min")?)
+        }
+        _ => error("Expected a stream"),
+    }
+}
+
+pub fn max(context: ExecutionContext) -> CrushResult<()> {
+    match context.input.recv()?.stream() {
+        Some(input) => {
+            let column = parse(input.types(), &context.arguments)?;
+            context
+                .output
+                .send(pick_column(input, column, Ordering::Greater, "max")?)
+        }
+        _ => error("Expected a stream"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::stream::test_util::{integer_stream, value_stream};
+    use chrono::Duration;
+
+    #[test]
+    fn test_avg() {
+        assert!(avg_column(Box::from(integer_stream(&[1, 2])), 0).unwrap() == Value::Float(1.5));
+        assert!(avg_column(Box::from(integer_stream(&[])), 0).is_err());
+        let floats = value_stream(ValueType::Float, vec![Value::Float(0.5), Value::Float(1.5)]);
+        assert!(avg_column(Box::from(floats), 0).unwrap() == Value::Float(1.0));
+        let durations = value_stream(
+            ValueType::Duration,
+            vec![
+                Value::Duration(Duration::seconds(1)),
+                Value::Duration(Duration::seconds(2)),
+            ],
+        );
+        assert!(
+            avg_column(Box::from(durations), 0).unwrap()
+                == Value::Duration(Duration::milliseconds(1500))
+        );
+        let strings = value_stream(ValueType::String, vec![Value::string("a")]);
+        assert!(avg_column(Box::from(strings), 0).is_err());
+    }
+
+    #[test]
+    fn test_min_max() {
+        let min = |s| pick_column(s, 0, Ordering::Less, "min");
+        let max = |s| pick_column(s, 0, Ordering::Greater, "max");
+        assert!(min(Box::from(integer_stream(&[3, 1, 2]))).unwrap() == Value::Integer(1));
+        assert!(max(Box::from(integer_stream(&[3, 1, 2]))).unwrap() == Value::Integer(3));
+        assert!(min(Box::from(integer_stream(&[]))).is_err());
+        let floats = value_stream(
+            ValueType::Float,
+            vec![Value::Float(f64::NAN), Value::Float(2.5), Value::Float(0.5)],
+        );
+        assert!(min(Box::from(floats)).unwrap() == Value::Float(0.5));
+        assert!(max(Box::from(value_stream(ValueType::String, vec![]))).is_err());
+    }
+}
@@ -1,24 +1,51 @@
 use crate::lang::errors::{argument_error, CrushResult};
 use crate::lang::execution_context::ExecutionContext;
-use crate::lang::stream::Stream;
-use crate::lang::value::Value;
+use crate::lang::stream::{CrushStream, ValueSender};
+use crate::lang::table::{ColumnType, Row};
+use crate::lang::value::{Value, ValueType};
 
-fn count_rows(mut s: Stream) -> Value {
+fn count_rows(input: &mut dyn CrushStream) -> i128 {
     let mut res: i128 = 0;
-    while let Ok(_) = s.read() {
+    while input.read().is_ok() {
         res += 1;
     }
-    Value::Integer(res)
+    res
+}
+
+/// Output the count as a stream with a single row and a single column. When used as an
+/// aggregation in group, the count itself is used.
+fn send_count(count: i128, sender: ValueSender) -> CrushResult<()> {
+    let output = sender.initialize(vec![ColumnType::new("count", ValueType::Integer)])?;
+    output.send(Row::new(vec![Value::Integer(count)]))
 }
 
 pub fn perform(context: ExecutionContext) -> CrushResult<()> {
     match context.input.recv()? {
-        Value::Table(r) => context.output.send(Value::Integer(r.rows().len() as i128)),
-        Value::List(r) => context.output.send(Value::Integer(r.len() as i128)),
-        Value::Dict(r) => context.output.send(Value::Integer(r.len() as i128)),
+        Value::Table(r) => send_count(r.rows().len() as i128, context.output),
+        Value::List(r) => send_count(r.len() as i128, context.output),
+        Value::Dict(r) => send_count(r.len() as i128, context.output),
         v => match v.stream() {
-            Some(readable) => context.output.send(count_rows(readable)),
+            Some(mut readable) => send_count(count_rows(readable.as_mut()), context.output),
             None => argument_error("Expected a stream"),
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::stream::test_util::{collect_rows, first_column_integers, integer_stream};
+
+    #[test]
+    fn test_count() {
+        assert_eq!(count_rows(&mut integer_stream(&[1, 2, 3])), 3);
+        assert_eq!(count_rows(&mut integer_stream(&[])), 0);
+    }
+
+    #[test]
+    fn test_send_count() {
+        let (types, rows) = collect_rows(|sender| send_count(3, sender).unwrap());
+        assert_eq!(types, vec![ColumnType::new("count", ValueType::Integer)]);
+        assert_eq!(first_column_integers(&rows), vec![3]);
+    }
+}
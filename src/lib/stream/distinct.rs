@@ -0,0 +1,86 @@
+use crate::lang::errors::{argument_error, error, CrushResult};
+use crate::lang::execution_context::{ArgumentVector, ExecutionContext};
+use crate::lang::stream::{CrushStream, ValueSender};
+use crate::lang::table::Row;
+use std::collections::HashSet;
+
+// Only rows of hashable values are put in the set, and those are immutable.
+#[allow(clippy::mutable_key_type)]
+pub fn run(input: &mut dyn CrushStream, sender: ValueSender) -> CrushResult<()> {
+    for column in input.types() {
+        if !column.cell_type.is_hashable() {
+            return argument_error(
+                format!(
+                    "Can't find distinct rows, column {} of type {} is not hashable",
+                    column.name,
+                    column.cell_type.to_string()
+                )
+                .as_str(),
+            );
+        }
+    }
+    let output = sender.initialize(input.types().to_vec())?;
+    let mut seen: HashSet<Row> = HashSet::new();
+    while let Ok(row) = input.read() {
+        // A column of type any passes the check above, but its cells may still be e.g. lists
+        for cell in row.cells() {
+            cell.check_hashable()?;
+        }
+        if !seen.contains(&row) {
+            seen.insert(row.clone());
+            output.send(row)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn distinct(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    match context.input.recv()?.stream() {
+        Some(mut input) => run(input.as_mut(), context.output),
+        None => error("Expected a stream"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::list::List;
+    use crate::lang::stream::channels;
+    use crate::lang::table::{ColumnType, Table, TableReader};
+    use crate::lang::value::{Value, ValueType};
+    use crate::lib::stream::test_util::{collect_rows, first_column_integers, integer_stream};
+
+    #[test]
+    fn test_distinct() {
+        let (_, rows) =
+            collect_rows(|sender| run(&mut integer_stream(&[3, 1, 3, 2, 1, 3]), sender).unwrap());
+        assert_eq!(first_column_integers(&rows), vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_distinct_requires_hashable_columns() {
+        let mut input = TableReader::new(Table::new(
+            vec![ColumnType::new(
+                "value",
+                ValueType::List(Box::from(ValueType::Integer)),
+            )],
+            vec![],
+        ));
+        let (sender, _receiver) = channels();
+        assert!(run(&mut input, sender).is_err());
+    }
+
+    #[test]
+    fn test_distinct_requires_hashable_cells() {
+        let mut input = TableReader::new(Table::new(
+            vec![ColumnType::new("value", ValueType::Any)],
+            vec![Row::new(vec![Value::List(List::new(
+                ValueType::Integer,
+                vec![],
+            ))])],
+        ));
+        let (sender, _receiver) = channels();
+        assert!(run(&mut input, sender).is_err());
+    }
+}
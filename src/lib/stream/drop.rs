@@ -0,0 +1,111 @@
+use crate::lang::argument::ArgumentHandler;
+use crate::lang::command::OutputType::Unknown;
+use crate::lang::errors::{argument_error, error, CrushResult};
+use crate::lang::execution_context::ExecutionContext;
+use crate::lang::stream::{CrushStream, OutputStream};
+use crate::lang::table::{ColumnType, ColumnVec, Row};
+use crate::lang::value::Field;
+use signature::signature;
+
+#[signature(
+    drop,
+    short = "Remove columns from the io",
+    long = "All other columns are passed on unchanged and in their original order.",
+    example = "ls | drop ^inode ^nlink",
+    output = Unknown
+)]
+pub struct DropSignature {
+    #[unnamed()]
+    #[description("the columns to remove.")]
+    field: Vec<Field>,
+}
+
+/// The indices of the columns that remain after removing the specified ones.
+fn kept_columns(input_type: &[ColumnType], fields: &[Field]) -> CrushResult<Vec<usize>> {
+    if fields.is_empty() {
+        return argument_error("No columns to drop");
+    }
+    let dropped = fields
+        .iter()
+        .map(|field| input_type.find(field))
+        .collect::<CrushResult<Vec<_>>>()?;
+    Ok((0..input_type.len())
+        .filter(|idx| !dropped.contains(idx))
+        .collect())
+}
+
+fn run(kept: &[usize], input: &mut dyn CrushStream, output: OutputStream) -> CrushResult<()> {
+    while let Ok(row) = input.read() {
+        output.send(Row::new(
+            kept.iter().map(|idx| row.cells()[*idx].clone()).collect(),
+        ))?;
+    }
+    Ok(())
+}
+
+pub fn drop(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: DropSignature = DropSignature::parse(context.arguments, &context.printer)?;
+    match context.input.recv()?.stream() {
+        Some(mut input) => {
+            let kept = kept_columns(input.types(), &cfg.field)?;
+            let output = context
+                .output
+                .initialize(kept.iter().map(|idx| input.types()[*idx].clone()).collect())?;
+            run(&kept, input.as_mut(), output)
+        }
+        None => error("Expected a stream"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::table::{Table, TableReader};
+    use crate::lang::value::{Value, ValueType};
+    use crate::lib::stream::test_util::{collect_rows, first_column_integers};
+
+    fn field(name: &str) -> Field {
+        vec![name.to_string()]
+    }
+
+    #[test]
+    fn test_drop() {
+        let types = vec![
+            ColumnType::new("a", ValueType::Integer),
+            ColumnType::new("b", ValueType::Integer),
+            ColumnType::new("c", ValueType::Integer),
+        ];
+        let kept = kept_columns(&types, &[field("b"), field("a")]).unwrap();
+        assert_eq!(kept, vec![2]);
+        let mut input = TableReader::new(Table::new(
+            types,
+            vec![
+                Row::new(vec![
+                    Value::Integer(1),
+                    Value::Integer(2),
+                    Value::Integer(3),
+                ]),
+                Row::new(vec![
+                    Value::Integer(4),
+                    Value::Integer(5),
+                    Value::Integer(6),
+                ]),
+            ],
+        ));
+        let (_, rows) = collect_rows(|sender| {
+            let output = sender
+                .initialize(vec![ColumnType::new("c", ValueType::Integer)])
+                .unwrap();
+            run(&kept, &mut input, output).unwrap()
+        });
+        assert_eq!(first_column_integers(&rows), vec![3, 6]);
+        assert_eq!(rows[0].cells().len(), 1);
+    }
+
+    #[test]
+    fn test_drop_unknown_column() {
+        let types = vec![ColumnType::new("value", ValueType::Integer)];
+        assert!(kept_columns(&types, &[field("valeu")]).is_err());
+        assert!(kept_columns(&types, &[]).is_err());
+    }
+}
@@ -1,15 +1,15 @@
 use crate::lang::errors::{error, CrushResult};
-use crate::lang::execution_context::ExecutionContext;
+use crate::lang::execution_context::{ArgumentVector, ExecutionContext};
 use crate::lang::stream::{CrushStream, ValueSender};
 use crate::lang::table::ColumnType;
 use crate::lang::{table::Row, value::Value, value::ValueType};
 
-pub fn run(input: &mut dyn CrushStream, sender: ValueSender) -> CrushResult<()> {
+pub fn run(start: i128, input: &mut dyn CrushStream, sender: ValueSender) -> CrushResult<()> {
     let mut output_type = vec![ColumnType::new("idx", ValueType::Integer)];
     output_type.extend(input.types().to_vec());
     let output = sender.initialize(output_type)?;
 
-    let mut line: i128 = 0;
+    let mut line = start;
     while let Ok(row) = input.read() {
         let mut out = vec![Value::Integer(line)];
         out.extend(row.into_vec());
@@ -19,9 +19,39 @@ pub fn run(input: &mut dyn CrushStream, sender: ValueSender) -> CrushResult<()>
     Ok(())
 }
 
-pub fn perform(context: ExecutionContext) -> CrushResult<()> {
+pub fn perform(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len_range(0, 1)?;
+    let start = context.arguments.optional_integer(0)?.unwrap_or(0);
     match context.input.recv()?.stream() {
-        Some(mut r) => run(r.as_mut(), context.output),
+        Some(mut r) => run(start, r.as_mut(), context.output),
         None => error("Expected a stream"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::stream::test_util::{collect_rows, first_column_integers, integer_stream};
+
+    #[test]
+    fn test_enumerate() {
+        let (types, rows) =
+            collect_rows(|sender| run(0, &mut integer_stream(&[7, 8, 9]), sender).unwrap());
+        assert_eq!(
+            types,
+            vec![
+                ColumnType::new("idx", ValueType::Integer),
+                ColumnType::new("value", ValueType::Integer)
+            ]
+        );
+        assert_eq!(first_column_integers(&rows), vec![0, 1, 2]);
+        assert!(rows[2].cells()[1] == Value::Integer(9));
+    }
+
+    #[test]
+    fn test_enumerate_start() {
+        let (_, rows) =
+            collect_rows(|sender| run(10, &mut integer_stream(&[7, 8, 9]), sender).unwrap());
+        assert_eq!(first_column_integers(&rows), vec![10, 11, 12]);
+    }
+}
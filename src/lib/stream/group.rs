@@ -9,6 +9,7 @@ use crate::lang::scope::Scope;
 use crate::lang::stream::{channels, InputStream};
 use crate::lang::table::ColumnType;
 use crate::lang::table::ColumnVec;
+use crate::lang::table::Table;
 use crate::lang::value::Field;
 use crate::util::thread::{build, handle};
 use crate::{
@@ -24,19 +25,65 @@ use std::collections::HashMap;
     group,
     can_block = true,
     short = "Group stream by the specified column(s)",
+    long = "If no aggregation commands are given, the rows of each group are output as a table in a column named group.",
     example = "find . | group ^user ^type file_count={count} size={sum ^size}"
 )]
 pub struct Group {
     #[unnamed()]
     #[description("the column(s) to group by and copy into the output stream.")]
     group_by: Vec<Field>,
+    #[description("keep the group-by columns in the tables of grouped rows.")]
+    #[default(false)]
+    keep_key: bool,
     #[named()]
     #[description("create these additional columns by aggregating the grouped rows using the supplied aggregation command.")]
     command: OrderedStringMap<Command>,
 }
 
+/// The columns of the grouped rows that go into the group column.
+fn group_columns(input_type: &[ColumnType], indices: &[usize], keep_key: bool) -> Vec<usize> {
+    (0..input_type.len())
+        .filter(|idx| keep_key || !indices.contains(idx))
+        .collect()
+}
+
+fn group_table(rows: InputStream, columns: &[usize]) -> CrushResult<Value> {
+    let types = columns
+        .iter()
+        .map(|idx| rows.types()[*idx].clone())
+        .collect();
+    let mut res = Vec::new();
+    while let Some(row) = rows.recv_optional()? {
+        let cells = row.into_vec();
+        res.push(Row::new(
+            columns.iter().map(|idx| cells[*idx].clone()).collect(),
+        ));
+    }
+    Ok(Value::Table(Table::new(types, res)))
+}
+
+/// Aggregation commands like count output a single row with a single column, whose cell is
+/// used as is. Any other stream is stored as a table.
+fn aggregated(value: Value) -> CrushResult<Value> {
+    match value {
+        Value::TableStream(rows) => {
+            let mut res = Vec::new();
+            while let Some(row) = rows.recv_optional()? {
+                res.push(row);
+            }
+            if rows.types().len() == 1 && res.len() == 1 {
+                Ok(res.remove(0).into_vec().remove(0))
+            } else {
+                Ok(Value::Table(Table::new(rows.types().to_vec(), res)))
+            }
+        }
+        v => Ok(v),
+    }
+}
+
 fn aggregate(
     commands: Vec<Command>,
+    columns: Vec<usize>,
     printer: Printer,
     scope: Scope,
     destination: OutputStream,
@@ -45,7 +92,9 @@ fn aggregate(
     while let Ok((key, rows)) = task_input.recv() {
         match commands.len() {
             0 => {
-                destination.send(Row::new(key))?;
+                let mut result = key;
+                result.push(group_table(rows, &columns)?);
+                destination.send(Row::new(result))?;
             }
             1 => {
                 let (input_sender, input_receiver) = channels();
@@ -61,7 +110,7 @@ fn aggregate(
                     printer: printer.clone(),
                 })?;
                 let mut result = key;
-                result.push(output_receiver.recv()?);
+                result.push(aggregated(output_receiver.recv()?)?);
                 destination.send(Row::new(result))?;
             }
             _ => {
@@ -91,7 +140,7 @@ fn aggregate(
 
                 let mut result = key;
                 for receiver in receivers {
-                    result.push(receiver.recv()?);
+                    result.push(aggregated(receiver.recv()?)?);
                 }
                 destination.send(Row::new(result))?;
             }
@@ -102,6 +151,7 @@ fn aggregate(
 
 fn create_worker_thread(
     cfg: &Group,
+    columns: &[usize],
     printer: &Printer,
     scope: &Scope,
     destination: &OutputStream,
@@ -112,6 +162,7 @@ fn create_worker_thread(
         .iter()
         .map(|(_name, cmd)| cmd.copy())
         .collect::<Vec<_>>();
+    let my_columns = columns.to_vec();
     let my_printer = printer.clone();
     let my_scope = scope.clone();
     let my_input = task_input.clone();
@@ -120,6 +171,7 @@ fn create_worker_thread(
         let local_printer = my_printer.clone();
         local_printer.handle_error(aggregate(
             my_commands,
+            my_columns,
             my_printer,
             my_scope,
             my_destination,
@@ -150,6 +202,13 @@ pub fn group(context: ExecutionContext) -> CrushResult<()> {
         .map(|input_idx| input_type[*input_idx].clone())
         .collect::<Vec<_>>();
 
+    let columns = group_columns(&input_type, &indices, cfg.keep_key);
+    if cfg.command.is_empty() {
+        output_type.push(ColumnType::new(
+            "group",
+            ValueType::Table(columns.iter().map(|idx| input_type[*idx].clone()).collect()),
+        ));
+    }
     for name in cfg.command.keys() {
         output_type.push(ColumnType::new(name, ValueType::Any));
     }
@@ -160,7 +219,14 @@ pub fn group(context: ExecutionContext) -> CrushResult<()> {
     let (task_output, task_input) = unbounded::<(Vec<Value>, InputStream)>();
 
     for _ in 0..16 {
-        create_worker_thread(&cfg, &context.printer, &context.env, &output, &task_input);
+        create_worker_thread(
+            &cfg,
+            &columns,
+            &context.printer,
+            &context.env,
+            &output,
+            &task_input,
+        );
     }
 
     drop(task_input);
@@ -170,6 +236,9 @@ pub fn group(context: ExecutionContext) -> CrushResult<()> {
             .iter()
             .map(|idx| row.cells()[*idx].clone())
             .collect::<Vec<_>>();
+        for cell in &key {
+            cell.check_hashable()?;
+        }
         let val = groups.get(&key);
         match val {
             None => {
@@ -185,3 +254,65 @@ pub fn group(context: ExecutionContext) -> CrushResult<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::stream::streams;
+
+    #[test]
+    fn test_group_columns() {
+        let types = vec![
+            ColumnType::new("a", ValueType::Integer),
+            ColumnType::new("b", ValueType::String),
+            ColumnType::new("c", ValueType::Integer),
+        ];
+        assert_eq!(group_columns(&types, &[1], false), vec![0, 2]);
+        assert_eq!(group_columns(&types, &[1], true), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_group_table() {
+        let (output, input) = streams(vec![
+            ColumnType::new("key", ValueType::String),
+            ColumnType::new("value", ValueType::Integer),
+        ]);
+        for i in 0..3 {
+            output
+                .send(Row::new(vec![Value::string("a"), Value::Integer(i)]))
+                .unwrap();
+        }
+        drop(output);
+        match group_table(input, &[1]).unwrap() {
+            Value::Table(t) => {
+                assert_eq!(
+                    t.types().to_vec(),
+                    vec![ColumnType::new("value", ValueType::Integer)]
+                );
+                assert_eq!(t.rows().len(), 3);
+                assert!(t.rows()[2].cells()[0] == Value::Integer(2));
+            }
+            _ => panic!("Expected a table"),
+        }
+    }
+
+    #[test]
+    fn test_aggregated() {
+        let (output, input) = streams(vec![ColumnType::new("count", ValueType::Integer)]);
+        output.send(Row::new(vec![Value::Integer(3)])).unwrap();
+        drop(output);
+        assert!(aggregated(Value::TableStream(input)).unwrap() == Value::Integer(3));
+
+        let (output, input) = streams(vec![ColumnType::new("value", ValueType::Integer)]);
+        for i in 0..2 {
+            output.send(Row::new(vec![Value::Integer(i)])).unwrap();
+        }
+        drop(output);
+        match aggregated(Value::TableStream(input)).unwrap() {
+            Value::Table(t) => assert_eq!(t.rows().len(), 2),
+            _ => panic!("Expected a table"),
+        }
+
+        assert!(aggregated(Value::Integer(7)).unwrap() == Value::Integer(7));
+    }
+}
@@ -1,16 +1,18 @@
-use crate::lang::errors::{error, CrushResult};
+use crate::lang::errors::{argument_error, error, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, ExecutionContext};
 use crate::lang::stream::{CrushStream, ValueSender};
 
 pub fn run(lines: i128, input: &mut dyn CrushStream, sender: ValueSender) -> CrushResult<()> {
+    if lines < 0 {
+        return argument_error("The number of lines can't be negative");
+    }
     let output = sender.initialize(input.types().to_vec())?;
-    let mut count = 0;
-    while let Ok(row) = input.read() {
-        if count >= lines {
-            break;
+    // Check the count before reading so that we never wait for a row we won't use
+    for _ in 0..lines {
+        match input.read() {
+            Ok(row) => output.send(row)?,
+            Err(_) => break,
         }
-        output.send(row)?;
-        count += 1;
     }
     Ok(())
 }
@@ -23,3 +25,58 @@ pub fn perform(mut context: ExecutionContext) -> CrushResult<()> {
         None => error("Expected a stream"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::table::{ColumnType, Row};
+    use crate::lib::stream::test_util::{collect_rows, first_column_integers, integer_stream};
+    use chrono::Duration;
+
+    /// An endless stream that counts how many rows have been read from it.
+    struct Endless {
+        reads: usize,
+        types: Vec<ColumnType>,
+    }
+
+    impl CrushStream for Endless {
+        fn read(&mut self) -> CrushResult<Row> {
+            self.reads += 1;
+            Ok(Row::new(vec![]))
+        }
+
+        fn read_timeout(
+            &mut self,
+            _timeout: Duration,
+        ) -> Result<Row, crate::lang::stream::RecvTimeoutError> {
+            Ok(self.read().unwrap())
+        }
+
+        fn types(&self) -> &[ColumnType] {
+            &self.types
+        }
+    }
+
+    #[test]
+    fn test_head() {
+        let numbers: Vec<i128> = (0..100).collect();
+        let (_, rows) =
+            collect_rows(move |sender| run(5, &mut integer_stream(&numbers), sender).unwrap());
+        assert_eq!(first_column_integers(&rows), vec![0, 1, 2, 3, 4]);
+
+        let (_, rows) =
+            collect_rows(|sender| run(5, &mut integer_stream(&[1, 2]), sender).unwrap());
+        assert_eq!(first_column_integers(&rows), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_head_stops_reading() {
+        let mut input = Endless {
+            reads: 0,
+            types: vec![],
+        };
+        let (_, rows) = collect_rows(|sender| run(5, &mut input, sender).unwrap());
+        assert_eq!(rows.len(), 5);
+        assert_eq!(input.reads, 5);
+    }
+}
@@ -147,10 +147,13 @@ fn do_join(
 ) -> CrushResult<()> {
     let mut l_data: HashMap<Value, Row> = HashMap::new();
     while let Ok(row) = l.read() {
-        l_data.insert(row.cells()[cfg.left_column_idx].clone(), row);
+        let key = row.cells()[cfg.left_column_idx].clone();
+        key.check_hashable()?;
+        l_data.insert(key, row);
     }
 
     while let Ok(r_row) = r.read() {
+        r_row.cells()[cfg.right_column_idx].check_hashable()?;
         l_data
             .remove(&r_row.cells()[cfg.right_column_idx])
             .map(|l_row| {
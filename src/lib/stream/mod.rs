@@ -2,6 +2,7 @@ use crate::lang::argument::ArgumentHandler;
 use crate::lang::command::OutputType::{Known, Passthrough, Unknown};
 use crate::lang::errors::CrushResult;
 use crate::lang::scope::Scope;
+use crate::lang::table::ColumnType;
 use crate::lang::value::ValueType;
 
 mod head;
@@ -10,17 +11,24 @@ mod sort;
 mod tail;
 mod r#where;
 
+mod drop;
 mod enumerate;
 mod select;
 
+mod distinct;
 mod group;
 mod join;
 mod uniq;
 mod zip;
 
+mod aggr;
 mod count;
 mod seq;
 mod sum_avg;
+mod window;
+
+#[cfg(test)]
+pub mod test_util;
 
 pub fn declare(root: &Scope) -> CrushResult<()> {
     let e = root.create_lazy_namespace(
@@ -31,7 +39,9 @@ pub fn declare(root: &Scope) -> CrushResult<()> {
                 "head [lines:integer]", "Return the first lines of the io. Defaults to 10.", None, Passthrough)?;
             env.declare_command(
                 "tail", tail::perform, true,
-                "tail [lines:integer]", "Return the last lines of the io. Defaults to 10.", None, Passthrough)?;
+                "tail [lines:integer]", "Return the last lines of the io. Defaults to 10.",
+                Some("    The whole input is read before any output is produced, so tail never finishes\n    on infinite streams."),
+                Passthrough)?;
             r#where::Where::declare(env)?;
             sort::Sort::declare(env)?;
             env.declare_command(
@@ -45,44 +55,91 @@ pub fn declare(root: &Scope) -> CrushResult<()> {
                 Unknown)?;
             env.declare_command(
                 "uniq", uniq::uniq, true,
-                "uniq column:field",
-                "Only output the first row if multiple rows has the same value for the specified column",
-                example!("ps | uniq ^user"),
+                "uniq [column:field]",
+                "Only output the first of multiple consecutive rows that are equal",
+                Some(r#"    If a column is given, rows are compared on that column only, otherwise the
+    whole row is compared. Rows that are equal but not adjacent are all output.
+
+    Example:
+
+    ps | sort ^user | uniq ^user"#),
+                Passthrough)?;
+            env.declare_command(
+                "distinct", distinct::distinct, true,
+                "distinct",
+                "Only output the first occurrence of every row",
+                Some(r#"    Unlike uniq, duplicates are removed even if they are not adjacent. Every row
+    that has been output is remembered, so all columns must be hashable.
+
+    Example:
+
+    ps | select ^user | distinct"#),
                 Passthrough)?;
             env.declare_command(
                 "count", count::perform, true,
                 "count",
-                "Count the number of rows in the io", example!("ps | count"), Known(ValueType::Integer))?;
+                "Count the number of rows in the io",
+                Some(r#"    The output is a stream with a single row and a single integer column named
+    count, which is 0 for empty input. When count is used as an aggregation in
+    group, the count itself is used.
+
+    Example:
+
+    seq 1000 | count"#),
+                Known(ValueType::TableStream(vec![ColumnType::new("count", ValueType::Integer)])))?;
             env.declare_command(
                 "sum", sum_avg::sum, true,
-                "sum column:field",
+                "sum [column:field]",
                 "Calculate the sum for the specific column across all rows",
                 example!("ps | sum ^cpu"), Unknown)?;
             env.declare_command(
-                "min", sum_avg::min, true,
+                "min", aggr::min, true,
                 "min [column:field]",
                 "Find the minimum value of the specific column across all rows",
-                example!("ps | min ^cpu"), Unknown)?;
+                Some(r#"    The result has the same type as the column. Values that can't be compared,
+    like NaN, are skipped. It is an error if the input is empty.
+
+    Example:
+
+    ps | min ^cpu"#), Unknown)?;
             env.declare_command(
-                "max", sum_avg::max, true,
+                "max", aggr::max, true,
                 "max [column:field]",
                 "Find the maximum value of the specific column across all rows",
-                example!("ps | max ^cpu"), Unknown)?;
+                Some(r#"    The result has the same type as the column. Values that can't be compared,
+    like NaN, are skipped. It is an error if the input is empty.
+
+    Example:
+
+    ps | max ^cpu"#), Unknown)?;
             env.declare_command(
-                "avg", sum_avg::avg, true,
-                "avg column:field",
+                "avg", aggr::avg, true,
+                "avg [column:field]",
                 "Calculate the average of the specific column across all rows",
-                example!("ps | avg ^cpu"), Unknown)?;
+                Some(r#"    The average of integers or floats is a float, the average of durations is a
+    duration. It is an error if the input is empty.
+
+    Example:
+
+    ps | avg ^cpu"#), Unknown)?;
             env.declare_command(
                 "select", select::select, true,
-                "select copy_fields:field... [%] new_field=definition:command",
+                "select copy_fields:field... [%] new_field=(definition:command|old_field:field)",
                 "Pass on some old fields and calculate new ones for each line of io",
-                example!(r#"ls | select ^user path={"{}/{}":format (pwd) file}"#), Unknown)?;
+                Some(r#"    Unnamed fields are copied in the given order, and may be given more than once.
+    A named field copies an existing column under a new name.
+
+    Example:
+
+    ls | select ^user path={"{}/{}":format (pwd) file} owner=^user"#), Unknown)?;
+            drop::DropSignature::declare(env)?;
             env.declare_command(
                 "enumerate", enumerate::perform, true,
-                "enumerate", "Prepend a column containing the row number to each row of the io", None, Unknown)?;
+                "enumerate [start:integer]", "Prepend a column containing the row number to each row of the io",
+                Some("    Rows are numbered from start, which defaults to 0."), Unknown)?;
             zip::Zip::declare(env)?;
             seq::Seq::declare(env)?;
+            window::Window::declare(env)?;
             Ok(())
         }))?;
     root.r#use(&e);
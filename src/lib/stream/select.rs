@@ -1,5 +1,5 @@
 use crate::lang::command::Command;
-use crate::lang::errors::error;
+use crate::lang::errors::{error, mandate};
 use crate::lang::execution_context::ExecutionContext;
 use crate::lang::stream::{channels, empty_channel, Stream};
 use crate::lang::table::ColumnVec;
@@ -8,7 +8,6 @@ use crate::{
     lang::errors::CrushResult,
     lang::table::ColumnType,
     lang::{argument::Argument, table::Row, value::Value},
-    util::replace::Replace,
 };
 
 enum Location {
@@ -26,162 +25,223 @@ pub struct Config {
     columns: Vec<(Location, Source)>,
 }
 
-pub fn run(config: Config, mut input: Stream, context: ExecutionContext) -> CrushResult<()> {
-    let input_type = input.types().to_vec();
+/// The output type of the selection. Calculated columns take the type of their value in the
+/// first output row, so without that row the type is only known if nothing is calculated.
+fn output_type(
+    input_type: &[ColumnType],
+    config: &Config,
+    first: Option<&[Value]>,
+) -> Option<Vec<ColumnType>> {
     let mut output_type = if config.copy {
-        input_type.clone()
+        input_type.to_vec()
     } else {
         Vec::new()
     };
+    for (location, source) in &config.columns {
+        let idx = match location {
+            Location::Replace(idx) => *idx,
+            Location::Append(_) => output_type.len(),
+        };
+        let cell_type = match source {
+            Source::Argument(column) => input_type[*column].cell_type.clone(),
+            Source::Closure(_) => first?[idx].value_type(),
+        };
+        match location {
+            Location::Replace(idx) => output_type[*idx].cell_type = cell_type,
+            Location::Append(name) => output_type.push(ColumnType::new(name, cell_type)),
+        }
+    }
+    Some(output_type)
+}
 
-    let mut first_result = Vec::new();
-
-    match input.read() {
-        Ok(row) => {
-            if config.copy {
-                first_result.append(&mut row.cells().clone());
-            }
-            for (location, source) in &config.columns {
-                let value = match source {
-                    Source::Closure(closure) => {
-                        let (sender, receiver) = channels();
-                        let arguments: Vec<Argument> = row
-                            .cells()
-                            .iter()
-                            .zip(&input_type)
-                            .map(|(cell, cell_type)| {
-                                Argument::named(cell_type.name.as_ref(), cell.clone())
-                            })
-                            .collect();
-                        closure.invoke(ExecutionContext {
-                            input: empty_channel(),
-                            output: sender,
-                            arguments,
-                            env: context.env.clone(),
-                            this: None,
-                            printer: context.printer.clone(),
-                        })?;
-                        receiver.recv()?
-                    }
-                    Source::Argument(idx) => row.cells()[*idx].clone(),
-                };
-
-                match location {
-                    Location::Append(name) => {
-                        output_type.push(ColumnType::new(name.as_ref(), value.value_type()));
-                        first_result.push(value);
-                    }
-                    Location::Replace(idx) => {
-                        output_type.replace(
-                            *idx,
-                            ColumnType::new(output_type[*idx].name.as_ref(), value.value_type()),
-                        );
-                        first_result[*idx] = value;
-                    }
-                }
+fn select_row(
+    config: &Config,
+    input_type: &[ColumnType],
+    row: Row,
+    context: &ExecutionContext,
+) -> CrushResult<Vec<Value>> {
+    let mut result = if config.copy {
+        row.cells().clone()
+    } else {
+        Vec::new()
+    };
+    for (location, source) in &config.columns {
+        let value = match source {
+            Source::Closure(closure) => {
+                let arguments: Vec<Argument> = row
+                    .cells()
+                    .iter()
+                    .zip(input_type)
+                    .map(|(cell, cell_type)| Argument::named(&cell_type.name, cell.clone()))
+                    .collect();
+                let (sender, receiver) = channels();
+                closure.invoke(ExecutionContext {
+                    input: empty_channel(),
+                    output: sender,
+                    arguments,
+                    env: context.env.clone(),
+                    this: None,
+                    printer: context.printer.clone(),
+                })?;
+                receiver.recv()?
             }
+            Source::Argument(idx) => row.cells()[*idx].clone(),
+        };
+        match location {
+            Location::Append(_) => result.push(value),
+            Location::Replace(idx) => result[*idx] = value,
         }
-        Err(_) => return Ok(()),
     }
+    Ok(result)
+}
 
-    let output = context.output.initialize(output_type)?;
-    output.send(Row::new(first_result))?;
+pub fn run(config: Config, mut input: Stream, context: ExecutionContext) -> CrushResult<()> {
+    let input_type = input.types().to_vec();
+    let output = match output_type(&input_type, &config, None) {
+        Some(output_type) => context.output.initialize(output_type)?,
+        None => match input.read() {
+            Ok(row) => {
+                let first_result = select_row(&config, &input_type, row, &context)?;
+                let output = context.output.initialize(mandate(
+                    output_type(&input_type, &config, Some(&first_result)),
+                    "Failed to calculate the output type",
+                )?)?;
+                output.send(Row::new(first_result))?;
+                output
+            }
+            Err(_) => return Ok(()),
+        },
+    };
 
     while let Ok(row) = input.read() {
-        let mut next_result = Vec::new();
+        output.send(Row::new(select_row(&config, &input_type, row, &context)?))?;
+    }
+    Ok(())
+}
+
+fn parse(input_type: &[ColumnType], mut arguments: Vec<Argument>) -> CrushResult<Config> {
+    let mut copy = false;
+    let mut columns = Vec::new();
 
-        if config.copy {
-            next_result.append(&mut row.cells().clone());
+    if arguments.is_empty() {
+        return argument_error("No columns selected");
+    }
+
+    if let Value::Glob(g) = &arguments[0].value {
+        if arguments[0].argument_type.is_none() && &g.to_string() == "%" {
+            copy = true;
+            arguments.remove(0);
+        } else {
+            return argument_error("Invalid argument");
         }
-        for (location, source) in &config.columns {
-            let value = match source {
-                Source::Closure(closure) => {
-                    let arguments: Vec<Argument> = row
-                        .cells()
-                        .iter()
-                        .zip(&input_type)
-                        .map(|(cell, cell_type)| Argument::named(&cell_type.name, cell.clone()))
-                        .collect();
-                    let (sender, receiver) = channels();
-                    closure.invoke(ExecutionContext {
-                        input: empty_channel(),
-                        output: sender,
-                        arguments,
-                        env: context.env.clone(),
-                        this: None,
-                        printer: context.printer.clone(),
-                    })?;
-                    receiver.recv()?
-                }
-                Source::Argument(idx) => row.cells()[*idx].clone(),
-            };
-            match location {
-                Location::Append(_) => {
-                    next_result.push(value);
+    }
+
+    for a in arguments {
+        match (a.argument_type.as_deref(), a.value) {
+            (Some(name), Value::Command(closure)) => match (copy, input_type.find_str(name)) {
+                (true, Ok(idx)) => columns.push((Location::Replace(idx), Source::Closure(closure))),
+                _ => columns.push((Location::Append(name.to_string()), Source::Closure(closure))),
+            },
+            (Some(name), Value::Field(field)) => {
+                let source = Source::Argument(input_type.find(&field)?);
+                match (copy, input_type.find_str(name)) {
+                    (true, Ok(idx)) => columns.push((Location::Replace(idx), source)),
+                    _ => columns.push((Location::Append(name.to_string()), source)),
                 }
-                Location::Replace(idx) => {
-                    next_result[*idx] = value;
+            }
+            (None, Value::Field(field)) => {
+                if copy {
+                    return argument_error(
+                        format!("Column {} is already copied by %", field.join(":")).as_str(),
+                    );
                 }
+                let idx = input_type.find(&field)?;
+                columns.push((
+                    Location::Append(input_type[idx].name.clone()),
+                    Source::Argument(idx),
+                ));
             }
+            _ => return argument_error("Invalid argument"),
         }
-        output.send(Row::new(next_result))?;
     }
-    Ok(())
+    Ok(Config { columns, copy })
 }
 
-pub fn select(mut context: ExecutionContext) -> CrushResult<()> {
+pub fn select(context: ExecutionContext) -> CrushResult<()> {
     match context.input.clone().recv()?.stream() {
         Some(input) => {
-            let mut copy = false;
-            let mut columns = Vec::new();
+            let config = parse(input.types(), context.arguments.clone())?;
+            run(config, input, context)
+        }
+        _ => error("Expected a stream"),
+    }
+}
 
-            if context.arguments.len() == 0 {
-                return argument_error("No columns selected");
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::value::ValueType;
+    use crate::util::glob::Glob;
 
-            if let Value::Glob(g) = &context.arguments[0].value {
-                if context.arguments[0].argument_type.is_none() && &g.to_string() == "%" {
-                    copy = true;
-                    context.arguments.remove(0);
-                } else {
-                    return argument_error("Invalid argument");
-                }
-            }
+    fn input_type() -> Vec<ColumnType> {
+        vec![
+            ColumnType::new("user", ValueType::String),
+            ColumnType::new("size", ValueType::Integer),
+        ]
+    }
 
-            let input_type = input.types();
-            for a in &context.arguments {
-                match (a.argument_type.as_deref(), a.value.clone()) {
-                    (Some(name), Value::Command(closure)) => {
-                        match (copy, input_type.find_str(name)) {
-                            (true, Ok(idx)) => {
-                                columns.push((Location::Replace(idx), Source::Closure(closure)))
-                            }
-                            _ => columns.push((
-                                Location::Append(name.to_string()),
-                                Source::Closure(closure),
-                            )),
-                        }
-                    }
-                    (None, Value::Field(name)) => {
-                        if name.len() != 1 {
-                            return argument_error("Invalid field");
-                        }
-                        match (copy, input_type.find_str(name[0].as_ref())) {
-                            (false, Ok(idx)) => columns
-                                .push((Location::Append(name[0].clone()), Source::Argument(idx))),
-                            _ => {
-                                return argument_error(
-                                    format!("Unknown field {}", name[0]).as_str(),
-                                )
-                            }
-                        }
-                    }
-                    _ => return argument_error("Invalid argument"),
-                }
-            }
+    fn field(name: &str) -> Value {
+        Value::Field(vec![name.to_string()])
+    }
 
-            run(Config { columns, copy }, input, context)
-        }
-        _ => error("Expected a stream"),
+    fn names(output_type: &[ColumnType]) -> Vec<&str> {
+        output_type.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_select_fields() {
+        let config = parse(
+            &input_type(),
+            vec![
+                Argument::unnamed(field("size")),
+                Argument::unnamed(field("user")),
+                Argument::unnamed(field("size")),
+                Argument::named("bytes", field("size")),
+            ],
+        )
+        .unwrap();
+        let output_type = output_type(&input_type(), &config, None).unwrap();
+        assert_eq!(names(&output_type), vec!["size", "user", "size", "bytes"]);
+        assert!(output_type[3].cell_type == ValueType::Integer);
+    }
+
+    #[test]
+    fn test_select_copy_and_rename() {
+        let config = parse(
+            &input_type(),
+            vec![
+                Argument::unnamed(Value::Glob(Glob::new("%"))),
+                Argument::named("owner", field("user")),
+                Argument::named("size", field("user")),
+            ],
+        )
+        .unwrap();
+        let output_type = output_type(&input_type(), &config, None).unwrap();
+        assert_eq!(names(&output_type), vec!["user", "size", "owner"]);
+        assert!(output_type[1].cell_type == ValueType::String);
+    }
+
+    #[test]
+    fn test_select_invalid() {
+        assert!(parse(&input_type(), vec![]).is_err());
+        assert!(parse(&input_type(), vec![Argument::unnamed(field("usr"))]).is_err());
+        assert!(parse(
+            &input_type(),
+            vec![
+                Argument::unnamed(Value::Glob(Glob::new("%"))),
+                Argument::unnamed(field("user")),
+            ]
+        )
+        .is_err());
     }
 }
@@ -1,44 +1,144 @@
-use crate::lang::argument::ArgumentHandler;
-use crate::lang::errors::CrushResult;
+use crate::lang::argument::{Argument, ArgumentHandler};
+use crate::lang::errors::{argument_error, CrushResult};
 use crate::lang::execution_context::ExecutionContext;
 use crate::lang::table::ColumnType;
 use crate::lang::{table::Row, value::Value, value::ValueType};
 use signature::signature;
 
-#[signature(seq, short = "Return a stream of sequential numbers")]
+#[signature(
+    seq,
+    short = "Return a stream of sequential numbers",
+    long = "Unnamed arguments are interpreted as seq to, seq from to or seq from to step. The sequence starts at from and stops before reaching to. A negative step counts downwards, and if the step points away from to, from and to are swapped.",
+    example = "seq 2 10 2"
+)]
 #[derive(Debug)]
 pub struct Seq {
+    #[description("the end of the sequence, which is not included in the output.")]
     #[default(i128::max_value())]
     to: i128,
+    #[description("the first number of the sequence.")]
     #[default(0)]
     from: i128,
+    #[description("the difference between two consecutive numbers.")]
     #[default(1)]
     step: i128,
 }
 
-pub fn seq(context: ExecutionContext) -> CrushResult<()> {
-    let mut cfg: Seq = Seq::parse(context.arguments, &context.printer)?;
+/// The signature lists `to` first so that a single unnamed argument is the end of the
+/// sequence, but two or more unnamed arguments start with `from`.
+fn reorder_unnamed(arguments: &mut [Argument]) {
+    let unnamed: Vec<usize> = arguments
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| a.argument_type.is_none())
+        .map(|(idx, _)| idx)
+        .take(2)
+        .collect();
+    if unnamed.len() == 2 {
+        arguments.swap(unnamed[0], unnamed[1]);
+    }
+}
+
+/// The start and end of the sequence. If the step goes in the other direction, the
+/// sequence is reversed, so that e.g. seq 5 step=-1 counts down from 5.
+fn bounds(from: i128, to: i128, step: i128) -> (i128, i128) {
+    if (to > from) != (step > 0) {
+        (to, from)
+    } else {
+        (from, to)
+    }
+}
+
+fn sequence(from: i128, to: i128, step: i128) -> CrushResult<impl Iterator<Item = i128>> {
+    if step == 0 {
+        return argument_error("The step of a sequence can't be zero");
+    }
+    Ok(
+        std::iter::successors(Some(from), move |idx| idx.checked_add(step))
+            .take_while(move |idx| if step > 0 { *idx < to } else { *idx > to }),
+    )
+}
+
+pub fn seq(mut context: ExecutionContext) -> CrushResult<()> {
+    reorder_unnamed(&mut context.arguments);
+    let cfg: Seq = Seq::parse(context.arguments, &context.printer)?;
     let output = context
         .output
         .initialize(vec![ColumnType::new("value", ValueType::Integer)])?;
 
-    if (cfg.to > cfg.from) != (cfg.step > 0) {
-        let tmp = cfg.to;
-        cfg.to = cfg.from;
-        cfg.from = tmp;
-    }
-
-    let mut idx = cfg.from;
-    loop {
-        if cfg.step > 0 {
-            if idx >= cfg.to {
-                break;
-            }
-        } else if idx <= cfg.to {
-            break;
-        }
+    let (from, to) = bounds(cfg.from, cfg.to, cfg.step);
+    for idx in sequence(from, to, cfg.step)? {
         output.send(Row::new(vec![Value::Integer(idx)]))?;
-        idx += cfg.step;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reorder_unnamed() {
+        let mut one = vec![Argument::unnamed(Value::Integer(5))];
+        reorder_unnamed(&mut one);
+        assert!(one[0].value == Value::Integer(5));
+
+        let mut two = vec![
+            Argument::unnamed(Value::Integer(2)),
+            Argument::unnamed(Value::Integer(5)),
+        ];
+        reorder_unnamed(&mut two);
+        assert!(two[0].value == Value::Integer(5));
+        assert!(two[1].value == Value::Integer(2));
+
+        let mut named = vec![
+            Argument::named("from", Value::Integer(2)),
+            Argument::named("to", Value::Integer(5)),
+        ];
+        reorder_unnamed(&mut named);
+        assert_eq!(named[0].argument_type, Some("from".to_string()));
+
+        let mut mixed = vec![
+            Argument::unnamed(Value::Integer(2)),
+            Argument::unnamed(Value::Integer(10)),
+            Argument::named("step", Value::Integer(2)),
+        ];
+        reorder_unnamed(&mut mixed);
+        assert!(mixed[0].value == Value::Integer(10));
+        assert!(mixed[1].value == Value::Integer(2));
+        assert!(mixed[2].value == Value::Integer(2));
+        let cfg = Seq::parse(mixed, &crate::lang::printer::init().0).unwrap();
+        assert_eq!((cfg.from, cfg.to, cfg.step), (2, 10, 2));
+    }
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(bounds(0, 5, 1), (0, 5));
+        assert_eq!(bounds(0, 5, -1), (5, 0));
+        assert_eq!(bounds(10, 0, -3), (10, 0));
+        assert_eq!(bounds(10, 0, 3), (0, 10));
+        let (from, to) = bounds(0, 5, -1);
+        assert_eq!(
+            sequence(from, to, -1).unwrap().collect::<Vec<_>>(),
+            vec![5, 4, 3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn test_sequence() {
+        assert_eq!(
+            sequence(0, 3, 1).unwrap().collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            sequence(2, 5, 1).unwrap().collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+        assert_eq!(
+            sequence(10, 0, -3).unwrap().collect::<Vec<_>>(),
+            vec![10, 7, 4, 1]
+        );
+        assert_eq!(sequence(0, 10, -1).unwrap().count(), 0);
+        assert!(sequence(0, 10, 0).is_err());
+    }
+}
@@ -3,30 +3,82 @@ use crate::lang::command::OutputType::Passthrough;
 use crate::lang::errors::{error, CrushResult};
 use crate::lang::execution_context::ExecutionContext;
 use crate::lang::stream::CrushStream;
-use crate::lang::table::ColumnVec;
 use crate::lang::table::Row;
+use crate::lang::table::{ColumnType, ColumnVec};
 use crate::lang::value::Field;
 use crate::{lang::errors::argument_error, lang::stream::OutputStream};
 use signature::signature;
+use std::cmp::Ordering;
 
 #[signature(
     sort,
     can_block=true,
-    short="Sort io based on column",
-    long="ps | sort ^cpu",
+    short="Sort io based on one or more columns",
+    long="Rows that are equal in the first column are ordered by the second column, and so on. Rows that are equal in all columns keep their order.",
+    example="ls | sort ^size ^file --reverse",
     output=Passthrough)]
 pub struct Sort {
-    #[description("the column to sort on. Not required if there is only one column.")]
-    field: Option<Field>,
+    #[unnamed()]
+    #[description("the columns to sort on. Not required if there is only one column.")]
+    field: Vec<Field>,
+    #[description("sort in descending order.")]
+    #[default(false)]
+    reverse: bool,
 }
 
-pub fn run(idx: usize, input: &mut dyn CrushStream, output: OutputStream) -> CrushResult<()> {
+fn sort_rows(indices: &[usize], reverse: bool, rows: &mut [Row]) {
+    rows.sort_by(|a, b| {
+        let (a, b) = if reverse { (b, a) } else { (a, b) };
+        indices
+            .iter()
+            .map(|idx| a.cells()[*idx].total_cmp(&b.cells()[*idx]))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+/// The indices of the columns to sort on, which must all be comparable.
+fn sort_columns(input_type: &[ColumnType], fields: &[Field]) -> CrushResult<Vec<usize>> {
+    let indices = if fields.is_empty() {
+        if input_type.len() == 1 {
+            vec![0]
+        } else {
+            return argument_error("Missing comparison key");
+        }
+    } else {
+        fields
+            .iter()
+            .map(|field| input_type.find(field))
+            .collect::<CrushResult<Vec<_>>>()?
+    };
+    for idx in &indices {
+        let column = &input_type[*idx];
+        if !column.cell_type.is_comparable() {
+            return argument_error(
+                format!(
+                    "Can't sort on column {} of type {}",
+                    column.name,
+                    column.cell_type.to_string()
+                )
+                .as_str(),
+            );
+        }
+    }
+    Ok(indices)
+}
+
+pub fn run(
+    indices: &[usize],
+    reverse: bool,
+    input: &mut dyn CrushStream,
+    output: OutputStream,
+) -> CrushResult<()> {
     let mut res: Vec<Row> = Vec::new();
     while let Ok(row) = input.read() {
         res.push(row);
     }
 
-    res.sort_by(|a, b| a.cells()[idx].partial_cmp(&b.cells()[idx]).expect("OH NO!"));
+    sort_rows(indices, reverse, &mut res);
 
     for row in res {
         output.send(row)?;
@@ -40,23 +92,82 @@ pub fn sort(context: ExecutionContext) -> CrushResult<()> {
         Some(mut input) => {
             let output = context.output.initialize(input.types().to_vec())?;
             let cfg: Sort = Sort::parse(context.arguments, &context.printer)?;
-            let idx = match cfg.field {
-                None => {
-                    if input.types().len() == 1 {
-                        0
-                    } else {
-                        return argument_error("Missing comparison key");
-                    }
-                }
-                Some(field) => input.types().find(&field)?,
-            };
-
-            if input.types()[idx].cell_type.is_comparable() {
-                run(idx, input.as_mut(), output)
-            } else {
-                argument_error("Bad comparison key")
-            }
+            let indices = sort_columns(input.types(), &cfg.field)?;
+            run(&indices, cfg.reverse, input.as_mut(), output)
         }
         None => error("Expected a stream"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::value::{Value, ValueType};
+    use crate::lib::stream::test_util::first_column_integers;
+
+    fn rows(values: &[Value]) -> Vec<Row> {
+        values.iter().map(|v| Row::new(vec![v.clone()])).collect()
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut res = rows(&[Value::Integer(2), Value::Integer(3), Value::Integer(1)]);
+        sort_rows(&[0], false, &mut res);
+        assert_eq!(first_column_integers(&res), vec![1, 2, 3]);
+        sort_rows(&[0], true, &mut res);
+        assert_eq!(first_column_integers(&res), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_sort_mixed() {
+        let mut res = rows(&[
+            Value::Float(f64::NAN),
+            Value::Integer(3),
+            Value::Empty(),
+            Value::Float(1.5),
+        ]);
+        sort_rows(&[0], false, &mut res);
+        let types: Vec<ValueType> = res.iter().map(|r| r.cells()[0].value_type()).collect();
+        assert!(
+            types
+                == [
+                    ValueType::Empty,
+                    ValueType::Float,
+                    ValueType::Integer,
+                    ValueType::Float
+                ]
+        );
+    }
+
+    #[test]
+    fn test_sort_multiple_columns() {
+        let mut res: Vec<Row> = [(2, 1), (1, 2), (2, 0), (1, 1)]
+            .iter()
+            .map(|(a, b)| Row::new(vec![Value::Integer(*a), Value::Integer(*b)]))
+            .collect();
+        sort_rows(&[0, 1], false, &mut res);
+        let pairs: Vec<String> = res
+            .iter()
+            .map(|r| format!("{}{}", r.cells()[0].to_string(), r.cells()[1].to_string()))
+            .collect();
+        assert_eq!(pairs, vec!["11", "12", "20", "21"]);
+        sort_rows(&[1], true, &mut res);
+        assert_eq!(first_column_integers(&res), vec![1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn test_sort_columns() {
+        let types = vec![
+            ColumnType::new("a", ValueType::Integer),
+            ColumnType::new("b", ValueType::List(Box::from(ValueType::Integer))),
+        ];
+        assert!(sort_columns(&types, &[]).is_err());
+        assert_eq!(sort_columns(&types[..1], &[]).unwrap(), vec![0]);
+        assert_eq!(
+            sort_columns(&types, &[vec!["a".to_string()]]).unwrap(),
+            vec![0]
+        );
+        assert!(sort_columns(&types, &[vec!["b".to_string()]]).is_err());
+        assert!(sort_columns(&types, &[vec!["c".to_string()]]).is_err());
+    }
+}
@@ -5,9 +5,8 @@ use crate::lang::table::ColumnVec;
 use crate::lang::{argument::Argument, table::ColumnType};
 use crate::lang::{value::Value, value::ValueType};
 use chrono::Duration;
-use float_ord::FloatOrd;
 
-fn parse(input_type: &[ColumnType], arguments: &[Argument]) -> CrushResult<usize> {
+pub(super) fn parse(input_type: &[ColumnType], arguments: &[Argument]) -> CrushResult<usize> {
     match arguments.len() {
         0 => {
             if input_type.len() == 1 {
@@ -49,138 +48,64 @@ sum_function!(sum_int, i128, 0, Integer);
 sum_function!(sum_float, f64, 0.0, Float);
 sum_function!(sum_duration, Duration, Duration::seconds(0), Duration);
 
-pub fn sum(context: ExecutionContext) -> CrushResult<()> {
-    match context.input.recv()?.stream() {
-        Some(input) => {
-            let column = parse(input.types(), &context.arguments)?;
-            match &input.types()[column].cell_type {
-                ValueType::Integer => context.output.send(sum_int(input, column)?),
-                ValueType::Float => context.output.send(sum_float(input, column)?),
-                ValueType::Duration => context.output.send(sum_duration(input, column)?),
-                t => argument_error(
-                    format!("Can't calculate sum of elements of type {}", t.to_string()).as_str(),
-                ),
-            }
-        }
-        _ => error("Expected a stream"),
+/// The sum has the same type as the column, so that e.g. summing integers gives an integer.
+fn sum_column(input: Stream, column: usize) -> CrushResult<Value> {
+    match &input.types()[column].cell_type {
+        ValueType::Integer => sum_int(input, column),
+        ValueType::Float => sum_float(input, column),
+        ValueType::Duration => sum_duration(input, column),
+        t => argument_error(
+            format!("Can't calculate sum of elements of type {}", t.to_string()).as_str(),
+        ),
     }
 }
 
-macro_rules! avg_function {
-    ($name:ident, $var_type:ident, $var_initializer:expr, $value_type:ident, $count_type:ident) => {
-        fn $name(mut s: Stream, column: usize) -> CrushResult<Value> {
-            let mut res: $var_type = $var_initializer;
-            let mut count: i128 = 0;
-            loop {
-                match s.read() {
-                    Ok(row) => {
-                        count += 1;
-                        match row.cells()[column] {
-                            Value::$value_type(i) => res = res + i,
-                            _ => return error("Invalid cell value"),
-                        }
-                    }
-                    Err(_) => break,
-                }
-            }
-            Ok(Value::$value_type(res / (count as $count_type)))
-        }
-    };
-}
-
-avg_function!(avg_int, i128, 0, Integer, i128);
-avg_function!(avg_float, f64, 0.0, Float, f64);
-avg_function!(avg_duration, Duration, Duration::seconds(0), Duration, i32);
-
-pub fn avg(context: ExecutionContext) -> CrushResult<()> {
+pub fn sum(context: ExecutionContext) -> CrushResult<()> {
     match context.input.recv()?.stream() {
         Some(input) => {
             let column = parse(input.types(), &context.arguments)?;
-            match &input.types()[column].cell_type {
-                ValueType::Integer => context.output.send(avg_int(input, column)?),
-                ValueType::Float => context.output.send(avg_float(input, column)?),
-                ValueType::Duration => context.output.send(avg_duration(input, column)?),
-                t => argument_error(
-                    format!(
-                        "Can't calculate average of elements of type {}",
-                        t.to_string()
-                    )
-                    .as_str(),
-                ),
-            }
+            context.output.send(sum_column(input, column)?)
         }
         _ => error("Expected a stream"),
     }
 }
 
-macro_rules! aggr_function {
-    ($name:ident, $value_type:ident, $op:expr) => {
-        fn $name(mut s: Stream, column: usize) -> CrushResult<Value> {
-            let mut res = match s.read()?.cells()[column] {
-                Value::$value_type(i) => i,
-                _ => return error("Invalid cell value, expected an integer"),
-            };
-            while let Ok(row) = s.read() {
-                match row.cells()[column] {
-                    Value::$value_type(i) => res = $op(res, i),
-                    _ => return error("Invalid cell value, expected an integer"),
-                }
-            }
-            Ok(Value::$value_type(res))
-        }
-    };
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::stream::CrushStream;
+    use crate::lib::stream::test_util::{integer_stream, value_stream};
 
-aggr_function!(min_int, Integer, |a, b| std::cmp::min(a, b));
-aggr_function!(min_float, Float, |a, b| std::cmp::min(
-    FloatOrd(a),
-    FloatOrd(b)
-)
-.0);
-aggr_function!(min_duration, Duration, |a, b| std::cmp::min(a, b));
-aggr_function!(min_time, Time, |a, b| std::cmp::min(a, b));
-
-aggr_function!(max_int, Integer, |a, b| std::cmp::max(a, b));
-aggr_function!(max_float, Float, |a, b| std::cmp::max(
-    FloatOrd(a),
-    FloatOrd(b)
-)
-.0);
-aggr_function!(max_duration, Duration, |a, b| std::cmp::max(a, b));
-aggr_function!(max_time, Time, |a, b| std::cmp::max(a, b));
+    fn single_column(cell_type: ValueType, values: Vec<Value>) -> Stream {
+        Box::from(value_stream(cell_type, values))
+    }
 
-pub fn min(context: ExecutionContext) -> CrushResult<()> {
-    match context.input.recv()?.stream() {
-        Some(input) => {
-            let column = parse(input.types(), &context.arguments)?;
-            match &input.types()[column].cell_type {
-                ValueType::Integer => context.output.send(min_int(input, column)?),
-                ValueType::Float => context.output.send(min_float(input, column)?),
-                ValueType::Duration => context.output.send(min_duration(input, column)?),
-                ValueType::Time => context.output.send(min_time(input, column)?),
-                t => argument_error(
-                    format!("Can't pick min of elements of type {}", t.to_string()).as_str(),
+    #[test]
+    fn test_sum() {
+        let column = parse(integer_stream(&[]).types(), &[]).unwrap();
+        assert!(
+            sum_column(Box::from(integer_stream(&[1, 2, 3, 4])), column).unwrap()
+                == Value::Integer(10)
+        );
+        assert!(sum_column(Box::from(integer_stream(&[])), column).unwrap() == Value::Integer(0));
+        assert!(
+            sum_column(
+                single_column(
+                    ValueType::Float,
+                    vec![Value::Float(0.5), Value::Float(1.25)]
                 ),
-            }
-        }
-        _ => error("Expected a stream"),
+                0
+            )
+            .unwrap()
+                == Value::Float(1.75)
+        );
     }
-}
 
-pub fn max(context: ExecutionContext) -> CrushResult<()> {
-    match context.input.recv()?.stream() {
-        Some(input) => {
-            let column = parse(input.types(), &context.arguments)?;
-            match &input.types()[column].cell_type {
-                ValueType::Integer => context.output.send(max_int(input, column)?),
-                ValueType::Float => context.output.send(max_float(input, column)?),
-                ValueType::Duration => context.output.send(max_duration(input, column)?),
-                ValueType::Time => context.output.send(max_time(input, column)?),
-                t => argument_error(
-                    format!("Can't pick max of elements of type {}", t.to_string()).as_str(),
-                ),
-            }
-        }
-        _ => error("Expected a stream"),
+    #[test]
+    fn test_sum_errors() {
+        let strings = single_column(ValueType::String, vec![Value::string("a")]);
+        assert!(sum_column(strings, 0).is_err());
+        let missing = vec![Argument::unnamed(Value::Field(vec!["nope".to_string()]))];
+        assert!(parse(integer_stream(&[]).types(), &missing).is_err());
     }
 }
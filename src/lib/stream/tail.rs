@@ -1,15 +1,26 @@
 use std::collections::VecDeque;
+use std::convert::TryFrom;
 
-use crate::lang::errors::{error, CrushResult};
+use crate::lang::errors::{argument_error, error, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, ExecutionContext};
 use crate::lang::stream::{CrushStream, ValueSender};
 use crate::lang::table::Row;
 
+/// Reads the entire input, keeping only the last lines rows in memory. This means that tail
+/// never terminates when reading from an infinite stream.
 fn run(lines: i128, input: &mut dyn CrushStream, sender: ValueSender) -> CrushResult<()> {
+    if lines < 0 {
+        return argument_error("The number of lines can't be negative");
+    }
+    // The buffer grows as rows arrive, so a huge line count only costs what the input holds
+    let lines = usize::try_from(lines).unwrap_or(usize::MAX);
     let output = sender.initialize(input.types().to_vec())?;
     let mut q: VecDeque<Row> = VecDeque::new();
     while let Ok(row) = input.read() {
-        if q.len() >= lines as usize {
+        if lines == 0 {
+            continue;
+        }
+        if q.len() >= lines {
             q.pop_front();
         }
         q.push_back(row);
@@ -28,3 +39,30 @@ pub fn perform(mut context: ExecutionContext) -> CrushResult<()> {
         None => error("Expected a stream"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::stream::test_util::{collect_rows, first_column_integers, integer_stream};
+
+    #[test]
+    fn test_tail() {
+        let numbers: Vec<i128> = (0..20).collect();
+        let (_, rows) =
+            collect_rows(|sender| run(3, &mut integer_stream(&numbers), sender).unwrap());
+        assert_eq!(first_column_integers(&rows), vec![17, 18, 19]);
+
+        let (_, rows) =
+            collect_rows(|sender| run(0, &mut integer_stream(&numbers), sender).unwrap());
+        assert!(rows.is_empty());
+
+        let (_, rows) =
+            collect_rows(|sender| run(5, &mut integer_stream(&[1, 2]), sender).unwrap());
+        assert_eq!(first_column_integers(&rows), vec![1, 2]);
+
+        let (_, rows) = collect_rows(|sender| {
+            run(100_000_000_000_000, &mut integer_stream(&[1, 2]), sender).unwrap()
+        });
+        assert_eq!(first_column_integers(&rows), vec![1, 2]);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::lang::stream::{channels, ValueSender};
+use crate::lang::table::{ColumnType, Row, Table, TableReader};
+use crate::lang::value::{Value, ValueType};
+
+/// A single column stream of integers, like the output of seq.
+pub fn integer_stream(values: &[i128]) -> TableReader {
+    TableReader::new(Table::new(
+        vec![ColumnType::new("value", ValueType::Integer)],
+        values
+            .iter()
+            .map(|i| Row::new(vec![Value::Integer(*i)]))
+            .collect(),
+    ))
+}
+
+/// A single column stream of arbitrary values of the specified type.
+pub fn value_stream(cell_type: ValueType, values: Vec<Value>) -> TableReader {
+    TableReader::new(Table::new(
+        vec![ColumnType::new("value", cell_type)],
+        values.into_iter().map(|v| Row::new(vec![v])).collect(),
+    ))
+}
+
+/// Run a stream command body and collect every row it outputs. The output is buffered, so
+/// the command must not output more rows than fit in a stream.
+pub fn collect_rows(run: impl FnOnce(ValueSender)) -> (Vec<ColumnType>, Vec<Row>) {
+    let (sender, receiver) = channels();
+    run(sender);
+    let mut stream = receiver
+        .recv()
+        .unwrap()
+        .stream()
+        .expect("Expected a stream");
+    let types = stream.types().to_vec();
+    let mut rows = Vec::new();
+    while let Ok(row) = stream.read() {
+        rows.push(row);
+    }
+    (types, rows)
+}
+
+/// The integers in the first column of each row.
+pub fn first_column_integers(rows: &[Row]) -> Vec<i128> {
+    rows.iter()
+        .map(|row| match &row.cells()[0] {
+            Value::Integer(i) => *i,
+            _ => panic!("Expected an integer"),
+        })
+        .collect()
+}
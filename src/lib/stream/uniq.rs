@@ -1,46 +1,52 @@
 use crate::lang::argument::Argument;
-use crate::lang::errors::{error, CrushResult};
+use crate::lang::errors::{argument_error, error, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, ExecutionContext};
-use crate::lang::printer::Printer;
 use crate::lang::stream::{CrushStream, OutputStream};
+use crate::lang::table::ColumnType;
 use crate::lang::table::ColumnVec;
 use crate::lang::table::Row;
-use crate::lang::{table::ColumnType, value::Value};
-use std::collections::HashSet;
+use crate::lang::value::Value;
 
 fn parse(input_type: &[ColumnType], mut arguments: Vec<Argument>) -> CrushResult<Option<usize>> {
     arguments.check_len_range(0, 1)?;
-    if let Some(f) = arguments.optional_field(0)? {
-        Ok(Some(input_type.find(&f)?))
-    } else {
-        Ok(None)
+    let idx = match arguments.optional_field(0)? {
+        Some(f) => Some(input_type.find(&f)?),
+        None => None,
+    };
+    let compared: Vec<&ColumnType> = match idx {
+        Some(idx) => vec![&input_type[idx]],
+        None => input_type.iter().collect(),
+    };
+    for column in compared {
+        if !column.cell_type.is_comparable() {
+            return argument_error(
+                format!(
+                    "Can't compare values in column {} of type {}",
+                    column.name,
+                    column.cell_type.to_string()
+                )
+                .as_str(),
+            );
+        }
     }
+    Ok(idx)
 }
 
-fn run(
-    idx: Option<usize>,
-    input: &mut dyn CrushStream,
-    output: OutputStream,
-    printer: &Printer,
-) -> CrushResult<()> {
+/// The part of a row that decides whether it is a duplicate of the previous one.
+fn key(idx: Option<usize>, row: &Row) -> Vec<Value> {
     match idx {
-        None => {
-            let mut seen: HashSet<Row> = HashSet::new();
-            while let Ok(row) = input.read() {
-                if !seen.contains(&row) {
-                    seen.insert(row.clone());
-                    printer.handle_error(output.send(row));
-                }
-            }
-        }
-        Some(idx) => {
-            let mut seen: HashSet<Value> = HashSet::new();
-            while let Ok(row) = input.read() {
-                if !seen.contains(&row.cells()[idx]) {
-                    seen.insert(row.cells()[idx].clone());
-                    printer.handle_error(output.send(row));
-                }
-            }
+        None => row.cells().clone(),
+        Some(idx) => vec![row.cells()[idx].clone()],
+    }
+}
+
+fn run(idx: Option<usize>, input: &mut dyn CrushStream, output: OutputStream) -> CrushResult<()> {
+    let mut previous: Option<Vec<Value>> = None;
+    while let Ok(row) = input.read() {
+        let current = key(idx, &row);
+        if previous.as_ref() != Some(&current) {
+            output.send(row)?;
+            previous = Some(current);
         }
     }
     Ok(())
@@ -51,8 +57,42 @@ pub fn uniq(context: ExecutionContext) -> CrushResult<()> {
         Some(mut input) => {
             let idx = parse(input.types(), context.arguments)?;
             let output = context.output.initialize(input.types().to_vec())?;
-            run(idx, input.as_mut(), output, &context.printer)
+            run(idx, input.as_mut(), output)
         }
         _ => error("Expected io to be a stream"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::value::ValueType;
+    use crate::lib::stream::test_util::{collect_rows, first_column_integers, integer_stream};
+
+    #[test]
+    fn test_uniq() {
+        let (_, rows) = collect_rows(|sender| {
+            let mut input = integer_stream(&[1, 1, 2, 2, 2, 1, 3, 3]);
+            let output = sender.initialize(input.types().to_vec()).unwrap();
+            run(None, &mut input, output).unwrap()
+        });
+        assert_eq!(first_column_integers(&rows), vec![1, 2, 1, 3]);
+    }
+
+    #[test]
+    fn test_uniq_requires_comparable_columns() {
+        let types = vec![
+            ColumnType::new("a", ValueType::Integer),
+            ColumnType::new("b", ValueType::List(Box::from(ValueType::Integer))),
+        ];
+        assert!(parse(&types, vec![]).is_err());
+        assert_eq!(
+            parse(
+                &types,
+                vec![Argument::unnamed(Value::Field(vec!["a".to_string()]))]
+            )
+            .unwrap(),
+            Some(0)
+        );
+    }
+}
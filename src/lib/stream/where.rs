@@ -1,11 +1,13 @@
 use crate::lang::argument::ArgumentHandler;
 use crate::lang::command::Command;
 use crate::lang::command::OutputType::Passthrough;
-use crate::lang::errors::{error, CrushResult};
+use crate::lang::errors::{argument_error, error, CrushResult};
 use crate::lang::execution_context::ExecutionContext;
 use crate::lang::stream::{black_hole, channels, empty_channel};
+use crate::lang::table::ColumnVec;
 use crate::lang::{argument::Argument, table::ColumnType};
 use crate::lang::{table::Row, value::Value};
+use crate::lib::comp::{compare, OPERATORS};
 use signature::signature;
 
 #[signature(
@@ -13,19 +15,76 @@ r#where,
 can_block = true,
 output = Passthrough,
 short = "Filter out rows from io based on condition",
-long = "The columns of the row are exported to the environment using the column names.",
+long = "The condition is either a closure or a column, a comparison operator and a value. The columns of the row are exported to the environment of the closure using the column names. The comparison operator is one of ==, !=, <, <=, > and >=.",
 example = "ps | where {status != \"Sleeping\"}")]
 pub struct Where {
+    #[unnamed()]
     #[description("the condition to filter on.")]
-    condition: Command,
+    condition: Vec<Value>,
+}
+
+enum Condition {
+    Closure(Command),
+    Comparison {
+        idx: usize,
+        operator: String,
+        value: Value,
+    },
+}
+
+fn parse_condition(input_type: &[ColumnType], mut condition: Vec<Value>) -> CrushResult<Condition> {
+    match condition.len() {
+        1 => match condition.remove(0) {
+            Value::Command(c) => Ok(Condition::Closure(c)),
+            v => argument_error(
+                format!(
+                    "Expected the condition to be a command, got a {}",
+                    v.value_type().to_string()
+                )
+                .as_str(),
+            ),
+        },
+        3 => {
+            let value = condition.remove(2);
+            match (condition.remove(0), condition.remove(0)) {
+                (Value::Field(field), Value::String(operator)) => {
+                    if !OPERATORS.contains(&operator.as_str()) {
+                        return argument_error(
+                            format!("Unknown comparison operator {}", operator).as_str(),
+                        );
+                    }
+                    Ok(Condition::Comparison {
+                        idx: input_type.find(&field)?,
+                        operator,
+                        value,
+                    })
+                }
+                _ => argument_error(
+                    "Expected the condition to be a column, a comparison operator and a value",
+                ),
+            }
+        }
+        _ => argument_error(
+            "Expected the condition to be a command, or a column, a comparison operator and a value",
+        ),
+    }
 }
 
 fn evaluate(
-    condition: Command,
+    condition: &Condition,
+    row_number: usize,
     row: &Row,
     input_type: &[ColumnType],
     base_context: &ExecutionContext,
 ) -> CrushResult<bool> {
+    let condition = match condition {
+        Condition::Comparison {
+            idx,
+            operator,
+            value,
+        } => return compare(operator, &row.cells()[*idx], value),
+        Condition::Closure(c) => c.copy(),
+    };
     let arguments = row
         .clone()
         .into_vec()
@@ -45,7 +104,14 @@ fn evaluate(
 
     match reciever.recv()? {
         Value::Bool(b) => Ok(b),
-        _ => error("Expected a boolean result"),
+        v => argument_error(
+            format!(
+                "Expected the condition to return a boolean for row {}, got a {}",
+                row_number,
+                v.value_type().to_string()
+            )
+            .as_str(),
+        ),
     }
 }
 
@@ -54,6 +120,7 @@ pub fn r#where(context: ExecutionContext) -> CrushResult<()> {
 
     match context.input.recv()?.stream() {
         Some(mut input) => {
+            let condition = parse_condition(input.types(), cfg.condition)?;
             let base_context = ExecutionContext {
                 input: empty_channel(),
                 output: black_hole(),
@@ -63,8 +130,9 @@ pub fn r#where(context: ExecutionContext) -> CrushResult<()> {
                 printer: context.printer.clone(),
             };
             let output = context.output.initialize(input.types().to_vec())?;
+            let mut row_number = 1;
             while let Ok(row) = input.read() {
-                match evaluate(cfg.condition.copy(), &row, input.types(), &base_context) {
+                match evaluate(&condition, row_number, &row, input.types(), &base_context) {
                     Ok(val) => {
                         if val && output.send(row).is_err() {
                             break;
@@ -72,9 +140,44 @@ pub fn r#where(context: ExecutionContext) -> CrushResult<()> {
                     }
                     Err(e) => base_context.printer.crush_error(e),
                 }
+                row_number += 1;
             }
             Ok(())
         }
         None => error("Expected a stream"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::value::ValueType;
+
+    #[test]
+    fn test_parse_condition() {
+        let types = vec![ColumnType::new("size", ValueType::Integer)];
+        let comparison = vec![
+            Value::Field(vec!["size".to_string()]),
+            Value::string(">"),
+            Value::Integer(5),
+        ];
+        assert!(matches!(
+            parse_condition(&types, comparison).unwrap(),
+            Condition::Comparison { idx: 0, .. }
+        ));
+        let unknown_operator = vec![
+            Value::Field(vec!["size".to_string()]),
+            Value::string("=~"),
+            Value::Integer(5),
+        ];
+        assert!(parse_condition(&types, unknown_operator).is_err());
+        let unknown_column = vec![
+            Value::Field(vec!["name".to_string()]),
+            Value::string("=="),
+            Value::Integer(5),
+        ];
+        assert!(parse_condition(&types, unknown_column).is_err());
+        assert!(parse_condition(&types, vec![Value::Integer(1)]).is_err());
+        assert!(parse_condition(&types, vec![]).is_err());
+    }
+}
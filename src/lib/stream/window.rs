@@ -0,0 +1,119 @@
+use crate::lang::argument::ArgumentHandler;
+use crate::lang::command::OutputType::Unknown;
+use crate::lang::errors::{argument_error, error, CrushResult};
+use crate::lang::execution_context::ExecutionContext;
+use crate::lang::stream::{CrushStream, ValueSender};
+use crate::lang::table::{ColumnType, Row, Table};
+use crate::lang::value::{Value, ValueType};
+use signature::signature;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+#[signature(
+    window,
+    can_block = true,
+    output = Unknown,
+    short = "Return a stream of sliding windows over the input",
+    long = "Each output row has a single column named window, containing a table of size consecutive input rows. If the input has fewer than size rows, nothing is output.",
+    example = "seq 5 | window 3"
+)]
+pub struct Window {
+    #[description("the number of rows in each window.")]
+    #[default(2)]
+    size: i128,
+    #[description("the number of rows the window advances between outputs.")]
+    #[default(1)]
+    step: i128,
+}
+
+pub fn run(
+    size: i128,
+    step: i128,
+    input: &mut dyn CrushStream,
+    sender: ValueSender,
+) -> CrushResult<()> {
+    if size < 1 {
+        return argument_error("The window size must be at least 1");
+    }
+    if step < 1 {
+        return argument_error("The window step must be at least 1");
+    }
+    let size = usize::try_from(size).unwrap_or(usize::MAX);
+    let step = usize::try_from(step).unwrap_or(usize::MAX);
+    let types = input.types().to_vec();
+    let output = sender.initialize(vec![ColumnType::new(
+        "window",
+        ValueType::Table(types.clone()),
+    )])?;
+    let mut buffer: VecDeque<Row> = VecDeque::new();
+    // The number of rows to read after the buffer is full before the next window is sent
+    let mut skip = 0usize;
+    while let Ok(row) = input.read() {
+        if buffer.len() == size {
+            buffer.pop_front();
+        }
+        buffer.push_back(row);
+        if buffer.len() < size {
+            continue;
+        }
+        if skip == 0 {
+            output.send(Row::new(vec![Value::Table(Table::new(
+                types.clone(),
+                buffer.iter().cloned().collect(),
+            ))]))?;
+            skip = step;
+        }
+        skip -= 1;
+    }
+    Ok(())
+}
+
+pub fn window(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Window = Window::parse(context.arguments, &context.printer)?;
+    match context.input.recv()?.stream() {
+        Some(mut input) => run(cfg.size, cfg.step, input.as_mut(), context.output),
+        None => error("Expected a stream"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::stream::test_util::{collect_rows, first_column_integers, integer_stream};
+
+    fn windows(size: i128, step: i128, values: &[i128]) -> Vec<Vec<i128>> {
+        let (_, rows) =
+            collect_rows(|sender| run(size, step, &mut integer_stream(values), sender).unwrap());
+        rows.iter()
+            .map(|row| match &row.cells()[0] {
+                Value::Table(t) => first_column_integers(t.rows()),
+                _ => panic!("Expected a table"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_window() {
+        assert_eq!(
+            windows(3, 1, &[0, 1, 2, 3, 4]),
+            vec![vec![0, 1, 2], vec![1, 2, 3], vec![2, 3, 4]]
+        );
+        assert_eq!(
+            windows(3, 2, &[0, 1, 2, 3, 4]),
+            vec![vec![0, 1, 2], vec![2, 3, 4]]
+        );
+        assert_eq!(
+            windows(2, 3, &[0, 1, 2, 3, 4]),
+            vec![vec![0, 1], vec![3, 4]]
+        );
+        assert!(windows(3, 1, &[0, 1]).is_empty());
+        assert!(windows(100_000_000_000_000, 1, &[0, 1]).is_empty());
+    }
+
+    #[test]
+    fn test_window_arguments() {
+        let (sender, _receiver) = crate::lang::stream::channels();
+        assert!(run(0, 1, &mut integer_stream(&[]), sender.clone()).is_err());
+        assert!(run(2, 0, &mut integer_stream(&[]), sender).is_err());
+    }
+}
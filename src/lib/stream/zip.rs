@@ -1,10 +1,16 @@
 use crate::lang::argument::ArgumentHandler;
 use crate::lang::errors::CrushResult;
 use crate::lang::execution_context::ExecutionContext;
-use crate::lang::stream::Stream;
+use crate::lang::stream::{CrushStream, OutputStream, Stream};
+use crate::lang::table::ColumnType;
 use signature::signature;
 
-#[signature(zip, can_block = true, short = "Combine two streams of data into one")]
+#[signature(
+    zip,
+    can_block = true,
+    short = "Combine two streams of data into one",
+    long = "The output ends when either stream ends. Columns of the second stream whose names are already used by the first stream get a numeric suffix, e.g. value_2."
+)]
 pub struct Zip {
     #[description("the first stream.")]
     first: Stream,
@@ -12,15 +18,83 @@ pub struct Zip {
     second: Stream,
 }
 
+/// The columns of both streams, with any duplicate names in the second stream suffixed to
+/// make them unique.
+fn zip_types(first: &[ColumnType], second: &[ColumnType]) -> Vec<ColumnType> {
+    let mut res = first.to_vec();
+    for column in second {
+        let mut name = column.name.clone();
+        let mut suffix = 2;
+        while res.iter().any(|c| c.name == name) {
+            name = format!("{}_{}", column.name, suffix);
+            suffix += 1;
+        }
+        res.push(ColumnType::new(&name, column.cell_type.clone()));
+    }
+    res
+}
+
+fn run(
+    first: &mut dyn CrushStream,
+    second: &mut dyn CrushStream,
+    output: OutputStream,
+) -> CrushResult<()> {
+    while let Ok(mut row1) = first.read() {
+        match second.read() {
+            Ok(row2) => {
+                row1.append(&mut row2.into_vec());
+                output.send(row1)?;
+            }
+            Err(_) => break,
+        }
+    }
+    Ok(())
+}
+
 pub fn zip(context: ExecutionContext) -> CrushResult<()> {
     let mut cfg: Zip = Zip::parse(context.arguments, &context.printer)?;
-    let mut output_type = Vec::new();
-    output_type.append(&mut cfg.first.types().to_vec());
-    output_type.append(&mut cfg.second.types().to_vec());
-    let output = context.output.initialize(output_type)?;
-    while let (Ok(mut row1), Ok(row2)) = (cfg.first.read(), cfg.second.read()) {
-        row1.append(&mut row2.into_vec());
-        output.send(row1)?;
+    let output = context
+        .output
+        .initialize(zip_types(cfg.first.types(), cfg.second.types()))?;
+    run(cfg.first.as_mut(), cfg.second.as_mut(), output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::value::{Value, ValueType};
+    use crate::lib::stream::test_util::{collect_rows, integer_stream};
+
+    #[test]
+    fn test_zip_types() {
+        let value = ColumnType::new("value", ValueType::Integer);
+        let value_2 = ColumnType::new("value_2", ValueType::String);
+        let names = |types: Vec<ColumnType>| types.into_iter().map(|c| c.name).collect::<Vec<_>>();
+        assert_eq!(
+            names(zip_types(
+                std::slice::from_ref(&value),
+                std::slice::from_ref(&value)
+            )),
+            vec!["value", "value_2"]
+        );
+        assert_eq!(
+            names(zip_types(&[value.clone(), value_2], &[value])),
+            vec!["value", "value_2", "value_3"]
+        );
+    }
+
+    #[test]
+    fn test_zip() {
+        let (types, rows) = collect_rows(|sender| {
+            let mut first = integer_stream(&[0, 1, 2, 3, 4]);
+            let mut second = integer_stream(&[10, 11, 12]);
+            let output = sender
+                .initialize(zip_types(first.types(), second.types()))
+                .unwrap();
+            run(&mut first, &mut second, output).unwrap()
+        });
+        assert_eq!(types.len(), 2);
+        assert_eq!(rows.len(), 3);
+        assert!(rows[2].cells() == &vec![Value::Integer(2), Value::Integer(12)]);
     }
-    Ok(())
 }
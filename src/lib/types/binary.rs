@@ -1,12 +1,18 @@
+use crate::lang::argument::{Argument, ArgumentHandler};
 use crate::lang::command::Command;
 use crate::lang::command::OutputType::{Known, Unknown};
 use crate::lang::command::TypeMap;
-use crate::lang::errors::{mandate, CrushResult};
+use crate::lang::errors::{argument_error, mandate, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, This};
 use crate::lang::value::ValueType;
-use crate::lang::{execution_context::ExecutionContext, value::Value};
+use crate::lang::{execution_context::ExecutionContext, list::List, value::Value};
+use crate::util::bytes::bytes_to_integer;
+use crate::util::hash;
+use crate::util::hex;
+use crate::util::index::clamp_index;
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
+use signature::signature;
 
 fn full(name: &'static str) -> Vec<&'static str> {
     vec!["global", "types", "binary", name]
@@ -15,6 +21,7 @@ fn full(name: &'static str) -> Vec<&'static str> {
 lazy_static! {
     pub static ref METHODS: OrderedMap<String, Command> = {
         let mut res: OrderedMap<String, Command> = OrderedMap::new();
+        let path = vec!["global", "types", "binary"];
         res.declare(
             full("len"),
             len,
@@ -33,6 +40,115 @@ lazy_static! {
             None,
             Unknown,
         );
+        res.declare(
+            full("slice"),
+            slice,
+            false,
+            "binary:slice from:integer [to:integer]",
+            "Returns the bytes in the range [from, to)",
+            Some(
+                r#"    If to is omitted, the slice extends to the end of the binary. Negative
+    indices count from the end of the binary, and indices that are out of bounds
+    are clamped to the binary. It is an error for from to come after to."#,
+            ),
+            Known(ValueType::Binary),
+        );
+        res.declare(
+            full("index_of"),
+            index_of,
+            false,
+            "binary:index_of needle:(binary|string)",
+            "Returns the offset of the first occurrence of needle in this binary",
+            Some("    It is an error if needle does not occur in this binary."),
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("find"),
+            find,
+            false,
+            "binary:find needle:(binary|string)",
+            "Returns the offset of the first occurrence of needle in this binary",
+            Some(
+                r#"    A string needle is searched for as UTF-8. If needle does not occur in this
+    binary, nothing is returned."#,
+            ),
+            Unknown,
+        );
+        res.declare(
+            full("find_all"),
+            find_all,
+            false,
+            "binary:find_all needle:(binary|string)",
+            "Returns a list of the offsets of all occurrences of needle in this binary",
+            Some(
+                r#"    A string needle is searched for as UTF-8. Occurrences don't overlap, the search
+    for the next occurrence starts after the end of the previous one."#,
+            ),
+            Known(ValueType::List(Box::from(ValueType::Integer))),
+        );
+        res.declare(
+            full("split"),
+            split,
+            false,
+            "binary:split delimiter:(binary|string)",
+            "Splits this binary into a list of binaries at every occurrence of delimiter",
+            Some("    A string delimiter is searched for as UTF-8."),
+            Known(ValueType::List(Box::from(ValueType::Binary))),
+        );
+        let _ = ToBase64::declare_method(&mut res, &path);
+        res.declare(
+            full("hex"),
+            hex,
+            false,
+            "binary:hex",
+            "Encode this binary as a string of lowercase hexadecimal digits",
+            None,
+            Known(ValueType::String),
+        );
+        res.declare(
+            full("from_hex"),
+            from_hex,
+            false,
+            "binary:from_hex hex:string",
+            "Decode a string of hexadecimal digits into a binary",
+            Some(
+                r#"    The string must contain two digits for every byte and nothing else.
+
+    Example:
+
+    binary:from_hex "c0ffee""#,
+            ),
+            Known(ValueType::Binary),
+        );
+        let _ = ToInteger::declare_method(&mut res, &path);
+        res.declare(
+            full("sha256"),
+            sha256,
+            false,
+            "binary:sha256",
+            "The SHA-256 digest of this binary",
+            None,
+            Known(ValueType::Binary),
+        );
+        res.declare(
+            full("sha1"),
+            sha1,
+            false,
+            "binary:sha1",
+            "The SHA-1 digest of this binary",
+            None,
+            Known(ValueType::Binary),
+        );
+        res.declare(
+            full("md5"),
+            md5,
+            false,
+            "binary:md5",
+            "The MD5 digest of this binary",
+            None,
+            Known(ValueType::Binary),
+        );
+        let _ = Digest::declare_method(&mut res, &path);
         res
     };
 }
@@ -50,3 +166,311 @@ fn getitem(mut context: ExecutionContext) -> CrushResult<()> {
         *mandate(val.get(idx as usize), "Index out of bounds")? as i128,
     ))
 }
+
+fn slice_bytes(val: &[u8], from: i128, to: Option<i128>) -> CrushResult<Vec<u8>> {
+    let from = clamp_index(from, val.len());
+    let to = to.map(|to| clamp_index(to, val.len())).unwrap_or(val.len());
+    if from > to {
+        return argument_error(format!("Slice start {} is after slice end {}", from, to).as_str());
+    }
+    Ok(val[from..to].to_vec())
+}
+
+fn slice(mut context: ExecutionContext) -> CrushResult<()> {
+    let val = context.this.binary()?;
+    context.arguments.check_len_range(1, 2)?;
+    let from = context.arguments.integer(0)?;
+    let to = context.arguments.optional_integer(1)?;
+    context
+        .output
+        .send(Value::Binary(slice_bytes(&val, from, to)?))
+}
+
+/// The offset of the first occurrence of needle in haystack. An empty needle is found at
+/// offset 0.
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// The offsets of all non-overlapping occurrences of needle in haystack.
+fn find_all_bytes(haystack: &[u8], needle: &[u8]) -> Vec<usize> {
+    let mut res = Vec::new();
+    let mut offset = 0;
+    while let Some(idx) = find_bytes(&haystack[offset..], needle) {
+        res.push(offset + idx);
+        offset += idx + needle.len();
+    }
+    res
+}
+
+fn split_bytes(haystack: &[u8], delimiter: &[u8]) -> Vec<Vec<u8>> {
+    let mut res = Vec::new();
+    let mut start = 0;
+    for idx in find_all_bytes(haystack, delimiter) {
+        res.push(haystack[start..idx].to_vec());
+        start = idx + delimiter.len();
+    }
+    res.push(haystack[start..].to_vec());
+    res
+}
+
+/// The single needle argument of the search methods, a binary or a string. An empty needle
+/// occurs at the start of every binary.
+fn needle(arguments: &mut Vec<Argument>) -> CrushResult<Vec<u8>> {
+    arguments.check_len(1)?;
+    let needle = match arguments.value(0)? {
+        Value::Binary(b) => b,
+        Value::String(s) => s.into_bytes(),
+        v => {
+            return argument_error(
+                format!(
+                    "Expected a binary or a string, got a {}",
+                    v.value_type().to_string()
+                )
+                .as_str(),
+            )
+        }
+    };
+    Ok(needle)
+}
+
+/// Like needle, but for the methods that can't make progress on an empty needle.
+fn non_empty_needle(arguments: &mut Vec<Argument>) -> CrushResult<Vec<u8>> {
+    let needle = needle(arguments)?;
+    if needle.is_empty() {
+        return argument_error("Can't search for an empty needle");
+    }
+    Ok(needle)
+}
+
+fn index_of(mut context: ExecutionContext) -> CrushResult<()> {
+    let needle = needle(&mut context.arguments)?;
+    let val = context.this.binary()?;
+    context.output.send(Value::Integer(mandate(
+        find_bytes(&val, &needle),
+        "The needle does not occur in the binary",
+    )? as i128))
+}
+
+fn find(mut context: ExecutionContext) -> CrushResult<()> {
+    let needle = non_empty_needle(&mut context.arguments)?;
+    let val = context.this.binary()?;
+    match find_bytes(&val, &needle) {
+        Some(idx) => context.output.send(Value::Integer(idx as i128)),
+        None => context.output.send(Value::Empty()),
+    }
+}
+
+fn find_all(mut context: ExecutionContext) -> CrushResult<()> {
+    let needle = non_empty_needle(&mut context.arguments)?;
+    let val = context.this.binary()?;
+    context.output.send(Value::List(List::new(
+        ValueType::Integer,
+        find_all_bytes(&val, &needle)
+            .into_iter()
+            .map(|idx| Value::Integer(idx as i128))
+            .collect(),
+    )))
+}
+
+fn split(mut context: ExecutionContext) -> CrushResult<()> {
+    let delimiter = non_empty_needle(&mut context.arguments)?;
+    let val = context.this.binary()?;
+    context.output.send(Value::List(List::new(
+        ValueType::Binary,
+        split_bytes(&val, &delimiter)
+            .into_iter()
+            .map(Value::Binary)
+            .collect(),
+    )))
+}
+
+#[signature(
+    to_base64,
+    can_block = false,
+    output = Known(ValueType::String),
+    short = "Encode this binary as a base64 string with padding",
+    example = "(bin:from some_file):to_base64 url=true"
+)]
+struct ToBase64 {
+    #[description("use the URL and filename safe alphabet, with - and _ instead of + and /.")]
+    #[default(false)]
+    url: bool,
+}
+
+fn to_base64(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: ToBase64 = ToBase64::parse(context.arguments, &context.printer)?;
+    let config = if cfg.url {
+        base64::URL_SAFE
+    } else {
+        base64::STANDARD
+    };
+    context.output.send(Value::String(base64::encode_config(
+        &context.this.binary()?,
+        config,
+    )))
+}
+
+fn hex(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::String(hex::encode(&context.this.binary()?)))
+}
+
+fn from_hex(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let s = context.arguments.string(0)?;
+    context.output.send(Value::Binary(hex::decode(&s)?))
+}
+
+#[signature(
+    to_integer,
+    can_block = false,
+    output = Known(ValueType::Integer),
+    short = "Decode this binary as a two's complement integer",
+    long = "The binary may be at most 16 bytes long.",
+    example = "(bin:from some_file):to_integer endian=little"
+)]
+struct ToInteger {
+    #[description("the byte order of the encoding.")]
+    #[values("big", "little")]
+    #[default("big")]
+    endian: String,
+}
+
+fn to_integer(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: ToInteger = ToInteger::parse(context.arguments, &context.printer)?;
+    let value = context.this.binary()?;
+    context.output.send(Value::Integer(bytes_to_integer(
+        &value,
+        cfg.endian == "little",
+    )?))
+}
+
+fn digest_this(context: ExecutionContext, algorithm: &str) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let val = context.this.binary()?;
+    context
+        .output
+        .send(Value::Binary(hash::digest(algorithm, &mut &val[..])?))
+}
+
+fn sha256(context: ExecutionContext) -> CrushResult<()> {
+    digest_this(context, "sha256")
+}
+
+fn sha1(context: ExecutionContext) -> CrushResult<()> {
+    digest_this(context, "sha1")
+}
+
+fn md5(context: ExecutionContext) -> CrushResult<()> {
+    digest_this(context, "md5")
+}
+
+#[signature(
+    hash,
+    can_block = true,
+    output = Known(ValueType::Binary),
+    short = "The digest of this binary or binary stream using the specified hash algorithm",
+    long = "Binary streams are hashed as they are read, so they are never held in memory in their entirety.",
+    example = "(binary:from_hex \"c0ffee\"):hash algorithm=sha1"
+)]
+pub struct Digest {
+    #[description("the hash algorithm to use.")]
+    #[values("sha256", "sha1", "md5")]
+    #[default("sha256")]
+    algorithm: String,
+}
+
+/// The hash method of both binaries and binary streams.
+fn hash(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Digest = Digest::parse(context.arguments, &context.printer)?;
+    let digest = match context.this {
+        Some(Value::BinaryStream(mut input)) => hash::digest(&cfg.algorithm, input.as_mut())?,
+        this => hash::digest(&cfg.algorithm, &mut &this.binary()?[..])?,
+    };
+    context.output.send(Value::Binary(digest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::types::test_util::call_method;
+
+    #[test]
+    fn test_slice() {
+        let val = b"hello".to_vec();
+        assert_eq!(slice_bytes(&val, 1, Some(3)).unwrap(), b"el".to_vec());
+        assert_eq!(slice_bytes(&val, -3, Some(-1)).unwrap(), b"ll".to_vec());
+        assert_eq!(slice_bytes(&val, -2, None).unwrap(), b"lo".to_vec());
+        assert_eq!(slice_bytes(&val, -10, Some(10)).unwrap(), b"hello".to_vec());
+        assert_eq!(slice_bytes(&val, 0, None).unwrap(), b"hello".to_vec());
+        assert_eq!(slice_bytes(&val, 2, Some(2)).unwrap(), Vec::<u8>::new());
+        assert_eq!(slice_bytes(&val, 7, None).unwrap(), Vec::<u8>::new());
+        assert!(slice_bytes(&val, 3, Some(1)).is_err());
+        assert!(slice_bytes(&val, -1, Some(2)).is_err());
+    }
+
+    #[test]
+    fn test_find_bytes() {
+        assert_eq!(find_bytes(b"hello", b"ll"), Some(2));
+        assert_eq!(find_bytes(b"hello", b"lo"), Some(3));
+        assert_eq!(find_bytes(b"hello", b"x"), None);
+        assert_eq!(find_bytes(b"hello", b""), Some(0));
+        assert_eq!(find_bytes(b"lo", b"hello"), None);
+    }
+
+    #[test]
+    fn test_index_of_empty_needle() {
+        let hello = || Value::Binary(b"hello".to_vec());
+        let index_of = |needle: Vec<u8>| {
+            call_method(
+                hello(),
+                "index_of",
+                vec![Argument::unnamed(Value::Binary(needle))],
+            )
+        };
+        assert!(index_of(vec![]).unwrap() == Value::Integer(0));
+        assert!(index_of(b"lo".to_vec()).unwrap() == Value::Integer(3));
+        assert!(index_of(b"x".to_vec()).is_err());
+        assert!(call_method(
+            hello(),
+            "find_all",
+            vec![Argument::unnamed(Value::Binary(vec![]))]
+        )
+        .is_err());
+        assert!(call_method(
+            hello(),
+            "find",
+            vec![Argument::unnamed(Value::Binary(vec![]))]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_find_all_bytes() {
+        assert_eq!(find_all_bytes(b"a,b,,c", b","), vec![1, 3, 4]);
+        assert_eq!(find_all_bytes(b"aaaa", b"aa"), vec![0, 2]);
+        assert_eq!(find_all_bytes(b"aaa", b"aa"), vec![0]);
+        assert!(find_all_bytes(b"abc", b"x").is_empty());
+    }
+
+    #[test]
+    fn test_split_bytes() {
+        assert_eq!(
+            split_bytes(b"a,b,,c", b","),
+            vec![b"a".to_vec(), b"b".to_vec(), vec![], b"c".to_vec()]
+        );
+        assert_eq!(
+            split_bytes(b"a\r\nb\r\n", b"\r\n"),
+            vec![b"a".to_vec(), b"b".to_vec(), vec![]]
+        );
+        assert_eq!(split_bytes(b"abc", b"x"), vec![b"abc".to_vec()]);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::lang::argument::ArgumentHandler;
+use crate::lang::command::Command;
+use crate::lang::command::OutputType::Known;
+use crate::lang::command::TypeMap;
+use crate::lang::errors::{to_crush_error, CrushResult};
+use crate::lang::execution_context::{ArgumentVector, This};
+use crate::lang::value::ValueType;
+use crate::lang::{execution_context::ExecutionContext, value::Value};
+use crate::lib::types::binary::Digest;
+use crate::util::hash;
+use lazy_static::lazy_static;
+use ordered_map::OrderedMap;
+use std::fs::File;
+
+fn full(name: &'static str) -> Vec<&'static str> {
+    vec!["global", "types", "binary_stream", name]
+}
+
+lazy_static! {
+    pub static ref METHODS: OrderedMap<String, Command> = {
+        let mut res: OrderedMap<String, Command> = OrderedMap::new();
+        let path = vec!["global", "types", "binary_stream"];
+        res.declare(
+            full("save"),
+            save,
+            true,
+            "binary_stream:save file:file",
+            "Write the contents of this binary stream to the specified file",
+            Some(
+                r#"    The stream is copied to the file piece by piece, so it is never held in memory
+    in its entirety. Returns the file that was written."#,
+            ),
+            Known(ValueType::File),
+        );
+        res.declare(
+            full("sha256"),
+            sha256,
+            true,
+            "binary_stream:sha256",
+            "The SHA-256 digest of this binary stream",
+            None,
+            Known(ValueType::Binary),
+        );
+        res.declare(
+            full("sha1"),
+            sha1,
+            true,
+            "binary_stream:sha1",
+            "The SHA-1 digest of this binary stream",
+            None,
+            Known(ValueType::Binary),
+        );
+        res.declare(
+            full("md5"),
+            md5,
+            true,
+            "binary_stream:md5",
+            "The MD5 digest of this binary stream",
+            None,
+            Known(ValueType::Binary),
+        );
+        let _ = Digest::declare_method(&mut res, &path);
+        res
+    };
+}
+
+fn save(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let path = context.arguments.file(0)?;
+    let mut input = context.this.binary_stream()?;
+    let mut file = to_crush_error(File::create(&path))?;
+    to_crush_error(std::io::copy(input.as_mut(), &mut file))?;
+    context.output.send(Value::File(path))
+}
+
+/// The stream is hashed as it is read, so that arbitrarily large streams can be hashed in
+/// bounded memory.
+fn digest_this(context: ExecutionContext, algorithm: &str) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let mut input = context.this.binary_stream()?;
+    context
+        .output
+        .send(Value::Binary(hash::digest(algorithm, input.as_mut())?))
+}
+
+fn sha256(context: ExecutionContext) -> CrushResult<()> {
+    digest_this(context, "sha256")
+}
+
+fn sha1(context: ExecutionContext) -> CrushResult<()> {
+    digest_this(context, "sha1")
+}
+
+fn md5(context: ExecutionContext) -> CrushResult<()> {
+    digest_this(context, "md5")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::argument::Argument;
+    use crate::lang::binary::BinaryReader;
+    use crate::lib::types::test_util::call_method;
+    use crate::util::hex;
+    use std::fs;
+
+    #[test]
+    fn test_save() {
+        let path = std::env::temp_dir().join(format!("crush-save-{}", std::process::id()));
+        let data: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+        let res = call_method(
+            Value::BinaryStream(<dyn BinaryReader>::vec(&data)),
+            "save",
+            vec![Argument::unnamed(Value::File(path.clone()))],
+        );
+        let written = fs::read(&path);
+        let _ = fs::remove_file(&path);
+        assert!(matches!(res.unwrap(), Value::File(p) if p == path));
+        assert_eq!(written.unwrap(), data);
+    }
+
+    #[test]
+    fn test_hash() {
+        let res = call_method(
+            Value::BinaryStream(<dyn BinaryReader>::vec(&b"abc".to_vec())),
+            "hash",
+            vec![Argument::named("algorithm", Value::string("md5"))],
+        );
+        match res.unwrap() {
+            Value::Binary(b) => assert_eq!(hex::encode(&b), "900150983cd24fb0d6963f7d28e17f72"),
+            _ => panic!("Expected a binary"),
+        }
+    }
+}
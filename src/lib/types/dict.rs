@@ -1,12 +1,16 @@
+use crate::lang::argument::{Argument, ArgumentHandler};
 use crate::lang::command::Command;
 use crate::lang::command::OutputType::{Known, Unknown};
 use crate::lang::command::TypeMap;
 use crate::lang::errors::{argument_error, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, ExecutionContext, This};
+use crate::lang::stream::CrushStream;
 use crate::lang::value::Value;
-use crate::lang::{dict::Dict, value::ValueType};
+use crate::lang::{dict::Dict, dict::DictReader, list::List, value::ValueType};
+use crate::lib::types::{call, common_type};
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
+use signature::signature;
 
 fn full(name: &'static str) -> Vec<&'static str> {
     vec!["global", "types", "dict", name]
@@ -15,6 +19,7 @@ fn full(name: &'static str) -> Vec<&'static str> {
 lazy_static! {
     pub static ref METHODS: OrderedMap<String, Command> = {
         let mut res: OrderedMap<String, Command> = OrderedMap::new();
+        let path = vec!["global", "types", "dict"];
         res.declare(
             full("new"),
             new,
@@ -72,15 +77,98 @@ lazy_static! {
             None,
             Unknown,
         );
+        res.declare(
+            full("get"),
+            get,
+            false,
+            "dict:get key [default=value]",
+            "Return the value the specified key is mapped to",
+            Some(
+                r#"    If the key is not present in the dict, the default is returned, or nothing if
+    there is no default. The default must be of the value type of the dict.
+
+    Example:
+
+    d:get "a" default=0"#,
+            ),
+            Unknown,
+        );
+        res.declare(
+            full("set_default"),
+            set_default,
+            false,
+            "dict:set_default key value",
+            "Map the key to value unless it is already present, and return the value it maps to",
+            Some(
+                r#"    Example:
+
+    counts := ((dict string integer):new)
+    counts["a"] = (counts:set_default "a" 0) + 1"#,
+            ),
+            Unknown,
+        );
         res.declare(
             full("remove"),
             remove,
             false,
             "dict:remove key",
-            "Remove a mapping from the dict",
+            "Remove a mapping from the dict and return the value it mapped to",
+            Some("    If the key is not present in the dict, the output is empty."),
+            Unknown,
+        );
+        res.declare(
+            full("contains"),
+            contains,
+            false,
+            "dict:contains key",
+            "True if this dict contains a mapping for the specified key",
+            None,
+            Known(ValueType::Bool),
+        );
+        res.declare(
+            full("items"),
+            items,
+            true,
+            "dict:items",
+            "Return a stream of the mappings in this dict",
+            Some("    The stream has the columns key and value."),
+            Unknown,
+        );
+        res.declare(
+            full("keys"),
+            keys,
+            false,
+            "dict:keys",
+            "Return a list of the keys in this dict",
             None,
             Unknown,
         );
+        res.declare(
+            full("values"),
+            values,
+            false,
+            "dict:values",
+            "Return a list of the values in this dict",
+            None,
+            Unknown,
+        );
+        let _ = Merge::declare_method(&mut res, &path);
+        res.declare(
+            full("map_values"),
+            map_values,
+            true,
+            "dict:map_values function:command",
+            "Return a new dict with the result of calling function on each value of this dict",
+            Some(
+                r#"    All results must be of the same type, which becomes the value type of the new
+    dict. The keys are unchanged.
+
+    Example:
+
+    prices:map_values {|price| price * 2}"#,
+            ),
+            Unknown,
+        );
         res.declare(
             full("clone"),
             clone,
@@ -117,6 +205,15 @@ lazy_static! {
             None,
             Known(ValueType::Type),
         );
+        res.declare(
+            full("to_struct"),
+            to_struct,
+            false,
+            "dict:to_struct",
+            "Create a struct with one field for each mapping in this dict",
+            Some("    All keys of the dict must be strings."),
+            Known(ValueType::Struct),
+        );
         res
     };
 }
@@ -180,15 +277,177 @@ fn getitem(mut context: ExecutionContext) -> CrushResult<()> {
     Ok(())
 }
 
+/// Remove the optional named default argument from the arguments of a call and return it.
+fn default_argument(arguments: &mut Vec<Argument>) -> Option<Value> {
+    arguments
+        .iter()
+        .position(|a| a.argument_type.as_deref() == Some("default"))
+        .map(|idx| arguments.remove(idx).value)
+}
+
+fn get(mut context: ExecutionContext) -> CrushResult<()> {
+    let named_default = default_argument(&mut context.arguments);
+    context.arguments.check_len_range(1, 2)?;
+    let dict = context.this.dict()?;
+    let key = context.arguments.value(0)?;
+    let default = match named_default {
+        Some(default) => {
+            context.arguments.check_len(1)?;
+            Some(default)
+        }
+        None => context.arguments.optional_value(1)?,
+    };
+    dict.check_key(&key)?;
+    if let Some(default) = &default {
+        dict.check_value(default)?;
+    }
+    context
+        .output
+        .send(dict.get(&key).or(default).unwrap_or_else(Value::Empty))
+}
+
+fn set_default(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(2)?;
+    let dict = context.this.dict()?;
+    let value = context.arguments.value(1)?;
+    let key = context.arguments.value(0)?;
+    context.output.send(dict.set_default(key, value)?)
+}
+
+fn remove_key(dict: &Dict, key: &Value) -> CrushResult<Value> {
+    dict.check_key(key)?;
+    Ok(dict.remove(key).unwrap_or_else(Value::Empty))
+}
+
 fn remove(mut context: ExecutionContext) -> CrushResult<()> {
     context.arguments.check_len(1)?;
     let dict = context.this.dict()?;
     let key = context.arguments.value(0)?;
-    let o = context.output;
-    dict.remove(&key).map(|c| o.send(c));
+    context.output.send(remove_key(&dict, &key)?)
+}
+
+fn contains(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let dict = context.this.dict()?;
+    let key = context.arguments.value(0)?;
+    dict.check_key(&key)?;
+    context.output.send(Value::Bool(dict.contains(&key)))
+}
+
+fn items(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let mut reader = DictReader::new(context.this.dict()?);
+    let output = context.output.initialize(reader.types().to_vec())?;
+    while let Ok(row) = reader.read() {
+        output.send(row)?;
+    }
     Ok(())
 }
 
+#[signature(
+    merge,
+    can_block = false,
+    short = "Merge the mappings of another dict into this one",
+    long = "Unless in_place is set, a new dict is returned and this one is left unchanged. Both dicts must have the same key and value types. Keep decides what happens to keys present in both dicts: last keeps the value of the other dict, first keeps the value of this dict, and error makes the merge fail without modifying anything.",
+    example = "defaults:merge overrides"
+)]
+struct Merge {
+    #[description("the dict to merge into this one.")]
+    other: Value,
+    #[description("which value to keep for keys present in both dicts.")]
+    #[values("last", "first", "error")]
+    #[default("last")]
+    keep: String,
+    #[description("modify this dict instead of returning a new one.")]
+    #[default(false)]
+    in_place: bool,
+}
+
+/// Merge the mappings of other into target according to the keep policy of dict:merge.
+fn merge_into(target: &Dict, other: &Dict, keep: &str) -> CrushResult<()> {
+    if target.dict_type() != other.dict_type() {
+        return argument_error(
+            format!(
+                "Can't merge a {} into a {}",
+                other.dict_type().to_string(),
+                target.dict_type().to_string()
+            )
+            .as_str(),
+        );
+    }
+    let elements = other.elements();
+    if keep == "error" {
+        if let Some((key, _)) = elements.iter().find(|(k, _)| target.contains(k)) {
+            return argument_error(
+                format!("The key {} is present in both dicts", key.to_string()).as_str(),
+            );
+        }
+    }
+    for (key, value) in elements {
+        if keep == "first" {
+            target.set_default(key, value)?;
+        } else {
+            target.insert(key, value)?;
+        }
+    }
+    Ok(())
+}
+
+fn merge(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Merge = Merge::parse(context.arguments, &context.printer)?;
+    let this = context.this.dict()?;
+    let other = match cfg.other {
+        Value::Dict(d) => d,
+        v => {
+            return argument_error(
+                format!(
+                    "Expected a dict to merge, got a {}",
+                    v.value_type().to_string()
+                )
+                .as_str(),
+            )
+        }
+    };
+    let target = if cfg.in_place { this } else { this.copy() };
+    merge_into(&target, &other, &cfg.keep)?;
+    context.output.send(Value::Dict(target))
+}
+
+fn map_values(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let function = context.arguments.command(0)?;
+    let dict = context.this.clone().dict()?;
+    let mut keys = Vec::new();
+    let mut values = Vec::new();
+    for (key, value) in dict.elements() {
+        keys.push(key);
+        values.push(call(&function, vec![value], &context)?);
+    }
+    let res = Dict::new(dict.key_type(), common_type(&values)?);
+    for (key, value) in keys.into_iter().zip(values) {
+        res.insert(key, value)?;
+    }
+    context.output.send(Value::Dict(res))
+}
+
+fn keys(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let dict = context.this.dict()?;
+    context.output.send(Value::List(List::new(
+        dict.key_type(),
+        dict.elements().into_iter().map(|(k, _)| k).collect(),
+    )))
+}
+
+fn values(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let dict = context.this.dict()?;
+    context.output.send(Value::List(List::new(
+        dict.value_type(),
+        dict.elements().into_iter().map(|(_, v)| v).collect(),
+    )))
+}
+
 fn len(context: ExecutionContext) -> CrushResult<()> {
     context.arguments.check_len(0)?;
     context
@@ -229,3 +488,99 @@ fn value_type(context: ExecutionContext) -> CrushResult<()> {
         .output
         .send(Value::Type(context.this.dict()?.value_type()))
 }
+
+fn to_struct(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Dict(context.this.dict()?).convert(ValueType::Struct)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::types::test_util::call_method;
+
+    #[test]
+    fn test_default_argument() {
+        let mut arguments = vec![
+            Argument::named("default", Value::Integer(0)),
+            Argument::unnamed(Value::string("a")),
+        ];
+        assert!(default_argument(&mut arguments) == Some(Value::Integer(0)));
+        assert_eq!(arguments.len(), 1);
+        assert!(default_argument(&mut arguments).is_none());
+    }
+
+    fn dict_of(entries: &[(&str, i128)]) -> Dict {
+        let dict = Dict::new(ValueType::String, ValueType::Integer);
+        for (key, value) in entries {
+            dict.insert(Value::string(key), Value::Integer(*value))
+                .unwrap();
+        }
+        dict
+    }
+
+    #[test]
+    fn test_items() {
+        let dict = dict_of(&[("a", 1), ("b", 2)]);
+        let mut stream = call_method(Value::Dict(dict), "items", vec![])
+            .unwrap()
+            .stream()
+            .unwrap();
+        let types = stream.types().to_vec();
+        assert_eq!(types[0].name, "key");
+        assert_eq!(types[0].cell_type, ValueType::String);
+        assert_eq!(types[1].name, "value");
+        assert_eq!(types[1].cell_type, ValueType::Integer);
+        let mut rows = Vec::new();
+        while let Ok(row) = stream.read() {
+            rows.push(row);
+        }
+        assert_eq!(rows.len(), 2);
+        assert!(
+            rows.iter()
+                .any(|row| row.cells()[0] == Value::string("b")
+                    && row.cells()[1] == Value::Integer(2))
+        );
+    }
+
+    #[test]
+    fn test_merge_into() {
+        let other = dict_of(&[("b", 20), ("c", 30)]);
+
+        let last = dict_of(&[("a", 1), ("b", 2)]);
+        merge_into(&last, &other, "last").unwrap();
+        assert!(last.get(&Value::string("b")) == Some(Value::Integer(20)));
+        assert_eq!(last.len(), 3);
+
+        let first = dict_of(&[("a", 1), ("b", 2)]);
+        merge_into(&first, &other, "first").unwrap();
+        assert!(first.get(&Value::string("b")) == Some(Value::Integer(2)));
+        assert!(first.get(&Value::string("c")) == Some(Value::Integer(30)));
+
+        let error = dict_of(&[("a", 1), ("b", 2)]);
+        assert!(merge_into(&error, &other, "error").is_err());
+        assert_eq!(error.len(), 2);
+        merge_into(&error, &dict_of(&[("d", 4)]), "error").unwrap();
+        assert_eq!(error.len(), 3);
+
+        let floats = Dict::new(ValueType::String, ValueType::Float);
+        assert!(merge_into(&floats, &other, "last").is_err());
+    }
+
+    #[test]
+    fn test_remove_key() {
+        let dict = Dict::new(ValueType::String, ValueType::Integer);
+        dict.insert(Value::string("a"), Value::Integer(1)).unwrap();
+        dict.insert(Value::string("b"), Value::Integer(2)).unwrap();
+        assert!(remove_key(&dict, &Value::string("a")).unwrap() == Value::Integer(1));
+        assert_eq!(dict.len(), 1);
+        assert!(matches!(
+            remove_key(&dict, &Value::string("a")).unwrap(),
+            Value::Empty()
+        ));
+        assert!(remove_key(&dict, &Value::Integer(2)).is_err());
+        assert_eq!(dict.len(), 1);
+    }
+}
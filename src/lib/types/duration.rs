@@ -2,10 +2,15 @@ use crate::lang::argument::ArgumentHandler;
 use crate::lang::command::Command;
 use crate::lang::command::OutputType::{Known, Unknown};
 use crate::lang::command::TypeMap;
-use crate::lang::errors::{argument_error, CrushResult};
+use crate::lang::errors::{argument_error, mandate, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, This};
+use crate::lang::r#struct::Struct;
 use crate::lang::value::ValueType;
 use crate::lang::{execution_context::ExecutionContext, value::Value};
+use crate::util::time::{
+    duration_format_opts, duration_from_nanos, duration_nanos, duration_parts, duration_seconds,
+    parse_duration, DurationStyle, NANOS_IN_DAY, NANOS_IN_HOUR, NANOS_IN_MINUTE, NANOS_IN_SECOND,
+};
 use chrono::Duration;
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
@@ -44,6 +49,59 @@ lazy_static! {
             None,
             Known(ValueType::Duration));
         let _ = New::declare_method(&mut res, &path); // TODO why unused?
+        let _ = Of::declare_method(&mut res, &path);
+        let _ = Format::declare_method(&mut res, &path);
+        let _ = Parse::declare_method(&mut res, &path);
+        res.declare(
+            full("total_seconds"),
+            total_seconds,
+            false,
+            "duration:total_seconds",
+            "The number of seconds in this duration, including the fractional part",
+            None,
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("total_milliseconds"),
+            total_milliseconds,
+            false,
+            "duration:total_milliseconds",
+            "The number of whole milliseconds in this duration, rounded towards zero",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("seconds"),
+            seconds,
+            false,
+            "duration:seconds",
+            "The number of whole seconds in this duration, rounded towards zero",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("micros"),
+            micros,
+            false,
+            "duration:micros",
+            "The number of whole microseconds in this duration, rounded towards zero",
+            Some(r#"    It is an error if the number of microseconds doesn't fit in 64 bits."#),
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("decompose"),
+            decompose,
+            false,
+            "duration:decompose",
+            "Split this duration into the components used when printing it",
+            Some(
+                r#"    The result is a struct with the fields sign, years, days, hours, minutes,
+    seconds and microseconds. A year is 365 days. The sign is -1 for negative
+    durations, 0 for the empty duration and 1 otherwise, and all other fields are
+    never negative."#,
+            ),
+            Known(ValueType::Struct),
+        );
 /*
         res.declare(full("new"),
             new, false,
@@ -66,19 +124,78 @@ lazy_static! {
     };
 }
 
-binary_op!(
-    add,
-    duration,
-    Duration,
-    Duration,
-    |a, b| a + b,
-    Time,
-    Time,
-    |a, b| b + a
-);
-binary_op!(sub, duration, Duration, Duration, |a, b| a - b);
-binary_op!(mul, duration, Integer, Duration, |a, b| a * (b as i32));
-binary_op!(div, duration, Integer, Duration, |a, b| a / (b as i32));
+fn checked_add(a: Duration, b: Duration) -> CrushResult<Duration> {
+    mandate(a.checked_add(&b), "Duration overflow")
+}
+
+fn checked_sub(a: Duration, b: Duration) -> CrushResult<Duration> {
+    mandate(a.checked_sub(&b), "Duration overflow")
+}
+
+fn checked_mul(d: Duration, factor: i128) -> CrushResult<Duration> {
+    mandate(
+        duration_nanos(&d)
+            .checked_mul(factor)
+            .and_then(duration_from_nanos),
+        "Duration overflow",
+    )
+}
+
+fn checked_div(d: Duration, divisor: i128) -> CrushResult<Duration> {
+    if divisor == 0 {
+        return argument_error("Can't divide a duration by zero");
+    }
+    mandate(
+        duration_from_nanos(duration_nanos(&d) / divisor),
+        "Duration overflow",
+    )
+}
+
+fn add(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let this = context.this.duration()?;
+    match context.arguments.value(0)? {
+        Value::Duration(d) => context.output.send(Value::Duration(checked_add(this, d)?)),
+        Value::Time(t) => context.output.send(Value::Time(mandate(
+            t.checked_add_signed(this),
+            "Time overflow",
+        )?)),
+        v => argument_error(
+            format!(
+                "Expected a duration or a time, found a {}",
+                v.value_type().to_string()
+            )
+            .as_str(),
+        ),
+    }
+}
+
+fn sub(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let this = context.this.duration()?;
+    let delta = context.arguments.duration(0)?;
+    context
+        .output
+        .send(Value::Duration(checked_sub(this, delta)?))
+}
+
+fn mul(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let this = context.this.duration()?;
+    let factor = context.arguments.integer(0)?;
+    context
+        .output
+        .send(Value::Duration(checked_mul(this, factor)?))
+}
+
+fn div(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let this = context.this.duration()?;
+    let divisor = context.arguments.integer(0)?;
+    context
+        .output
+        .send(Value::Duration(checked_div(this, divisor)?))
+}
 
 #[allow(unused)]
 fn to_duration(a: i64, t: &str) -> CrushResult<chrono::Duration> {
@@ -120,17 +237,174 @@ struct New {
     days: i64,
 }
 
+/// Sum counts of units, each given as its length in nanoseconds, into a duration.
+fn sum_units(counts: &[(i64, i128)]) -> CrushResult<Duration> {
+    let nanos = counts
+        .iter()
+        .map(|(count, unit)| *count as i128 * unit)
+        .sum();
+    mandate(duration_from_nanos(nanos), "Duration overflow")
+}
+
 fn new(context: ExecutionContext) -> CrushResult<()> {
     let cfg: New = New::parse(context.arguments, &context.printer)?;
+    context.output.send(Value::Duration(sum_units(&[
+        (cfg.nanoseconds, 1),
+        (cfg.microseconds, 1_000),
+        (cfg.milliseconds, 1_000_000),
+        (cfg.seconds, NANOS_IN_SECOND),
+        (cfg.minutes, NANOS_IN_MINUTE),
+        (cfg.hours, NANOS_IN_HOUR),
+        (cfg.days, NANOS_IN_DAY),
+    ])?))
+}
+
+#[signature(
+    of,
+    can_block = false,
+    output = Known(ValueType::Duration),
+    short = "Create a new duration from the sum of the specified units",
+    example = "duration:of hours=1 minutes=30"
+)]
+struct Of {
+    #[description("the number of seconds in the duration.")]
+    #[default(0i64)]
+    seconds: i64,
+    #[description("the number of minutes in the duration.")]
+    #[default(0i64)]
+    minutes: i64,
+    #[description("the number of hours in the duration.")]
+    #[default(0i64)]
+    hours: i64,
+    #[description("the number of days in the duration.")]
+    #[default(0i64)]
+    days: i64,
+}
 
-    let res = Duration::nanoseconds(cfg.nanoseconds)
-        + Duration::microseconds(cfg.microseconds)
-        + Duration::milliseconds(cfg.milliseconds)
-        + Duration::seconds(cfg.seconds)
-        + Duration::minutes(cfg.minutes)
-        + Duration::hours(cfg.hours)
-        + Duration::days(cfg.days);
-    context.output.send(Value::Duration(res))
+fn of(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Of = Of::parse(context.arguments, &context.printer)?;
+    context.output.send(Value::Duration(sum_units(&[
+        (cfg.seconds, NANOS_IN_SECOND),
+        (cfg.minutes, NANOS_IN_MINUTE),
+        (cfg.hours, NANOS_IN_HOUR),
+        (cfg.days, NANOS_IN_DAY),
+    ])?))
+}
+
+#[signature(
+    format,
+    can_block = false,
+    output = Known(ValueType::String),
+    short = "Format this duration in the specified style",
+    long = "The human style is the one used when printing durations, e.g. 1:30:00. The iso style is an ISO-8601 duration, e.g. PT1H30M, and the seconds style is the number of seconds, e.g. 5400. Negative durations start with a minus sign in every style.",
+    example = "(duration:of minutes=90):format style=iso"
+)]
+struct Format {
+    #[description("the style to format the duration in.")]
+    #[values("human", "iso", "seconds")]
+    #[default("human")]
+    style: String,
+}
+
+#[signature(
+    parse,
+    can_block = false,
+    output = Known(ValueType::Duration),
+    short = "Parse a duration",
+    long = "The duration is either in the format used when printing durations, e.g. 1d2:30:00, or a sequence of numbers with the units ms, s, m, h or d, e.g. 1h30m. Either may start with a minus sign.",
+    example = "duration:parse \"1h30m\""
+)]
+struct Parse {
+    #[description("the duration to parse.")]
+    text: String,
+}
+
+fn parse(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Parse = Parse::parse(context.arguments, &context.printer)?;
+    context
+        .output
+        .send(Value::Duration(parse_duration(&cfg.text)?))
+}
+
+fn format(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Format = Format::parse(context.arguments, &context.printer)?;
+    let style = match cfg.style.as_str() {
+        "iso" => DurationStyle::Iso,
+        "seconds" => DurationStyle::Seconds,
+        _ => DurationStyle::Human,
+    };
+    context.output.send(Value::String(duration_format_opts(
+        &context.this.duration()?,
+        style,
+    )))
+}
+
+fn total_seconds(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Float(duration_seconds(&context.this.duration()?)))
+}
+
+fn total_milliseconds(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context.output.send(Value::Integer(
+        context.this.duration()?.num_milliseconds() as i128
+    ))
+}
+
+fn whole_micros(d: &Duration) -> CrushResult<i128> {
+    mandate(
+        d.num_microseconds().map(|n| n as i128),
+        "The duration is too long to be expressed in microseconds",
+    )
+}
+
+fn seconds(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context.output.send(Value::Integer(
+        context.this.duration()?.num_seconds() as i128
+    ))
+}
+
+fn micros(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Integer(whole_micros(&context.this.duration()?)?))
+}
+
+fn decomposed(d: &Duration) -> Struct {
+    let parts = duration_parts(d);
+    let sign = if parts.negative {
+        -1
+    } else if duration_nanos(d) == 0 {
+        0
+    } else {
+        1
+    };
+    Struct::new(
+        vec![
+            ("sign".to_string(), Value::Integer(sign)),
+            ("years".to_string(), Value::Integer(parts.years)),
+            ("days".to_string(), Value::Integer(parts.days)),
+            ("hours".to_string(), Value::Integer(parts.hours)),
+            ("minutes".to_string(), Value::Integer(parts.minutes)),
+            ("seconds".to_string(), Value::Integer(parts.seconds)),
+            (
+                "microseconds".to_string(),
+                Value::Integer(parts.nanoseconds / 1000),
+            ),
+        ],
+        None,
+    )
+}
+
+fn decompose(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Struct(decomposed(&context.this.duration()?)))
 }
 
 fn neg(context: ExecutionContext) -> CrushResult<()> {
@@ -139,3 +413,89 @@ fn neg(context: ExecutionContext) -> CrushResult<()> {
         .output
         .send(Value::Duration(-context.this.duration()?))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_arithmetic() {
+        assert_eq!(
+            checked_add(Duration::seconds(90), Duration::seconds(30)).unwrap(),
+            Duration::minutes(2)
+        );
+        assert_eq!(
+            checked_sub(Duration::seconds(1), Duration::seconds(3)).unwrap(),
+            Duration::seconds(-2)
+        );
+        assert!(checked_add(Duration::max_value(), Duration::seconds(1)).is_err());
+        assert!(checked_sub(Duration::min_value(), Duration::seconds(1)).is_err());
+    }
+
+    #[test]
+    fn test_whole_units() {
+        let d = Duration::days(3) + Duration::milliseconds(1500) + Duration::nanoseconds(999);
+        assert_eq!(d.num_seconds(), 3 * 86_400 + 1);
+        assert_eq!(d.num_milliseconds(), 3 * 86_400_000 + 1500);
+        assert_eq!(whole_micros(&d).unwrap(), 3 * 86_400_000_000 + 1_500_000);
+        assert_eq!(
+            whole_micros(&-d).unwrap(),
+            -(3 * 86_400_000_000 + 1_500_000)
+        );
+        assert!(whole_micros(&Duration::days(1 << 30)).is_err());
+        assert!(whole_micros(&Duration::max_value()).is_err());
+    }
+
+    #[test]
+    fn test_checked_mul_div() {
+        assert_eq!(
+            checked_mul(Duration::minutes(5), 3).unwrap(),
+            Duration::minutes(15)
+        );
+        assert_eq!(
+            checked_mul(Duration::nanoseconds(7), -2).unwrap(),
+            Duration::nanoseconds(-14)
+        );
+        assert!(checked_mul(Duration::days(1), 1 << 40).is_err());
+        assert!(checked_mul(Duration::seconds(1), i128::MAX).is_err());
+        assert_eq!(
+            checked_div(Duration::hours(1), 4).unwrap(),
+            Duration::minutes(15)
+        );
+        assert_eq!(
+            checked_div(Duration::nanoseconds(7), 2).unwrap(),
+            Duration::nanoseconds(3)
+        );
+        assert!(checked_div(Duration::hours(1), 0).is_err());
+    }
+
+    #[test]
+    fn test_sum_units() {
+        assert_eq!(
+            sum_units(&[
+                (1, NANOS_IN_HOUR),
+                (30, NANOS_IN_MINUTE),
+                (-1, NANOS_IN_SECOND)
+            ])
+            .unwrap(),
+            Duration::seconds(5399)
+        );
+        assert!(sum_units(&[(i64::MAX, NANOS_IN_DAY)]).is_err());
+    }
+
+    #[test]
+    fn test_decompose() {
+        let d = decomposed(&Duration::milliseconds(
+            -(((3 * 24 + 2) * 60 + 5) * 60_000 + 6_007),
+        ));
+        assert!(d.get("sign") == Some(Value::Integer(-1)));
+        assert!(d.get("years") == Some(Value::Integer(0)));
+        assert!(d.get("days") == Some(Value::Integer(3)));
+        assert!(d.get("hours") == Some(Value::Integer(2)));
+        assert!(d.get("minutes") == Some(Value::Integer(5)));
+        assert!(d.get("seconds") == Some(Value::Integer(6)));
+        assert!(d.get("microseconds") == Some(Value::Integer(7000)));
+        assert!(decomposed(&Duration::seconds(0)).get("sign") == Some(Value::Integer(0)));
+        assert!(decomposed(&Duration::days(366)).get("years") == Some(Value::Integer(1)));
+    }
+}
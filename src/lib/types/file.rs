@@ -1,16 +1,22 @@
+use crate::lang::argument::ArgumentHandler;
+use crate::lang::binary::BinaryReader;
 use crate::lang::command::Command;
 use crate::lang::command::OutputType::Known;
 use crate::lang::command::OutputType::Unknown;
 use crate::lang::command::TypeMap;
-use crate::lang::errors::{to_crush_error, CrushResult};
+use crate::lang::errors::{argument_error, to_crush_error, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, ExecutionContext, This};
 use crate::lang::r#struct::Struct;
 use crate::lang::value::Value;
 use crate::lang::value::ValueType;
+use chrono::{DateTime, Local};
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
-use std::fs::metadata;
+use signature::signature;
+use std::fs::{metadata, symlink_metadata, Metadata, OpenOptions};
+use std::io;
 use std::os::unix::fs::MetadataExt;
+use std::path::Path;
 
 fn full(name: &'static str) -> Vec<&'static str> {
     vec!["global", "types", "file", name]
@@ -19,6 +25,7 @@ fn full(name: &'static str) -> Vec<&'static str> {
 lazy_static! {
     pub static ref METHODS: OrderedMap<String, Command> = {
         let mut res: OrderedMap<String, Command> = OrderedMap::new();
+        let path = vec!["global", "types", "file"];
         res.declare(
             full("stat"),
             stat,
@@ -28,17 +35,32 @@ lazy_static! {
             Some(
                 r#"    The return value contains the following fields:
 
-    * is_directory:bool is the file is a directory
+    * is_dir:bool is the file a directory
     * is_file:bool is the file a regular file
     * is_symlink:bool is the file a symbolic link
+    * size:integer the size of the file
+    * modified:time the last modification time of the file
+    * readonly:bool is the file read-only
     * inode:integer the inode number of the file
     * nlink:integer the number of hardlinks to the file
     * mode:integer the permission bits for the file
-    * len: integer the size of the file"#,
+
+    For a symbolic link, the fields other than is_symlink describe the file it points
+    to, or the link itself if it points nowhere."#,
             ),
             Unknown,
         );
 
+        res.declare(
+            full("read"),
+            read,
+            false,
+            "file:read",
+            "Return a binary stream of the contents of this file",
+            Some("    The file is read as the stream is consumed, not all at once."),
+            Known(ValueType::BinaryStream),
+        );
+        let _ = WriteSignature::declare_method(&mut res, &path);
         res.declare(
             full("exists"),
             exists,
@@ -61,16 +83,31 @@ lazy_static! {
     };
 }
 
-pub fn stat(context: ExecutionContext) -> CrushResult<()> {
-    let file = context.this.file()?;
-    let metadata = to_crush_error(metadata(file))?;
-    context.output.send(Value::Struct(Struct::new(
+/// The metadata of a file, or of the link itself for a symbolic link that points nowhere,
+/// and whether the file is a symbolic link.
+fn file_metadata(file: &Path) -> CrushResult<(Metadata, bool)> {
+    let link = to_crush_error(symlink_metadata(file))?;
+    let is_symlink = link.file_type().is_symlink();
+    match metadata(file) {
+        Ok(metadata) => Ok((metadata, is_symlink)),
+        Err(_) if is_symlink => Ok((link, true)),
+        Err(e) => to_crush_error(Err(e)),
+    }
+}
+
+fn stat_struct(file: &Path) -> CrushResult<Struct> {
+    let (metadata, is_symlink) = file_metadata(file)?;
+    let modified = DateTime::<Local>::from(to_crush_error(metadata.modified())?);
+    Ok(Struct::new(
         vec![
-            ("is_directory".to_string(), Value::Bool(metadata.is_dir())),
+            ("is_dir".to_string(), Value::Bool(metadata.is_dir())),
             ("is_file".to_string(), Value::Bool(metadata.is_file())),
+            ("is_symlink".to_string(), Value::Bool(is_symlink)),
+            ("size".to_string(), Value::Integer(metadata.len() as i128)),
+            ("modified".to_string(), Value::Time(modified)),
             (
-                "is_symlink".to_string(),
-                Value::Bool(metadata.file_type().is_symlink()),
+                "readonly".to_string(),
+                Value::Bool(metadata.permissions().readonly()),
             ),
             ("inode".to_string(), Value::Integer(metadata.ino() as i128)),
             (
@@ -78,13 +115,74 @@ pub fn stat(context: ExecutionContext) -> CrushResult<()> {
                 Value::Integer(metadata.nlink() as i128),
             ),
             ("mode".to_string(), Value::Integer(metadata.mode() as i128)),
-            ("len".to_string(), Value::Integer(metadata.len() as i128)),
         ],
         None,
-    )))
+    ))
+}
+
+pub fn stat(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let file = context.this.file()?;
+    context.output.send(Value::Struct(stat_struct(&file)?))
+}
+
+pub fn read(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let file = context.this.file()?;
+    context
+        .output
+        .send(Value::BinaryStream(<dyn BinaryReader>::paths(vec![file])?))
+}
+
+#[signature(
+    write,
+    can_block = true,
+    output = Known(ValueType::Integer),
+    short = "Write the binary or binary stream received as input to this file",
+    long = "The file is created if it does not exist and truncated if it does, unless append is set. The output is the number of bytes written.",
+    example = "convert \"hello\" binary | ./greeting.txt:write --append"
+)]
+struct WriteSignature {
+    #[description("add to the end of the file instead of replacing its contents.")]
+    #[default(false)]
+    append: bool,
+}
+
+/// Write a binary or binary stream to a file and return the number of bytes written.
+fn write_value(path: &Path, value: Value, append: bool) -> CrushResult<u64> {
+    let mut reader = match value {
+        Value::Binary(bytes) => <dyn BinaryReader>::vec(&bytes),
+        Value::BinaryStream(reader) => reader,
+        v => {
+            return argument_error(
+                format!(
+                    "Expected a binary or a binary stream to write, got a {}",
+                    v.value_type().to_string()
+                )
+                .as_str(),
+            )
+        }
+    };
+    let mut file = to_crush_error(
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(path),
+    )?;
+    to_crush_error(io::copy(&mut reader, &mut file))
+}
+
+pub fn write(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: WriteSignature = WriteSignature::parse(context.arguments, &context.printer)?;
+    let file = context.this.file()?;
+    let written = write_value(&file, context.input.recv()?, cfg.append)?;
+    context.output.send(Value::Integer(written as i128))
 }
 
 pub fn exists(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
     context
         .output
         .send(Value::Bool(context.this.file()?.exists()))
@@ -96,3 +194,76 @@ pub fn getitem(mut context: ExecutionContext) -> CrushResult<()> {
     let sub = context.arguments.string(0)?;
     context.output.send(Value::File(base_directory.join(&sub)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::types::test_util::call_method;
+    use std::fs;
+    use std::io::Read;
+
+    #[test]
+    fn test_read() {
+        let path = std::env::temp_dir().join(format!("crush-read-{}", std::process::id()));
+        let data: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+        fs::write(&path, &data).unwrap();
+        let stream = call_method(Value::File(path.clone()), "read", vec![]);
+        fs::remove_file(&path).unwrap();
+        let mut res = Vec::new();
+        match stream.unwrap() {
+            Value::BinaryStream(mut s) => {
+                s.read_to_end(&mut res).unwrap();
+            }
+            _ => panic!("Expected a binary stream"),
+        }
+        assert_eq!(res, data);
+        assert!(call_method(Value::File(path), "read", vec![]).is_err());
+    }
+
+    #[test]
+    fn test_stat_struct() {
+        let dir = std::env::temp_dir().join(format!("crush-stat-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("file");
+        let link = dir.join("link");
+        fs::write(&file, "12345").unwrap();
+        std::os::unix::fs::symlink(dir.join("nowhere"), &link).unwrap();
+        let file_stat = stat_struct(&file);
+        let dir_stat = stat_struct(&dir);
+        let link_stat = stat_struct(&link);
+        let missing_stat = stat_struct(&dir.join("missing"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let file_stat = file_stat.unwrap();
+        assert!(file_stat.get("size") == Some(Value::Integer(5)));
+        assert!(file_stat.get("is_file") == Some(Value::Bool(true)));
+        assert!(file_stat.get("is_dir") == Some(Value::Bool(false)));
+        assert!(file_stat.get("readonly") == Some(Value::Bool(false)));
+        assert!(file_stat.get("modified").unwrap().value_type() == ValueType::Time);
+        assert!(dir_stat.unwrap().get("is_dir") == Some(Value::Bool(true)));
+        assert!(link_stat.unwrap().get("is_symlink") == Some(Value::Bool(true)));
+        assert!(missing_stat.is_err());
+    }
+
+    #[test]
+    fn test_write_value() {
+        let path = std::env::temp_dir().join(format!("crush-write-{}", std::process::id()));
+        let written = write_value(&path, Value::Binary(b"hello".to_vec()), false);
+        let appended = write_value(
+            &path,
+            Value::BinaryStream(<dyn BinaryReader>::vec(&b" world".to_vec())),
+            true,
+        );
+        let contents = fs::read(&path);
+        let replaced = write_value(&path, Value::Binary(b"bye".to_vec()), false);
+        let replaced_contents = fs::read(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(written.unwrap(), 5);
+        assert_eq!(appended.unwrap(), 6);
+        assert_eq!(contents.unwrap(), b"hello world");
+        assert_eq!(replaced.unwrap(), 3);
+        assert_eq!(replaced_contents.unwrap(), b"bye");
+        assert!(write_value(&path, Value::Integer(1), false).is_err());
+        assert!(!path.exists());
+    }
+}
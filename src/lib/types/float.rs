@@ -1,3 +1,4 @@
+use crate::lang::argument::ArgumentHandler;
 use crate::lang::command::Command;
 use crate::lang::command::OutputType::Known;
 use crate::lang::command::TypeMap;
@@ -7,6 +8,7 @@ use crate::lang::value::ValueType;
 use crate::lang::{execution_context::ExecutionContext, value::Value};
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
+use signature::signature;
 
 fn full(name: &'static str) -> Vec<&'static str> {
     vec!["global", "types", "float", name]
@@ -15,6 +17,7 @@ fn full(name: &'static str) -> Vec<&'static str> {
 lazy_static! {
     pub static ref METHODS: OrderedMap<String, Command> = {
         let mut res: OrderedMap<String, Command> = OrderedMap::new();
+        let path = vec!["global", "types", "float"];
         res.declare(
             full("__add__"),
             add,
@@ -61,7 +64,7 @@ lazy_static! {
             Known(ValueType::Float),
         );
         res.declare(
-            full("is_finite"),
+            full("is_infinite"),
             is_infinite,
             false,
             "float:is_infinite",
@@ -69,6 +72,15 @@ lazy_static! {
             None,
             Known(ValueType::Bool),
         );
+        res.declare(
+            full("is_finite"),
+            is_finite,
+            false,
+            "float:is_finite",
+            "True if this float is neither infinite nor NaN",
+            None,
+            Known(ValueType::Bool),
+        );
         res.declare(
             full("is_nan"),
             is_nan,
@@ -78,6 +90,114 @@ lazy_static! {
             None,
             Known(ValueType::Bool),
         );
+        res.declare(
+            full("floor"),
+            floor,
+            false,
+            "float:floor",
+            "The largest integral value less than or equal to this float",
+            None,
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("ceil"),
+            ceil,
+            false,
+            "float:ceil",
+            "The smallest integral value greater than or equal to this float",
+            None,
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("abs"),
+            abs,
+            false,
+            "float:abs",
+            "The absolute value of this float",
+            None,
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("sqrt"),
+            sqrt,
+            false,
+            "float:sqrt",
+            "The square root of this float",
+            Some("    The square root of a negative number is NaN, see float:is_nan."),
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("sin"),
+            sin,
+            false,
+            "float:sin",
+            "The sine of this float, in radians",
+            None,
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("cos"),
+            cos,
+            false,
+            "float:cos",
+            "The cosine of this float, in radians",
+            None,
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("tan"),
+            tan,
+            false,
+            "float:tan",
+            "The tangent of this float, in radians",
+            None,
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("atan"),
+            atan,
+            false,
+            "float:atan",
+            "The arc tangent of this float, in radians between -pi/2 and pi/2",
+            None,
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("atan2"),
+            atan2,
+            false,
+            "float:atan2 y:float",
+            "The angle of the point (this, y), in radians between -pi and pi",
+            Some(
+                r#"    This float is the x coordinate, so this is the arc tangent of y divided by
+    this float, using the signs of both to pick the quadrant.
+
+    Example:
+
+    (0.0):atan2 1.0"#,
+            ),
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("pi"),
+            pi,
+            false,
+            "float:pi",
+            "The ratio of the circumference of a circle to its diameter",
+            None,
+            Known(ValueType::Float),
+        );
+        res.declare(
+            full("e"),
+            e,
+            false,
+            "float:e",
+            "Euler's number, the base of the natural logarithm",
+            None,
+            Known(ValueType::Float),
+        );
+        let _ = Round::declare_method(&mut res, &path);
+        let _ = Format::declare_method(&mut res, &path);
         res
     };
 }
@@ -141,3 +261,220 @@ fn is_infinite(context: ExecutionContext) -> CrushResult<()> {
         .output
         .send(Value::Bool(context.this.float()?.is_infinite()))
 }
+
+fn is_finite(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Bool(context.this.float()?.is_finite()))
+}
+
+fn floor(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Float(context.this.float()?.floor()))
+}
+
+fn ceil(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Float(context.this.float()?.ceil()))
+}
+
+fn abs(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Float(context.this.float()?.abs()))
+}
+
+fn sqrt(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Float(context.this.float()?.sqrt()))
+}
+
+fn sin(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Float(context.this.float()?.sin()))
+}
+
+fn cos(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Float(context.this.float()?.cos()))
+}
+
+fn tan(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Float(context.this.float()?.tan()))
+}
+
+fn atan(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Float(context.this.float()?.atan()))
+}
+
+/// The angle of the point (x, y), as opposed to f64::atan2, which takes y as the receiver.
+fn angle(x: f64, y: f64) -> f64 {
+    y.atan2(x)
+}
+
+fn atan2(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let y = context.arguments.float(0)?;
+    context
+        .output
+        .send(Value::Float(angle(context.this.float()?, y)))
+}
+
+fn pi(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context.output.send(Value::Float(std::f64::consts::PI))
+}
+
+fn e(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context.output.send(Value::Float(std::f64::consts::E))
+}
+
+#[signature(
+    round,
+    can_block = false,
+    output = Known(ValueType::Float),
+    short = "Round this float to the specified number of decimal digits",
+    long = "Halfway cases are rounded away from zero. A negative number of digits rounds to tens, hundreds and so on.",
+    example = "(1234.5678):round digits=2"
+)]
+struct Round {
+    #[description("the number of digits after the decimal point to keep.")]
+    #[default(0)]
+    digits: i128,
+}
+
+fn round_digits(value: f64, digits: i128) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+    let digits = digits.clamp(-400, 400) as i32;
+    if digits >= 0 {
+        let scale = 10f64.powi(digits);
+        let scaled = value * scale;
+        if scaled.is_finite() {
+            scaled.round() / scale
+        } else {
+            value
+        }
+    } else {
+        let scale = 10f64.powi(-digits);
+        if scale.is_finite() {
+            (value / scale).round() * scale
+        } else {
+            0f64.copysign(value)
+        }
+    }
+}
+
+fn round(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Round = Round::parse(context.arguments, &context.printer)?;
+    context.output.send(Value::Float(round_digits(
+        context.this.float()?,
+        cfg.digits,
+    )))
+}
+
+#[signature(
+    format,
+    can_block = false,
+    output = Known(ValueType::String),
+    short = "Format this float with a fixed number of decimal digits",
+    example = "(0.1 + 0.2):format digits=2"
+)]
+struct Format {
+    #[description("the number of digits after the decimal point.")]
+    digits: i128,
+}
+
+fn format_digits(value: f64, digits: i128) -> CrushResult<String> {
+    if !(0..=u16::MAX as i128).contains(&digits) {
+        return argument_error(format!("Invalid number of digits {}", digits).as_str());
+    }
+    Ok(format!("{:.*}", digits as usize, value))
+}
+
+fn format(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Format = Format::parse(context.arguments, &context.printer)?;
+    context.output.send(Value::String(format_digits(
+        context.this.float()?,
+        cfg.digits,
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::argument::Argument;
+    use crate::lib::types::test_util::call_method;
+
+    #[test]
+    fn test_angle() {
+        use std::f64::consts::PI;
+        assert!((angle(1.0, 1.0) - PI / 4.0).abs() < 1e-12);
+        assert!((angle(0.0, 1.0) - PI / 2.0).abs() < 1e-12);
+        assert!((angle(-1.0, 0.0) - PI).abs() < 1e-12);
+        assert!((angle(0.0, -2.0) + PI / 2.0).abs() < 1e-12);
+        assert_eq!(angle(1.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_trigonometry() {
+        use std::f64::consts::PI;
+        let call = |this: f64, name: &str, arguments: Vec<Value>| match call_method(
+            Value::Float(this),
+            name,
+            arguments.into_iter().map(Argument::unnamed).collect(),
+        )
+        .unwrap()
+        {
+            Value::Float(f) => f,
+            _ => panic!("Expected a float"),
+        };
+        assert!((call(PI / 2.0, "sin", vec![]) - 1.0).abs() < 1e-12);
+        assert!((call(PI, "cos", vec![]) + 1.0).abs() < 1e-12);
+        assert!((call(PI / 4.0, "tan", vec![]) - 1.0).abs() < 1e-12);
+        assert!((call(1.0, "atan", vec![]) - PI / 4.0).abs() < 1e-12);
+        assert!((call(0.0, "atan2", vec![Value::Float(1.0)]) - PI / 2.0).abs() < 1e-12);
+        assert!((call(-1.0, "atan2", vec![Value::Float(0.0)]) - PI).abs() < 1e-12);
+        assert!(call_method(Value::Float(1.0), "atan2", vec![]).is_err());
+    }
+
+    #[test]
+    fn test_round_digits() {
+        assert_eq!(round_digits(2.5, 0), 3.0);
+        assert_eq!(round_digits(-2.5, 0), -3.0);
+        assert_eq!(round_digits(1.23456, 2), 1.23);
+        assert_eq!(round_digits(1234.5, -2), 1200.0);
+        assert_eq!(round_digits(1250.0, -2), 1300.0);
+        assert_eq!(round_digits(1e300, 100), 1e300);
+        assert_eq!(round_digits(1234.5, -1000), 0.0);
+        assert!(round_digits(f64::NAN, 2).is_nan());
+    }
+
+    #[test]
+    fn test_format_digits() {
+        assert_eq!(format_digits(0.1 + 0.2, 2).unwrap(), "0.30");
+        assert_eq!(format_digits(-1.5, 0).unwrap(), "-2");
+        assert_eq!(format_digits(1.0, 3).unwrap(), "1.000");
+        assert!(format_digits(1.0, -1).is_err());
+    }
+}
@@ -4,12 +4,14 @@ use crate::lang::command::TypeMap;
 use crate::lang::errors::CrushResult;
 use crate::lang::execution_context::{ArgumentVector, This};
 use crate::lang::list::List;
+use crate::lang::table::{ColumnType, Row};
 use crate::lang::value::ValueType;
 use crate::lang::{execution_context::ExecutionContext, value::Value};
 use crate::util::file::cwd;
 use crate::util::glob::Glob;
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
+use std::path::{Path, PathBuf};
 
 fn full(name: &'static str) -> Vec<&'static str> {
     vec!["global", "types", "glob", name]
@@ -36,6 +38,15 @@ lazy_static! {
             None,
             Known(ValueType::Bool),
         );
+        res.declare(
+            full("matches"),
+            r#match,
+            false,
+            "glob:matches text:string",
+            "True if the text matches the pattern",
+            None,
+            Known(ValueType::Bool),
+        );
         res.declare(
             full("not_match"),
             not_match,
@@ -54,6 +65,18 @@ lazy_static! {
             None,
             Known(ValueType::List(Box::from(ValueType::File))),
         );
+        res.declare(
+            full("expand"),
+            expand,
+            true,
+            "glob:expand",
+            "Return a stream of the files matching this glob, relative to the current directory",
+            Some("    The stream has a single column, file, and is sorted by file name."),
+            Known(ValueType::TableStream(vec![ColumnType::new(
+                "file",
+                ValueType::File,
+            )])),
+        );
         res
     };
 }
@@ -64,6 +87,7 @@ fn new(mut context: ExecutionContext) -> CrushResult<()> {
 }
 
 fn r#match(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
     let g = context.this.glob()?;
     let needle = context.arguments.string(0)?;
     context.output.send(Value::Bool(g.matches(&needle)))
@@ -84,3 +108,55 @@ fn files(context: ExecutionContext) -> CrushResult<()> {
         files.drain(..).map(|f| Value::File(f)).collect(),
     )))
 }
+
+/// The files matching a glob in the specified directory, sorted by name.
+fn expanded(g: &Glob, dir: &Path) -> CrushResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    g.glob_files(dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn expand(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let g = context.this.glob()?;
+    let output = context
+        .output
+        .initialize(vec![ColumnType::new("file", ValueType::File)])?;
+    for file in expanded(&g, &cwd()?)? {
+        output.send(Row::new(vec![Value::File(file)]))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_matches() {
+        let g = Glob::new("%.txt");
+        assert!(g.matches("a.txt"));
+        assert!(!g.matches("a.rs"));
+        assert!(!g.matches("dir/a.txt"));
+        assert!(Glob::new("?b").matches("ab"));
+    }
+
+    #[test]
+    fn test_expanded() {
+        let dir = std::env::temp_dir().join(format!("crush-glob-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for name in &["b.txt", "a.txt", "c.rs", "sub/d.txt"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        let res = expanded(&Glob::new("%.txt"), &dir);
+        let recursive = expanded(&Glob::new("%%.txt"), &dir);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            res.unwrap(),
+            vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")]
+        );
+        assert_eq!(recursive.unwrap().len(), 3);
+    }
+}
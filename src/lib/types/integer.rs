@@ -1,12 +1,16 @@
+use crate::lang::argument::ArgumentHandler;
 use crate::lang::command::Command;
 use crate::lang::command::OutputType::Known;
 use crate::lang::command::TypeMap;
-use crate::lang::errors::{argument_error, CrushResult};
+use crate::lang::errors::{argument_error, mandate, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, This};
 use crate::lang::value::ValueType;
 use crate::lang::{execution_context::ExecutionContext, value::Value};
+use crate::util::bytes::integer_to_bytes;
+use crate::util::radix::format_radix;
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
+use signature::signature;
 
 fn full(name: &'static str) -> Vec<&'static str> {
     vec!["global", "types", "integer", name]
@@ -15,6 +19,7 @@ fn full(name: &'static str) -> Vec<&'static str> {
 lazy_static! {
     pub static ref METHODS: OrderedMap<String, Command> = {
         let mut res: OrderedMap<String, Command> = OrderedMap::new();
+        let path = vec!["global", "types", "integer"];
         res.declare(
             full("__add__"),
             add,
@@ -78,6 +83,107 @@ lazy_static! {
             None,
             Known(ValueType::Integer),
         );
+        res.declare(
+            full("abs"),
+            abs,
+            false,
+            "integer:abs",
+            "The absolute value of this integer",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("signum"),
+            signum,
+            false,
+            "integer:signum",
+            "-1 if this integer is negative, 0 if it is zero and 1 if it is positive",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("pow"),
+            pow,
+            false,
+            "integer:pow exponent:integer",
+            "Raise this integer to the power of exponent",
+            Some(
+                r#"    The exponent must not be negative, convert this integer to a float to raise
+    it to a negative power. It is an error for the result to overflow."#,
+            ),
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("min"),
+            min,
+            false,
+            "integer:min other:integer",
+            "The smallest of this integer and other",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("max"),
+            max,
+            false,
+            "integer:max other:integer",
+            "The largest of this integer and other",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("bit_and"),
+            bit_and,
+            false,
+            "integer:bit_and other:integer",
+            "Bitwise and of this integer and other",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("bit_or"),
+            bit_or,
+            false,
+            "integer:bit_or other:integer",
+            "Bitwise or of this integer and other",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("bit_xor"),
+            bit_xor,
+            false,
+            "integer:bit_xor other:integer",
+            "Bitwise exclusive or of this integer and other",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("shl"),
+            shl,
+            false,
+            "integer:shl bits:integer",
+            "Shift this integer left by the specified number of bits",
+            Some(
+                r#"    Bits shifted out of the integer are lost. The number of bits must be
+    between 0 and 127."#,
+            ),
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("shr"),
+            shr,
+            false,
+            "integer:shr bits:integer",
+            "Shift this integer right by the specified number of bits",
+            Some(
+                r#"    This is an arithmetic shift, so the sign of the integer is preserved. The
+    number of bits must be between 0 and 127."#,
+            ),
+            Known(ValueType::Integer),
+        );
+        let _ = ToBinary::declare_method(&mut res, &path);
+        let _ = ToText::declare_method(&mut res, &path);
         res
     };
 }
@@ -124,6 +230,9 @@ binary_op!(
 );
 binary_op!(rem, integer, Integer, Integer, |a, b| a % b);
 binary_op!(r#mod, integer, Integer, Integer, |a, b| (a % b + b) % b);
+binary_op!(bit_and, integer, Integer, Integer, |a, b| a & b);
+binary_op!(bit_or, integer, Integer, Integer, |a, b| a | b);
+binary_op!(bit_xor, integer, Integer, Integer, |a, b| a ^ b);
 
 fn neg(context: ExecutionContext) -> CrushResult<()> {
     context.arguments.check_len(0)?;
@@ -131,3 +240,171 @@ fn neg(context: ExecutionContext) -> CrushResult<()> {
         .output
         .send(Value::Integer(-context.this.integer()?))
 }
+
+#[signature(
+    to_binary,
+    can_block = false,
+    output = Known(ValueType::Binary),
+    short = "Encode this integer as two's complement bytes",
+    long = "Without a width, the shortest encoding that preserves the sign is used.",
+    example = "(1024):to_binary width=4 endian=little"
+)]
+struct ToBinary {
+    #[description("the number of bytes in the encoding.")]
+    width: Option<usize>,
+    #[description("the byte order of the encoding.")]
+    #[values("big", "little")]
+    #[default("big")]
+    endian: String,
+}
+
+fn to_binary(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: ToBinary = ToBinary::parse(context.arguments, &context.printer)?;
+    let value = context.this.integer()?;
+    context.output.send(Value::Binary(integer_to_bytes(
+        value,
+        cfg.width,
+        cfg.endian == "little",
+    )?))
+}
+
+fn checked_abs(value: i128) -> CrushResult<i128> {
+    mandate(value.checked_abs(), "Integer overflow in abs")
+}
+
+fn abs(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let value = context.this.integer()?;
+    context.output.send(Value::Integer(checked_abs(value)?))
+}
+
+fn signum(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Integer(context.this.integer()?.signum()))
+}
+
+fn checked_pow(base: i128, exponent: i128) -> CrushResult<i128> {
+    if exponent < 0 {
+        return argument_error(
+            "Can't raise an integer to a negative power, convert it to a float first",
+        );
+    }
+    if exponent > u32::MAX as i128 {
+        return argument_error("Integer overflow in pow");
+    }
+    mandate(base.checked_pow(exponent as u32), "Integer overflow in pow")
+}
+
+fn pow(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let exponent = context.arguments.integer(0)?;
+    let base = context.this.integer()?;
+    context
+        .output
+        .send(Value::Integer(checked_pow(base, exponent)?))
+}
+
+fn min(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let other = context.arguments.integer(0)?;
+    context
+        .output
+        .send(Value::Integer(context.this.integer()?.min(other)))
+}
+
+fn max(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let other = context.arguments.integer(0)?;
+    context
+        .output
+        .send(Value::Integer(context.this.integer()?.max(other)))
+}
+
+fn shift_bits(bits: i128) -> CrushResult<u32> {
+    if !(0..128).contains(&bits) {
+        return argument_error(
+            format!(
+                "Can't shift by {} bits, expected a value between 0 and 127",
+                bits
+            )
+            .as_str(),
+        );
+    }
+    Ok(bits as u32)
+}
+
+fn shl(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let bits = shift_bits(context.arguments.integer(0)?)?;
+    context
+        .output
+        .send(Value::Integer(context.this.integer()? << bits))
+}
+
+fn shr(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let bits = shift_bits(context.arguments.integer(0)?)?;
+    context
+        .output
+        .send(Value::Integer(context.this.integer()? >> bits))
+}
+
+#[signature(
+    to_text,
+    can_block = false,
+    output = Known(ValueType::String),
+    short = "Format this integer in the specified radix",
+    long = "Digits above 9 are written as lowercase letters, and no prefix is added.",
+    example = "(255):to_text radix=16"
+)]
+struct ToText {
+    #[description("the radix to use, between 2 and 36.")]
+    #[default(10)]
+    radix: i128,
+}
+
+fn to_text(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: ToText = ToText::parse(context.arguments, &context.printer)?;
+    let value = context.this.integer()?;
+    context
+        .output
+        .send(Value::String(format_radix(value, cfg.radix)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_abs() {
+        assert_eq!(checked_abs(-5).unwrap(), 5);
+        assert_eq!(checked_abs(i128::MAX).unwrap(), i128::MAX);
+        assert_eq!(checked_abs(i128::MIN + 1).unwrap(), i128::MAX);
+        assert!(checked_abs(i128::MIN).is_err());
+    }
+
+    #[test]
+    fn test_shift_bits() {
+        assert_eq!(shift_bits(0).unwrap(), 0);
+        assert_eq!(shift_bits(127).unwrap(), 127);
+        assert_eq!(i128::MIN >> shift_bits(127).unwrap(), -1);
+        assert_eq!(1i128 << shift_bits(127).unwrap(), i128::MIN);
+        assert!(shift_bits(128).is_err());
+        assert!(shift_bits(-1).is_err());
+    }
+
+    #[test]
+    fn test_checked_pow() {
+        assert_eq!(checked_pow(2, 10).unwrap(), 1024);
+        assert_eq!(checked_pow(-3, 3).unwrap(), -27);
+        assert_eq!(checked_pow(7, 0).unwrap(), 1);
+        assert_eq!(checked_pow(i128::MAX, 1).unwrap(), i128::MAX);
+        assert_eq!(checked_pow(2, 126).unwrap(), 1 << 126);
+        assert!(checked_pow(2, 127).is_err());
+        assert!(checked_pow(i128::MAX, 2).is_err());
+        assert!(checked_pow(2, -1).is_err());
+        assert!(checked_pow(1, i128::MAX).is_err());
+    }
+}
@@ -1,14 +1,21 @@
+use crate::lang::argument::Argument;
 use crate::lang::argument::ArgumentHandler;
 use crate::lang::command::OutputType::Known;
 use crate::lang::command::OutputType::Unknown;
 use crate::lang::command::TypeMap;
-use crate::lang::errors::{argument_error, data_error, mandate, CrushResult};
+use crate::lang::errors::{argument_error, data_error, error, mandate, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, ExecutionContext, This};
+use crate::lang::list::index_error;
+use crate::lang::table::{ColumnType, Row, Table};
 use crate::lang::value::Value;
 use crate::lang::{command::Command, list::List, value::ValueType};
+use crate::lib::types::{call, common_type};
+use crate::util::index::clamp_index;
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
 use signature::signature;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 
 fn full(name: &'static str) -> Vec<&'static str> {
     vec!["global", "types", "list", name]
@@ -40,8 +47,8 @@ lazy_static! {
             full("push"),
             push,
             false,
-            "list:push",
-            "Push an element to the end of the list",
+            "list:push value:any...",
+            "Push elements to the end of the list",
             None,
             Unknown,
         );
@@ -50,7 +57,7 @@ lazy_static! {
             pop,
             false,
             "list:pop",
-            "Remove the last element from the list",
+            "Remove the last element from the list and return it, or empty if the list is empty",
             None,
             Unknown,
         );
@@ -59,7 +66,7 @@ lazy_static! {
             peek,
             false,
             "list:peek",
-            "Return the last element from the list",
+            "Return the last element from the list, or empty if the list is empty",
             None,
             Unknown,
         );
@@ -86,7 +93,7 @@ lazy_static! {
             remove,
             false,
             "list:remove idx:integer",
-            "Remove the element at the specified index",
+            "Remove the element at the specified index and return it",
             None,
             Unknown,
         );
@@ -103,8 +110,8 @@ lazy_static! {
             full("truncate"),
             truncate,
             false,
-            "list:truncate idx:integer",
-            "Remove all elements past the specified index",
+            "list:truncate len:integer",
+            "Remove all elements past the specified length",
             None,
             Unknown,
         );
@@ -162,6 +169,161 @@ lazy_static! {
             None,
             Unknown,
         );
+        res.declare(
+            full("join"),
+            join,
+            false,
+            "list:join separator:string",
+            "Join the elements of this list into a string, with separator between them",
+            Some(
+                r#"    Elements that are not strings are converted using their textual representation.
+    Lists of nested containers, like lists, dicts or tables, can not be joined."#,
+            ),
+            Known(ValueType::String),
+        );
+        res.declare(
+            full("map"),
+            map,
+            true,
+            "list:map function:command [type=type]",
+            "Return a new list with the result of calling function on each element of this list",
+            Some(
+                r#"    All results must be of the same type, which becomes the element type of the new
+    list. If a type is specified, it is the element type of the new list instead, so
+    mapping an empty list gives a list of that type.
+
+    Example:
+
+    (list:of 1 2 3):map {|x| x * x}"#,
+            ),
+            Unknown,
+        );
+        res.declare(
+            full("filter"),
+            filter,
+            true,
+            "list:filter condition:command",
+            "Return a new list with the elements of this list for which condition is true",
+            Some(
+                r#"    Example:
+
+    (list:of 1 2 3 4):filter {|x| (x // 2 * 2) == x}"#,
+            ),
+            Unknown,
+        );
+        res.declare(
+            full("reduce"),
+            reduce,
+            true,
+            "list:reduce function:command initial:any",
+            "Combine the elements of this list from left to right",
+            Some(
+                r#"    Function is called with the result so far, starting with initial, and the next
+    element. For an empty list, the result is initial.
+
+    Example:
+
+    (list:of 1 2 3):reduce {|sum x| sum + x} 0"#,
+            ),
+            Unknown,
+        );
+        res.declare(
+            full("slice"),
+            slice,
+            false,
+            "list:slice from:integer [to:integer]",
+            "Return a new list with the elements in the range [from, to)",
+            Some(
+                r#"    If to is omitted, the slice extends to the end of the list. Negative indices
+    count from the end of the list, and indices that are out of bounds are clamped
+    to the list. It is an error for from to come after to."#,
+            ),
+            Unknown,
+        );
+        let _ = Sort::declare_method(&mut res, &path);
+        let _ = Reverse::declare_method(&mut res, &path);
+        let _ = Dedup::declare_method(&mut res, &path);
+        res.declare(
+            full("contains"),
+            contains,
+            false,
+            "list:contains value:any",
+            "True if any element of this list is equal to value",
+            None,
+            Known(ValueType::Bool),
+        );
+        res.declare(
+            full("index_of"),
+            index_of,
+            false,
+            "list:index_of value:any",
+            "The index of the first element of this list that is equal to value",
+            Some(r#"    If no element is equal to value, the output is empty."#),
+            Unknown,
+        );
+        res.declare(
+            full("sum"),
+            sum,
+            false,
+            "list:sum",
+            "The sum of the elements of this list of integers or floats",
+            Some(r#"    The sum of an empty list is empty. Integer overflow is an error."#),
+            Unknown,
+        );
+        res.declare(
+            full("avg"),
+            avg,
+            false,
+            "list:avg",
+            "The average of the elements of this list of integers or floats, as a float",
+            Some(r#"    The average of an empty list is empty."#),
+            Unknown,
+        );
+        res.declare(
+            full("min"),
+            min,
+            false,
+            "list:min",
+            "The smallest element of this list of integers or floats",
+            Some(r#"    NaN is ignored. The minimum of an empty list is empty."#),
+            Unknown,
+        );
+        res.declare(
+            full("max"),
+            max,
+            false,
+            "list:max",
+            "The largest element of this list of integers or floats",
+            Some(r#"    NaN is ignored. The maximum of an empty list is empty."#),
+            Unknown,
+        );
+        res.declare(
+            full("zip"),
+            zip,
+            false,
+            "list:zip other:list",
+            "Return a table pairing up the elements of this list and other",
+            Some(
+                r#"    The table has the columns left and right and ends with the shorter list.
+
+    Example:
+
+    (list:of "a" "b"):zip (list:of 1 2 3)"#,
+            ),
+            Unknown,
+        );
+        res.declare(
+            full("enumerate"),
+            enumerate,
+            true,
+            "list:enumerate [start:integer]",
+            "Return a stream of the elements of this list and their indices",
+            Some(
+                r#"    The stream has the columns idx and value. Indices are counted from start, which
+    defaults to 0."#,
+            ),
+            Unknown,
+        );
         let _ = Repeat::declare_method(&mut res, &path); // TODO: why unused?
 
         res
@@ -270,32 +432,455 @@ fn empty(context: ExecutionContext) -> CrushResult<()> {
 
 fn push(mut context: ExecutionContext) -> CrushResult<()> {
     let l = context.this.list()?;
-    let mut new_elements: Vec<Value> = Vec::new();
-    for el in context.arguments.drain(..) {
-        if el.value.value_type() == l.element_type() || l.element_type() == ValueType::Any {
-            new_elements.push(el.value)
-        } else {
-            return argument_error("Invalid element type");
-        }
-    }
+    let mut new_elements: Vec<Value> = context.arguments.drain(..).map(|a| a.value).collect();
     if !new_elements.is_empty() {
         l.append(&mut new_elements)?;
     }
     context.output.send(Value::List(l))
 }
 
-fn pop(context: ExecutionContext) -> CrushResult<()> {
+fn join_values(values: Vec<Value>, separator: &str) -> CrushResult<String> {
+    let mut parts = Vec::with_capacity(values.len());
+    for value in values {
+        parts.push(match value {
+            Value::String(s) => s,
+            Value::List(_)
+            | Value::Dict(_)
+            | Value::Struct(_)
+            | Value::Table(_)
+            | Value::TableStream(_)
+            | Value::BinaryStream(_)
+            | Value::Scope(_)
+            | Value::Command(_) => {
+                return argument_error(
+                    format!(
+                        "Can't join elements of type {}",
+                        value.value_type().to_string()
+                    )
+                    .as_str(),
+                )
+            }
+            v => v.to_string(),
+        });
+    }
+    Ok(parts.join(separator))
+}
+
+fn join(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let separator = context.arguments.string(0)?;
+    let l = context.this.list()?;
+    context
+        .output
+        .send(Value::String(join_values(l.dump(), &separator)?))
+}
+
+fn slice_values(values: &[Value], from: i128, to: Option<i128>) -> CrushResult<Vec<Value>> {
+    let from = clamp_index(from, values.len());
+    let to = to
+        .map(|to| clamp_index(to, values.len()))
+        .unwrap_or(values.len());
+    if from > to {
+        return argument_error(format!("Slice start {} is after slice end {}", from, to).as_str());
+    }
+    Ok(values[from..to].to_vec())
+}
+
+fn slice(mut context: ExecutionContext) -> CrushResult<()> {
+    let list = context.this.list()?;
+    context.arguments.check_len_range(1, 2)?;
+    let from = context.arguments.integer(0)?;
+    let to = context.arguments.optional_integer(1)?;
+    context.output.send(Value::List(List::new(
+        list.element_type(),
+        slice_values(&list.dump(), from, to)?,
+    )))
+}
+
+/// Output the new elements of a list, either by replacing the elements of the list itself
+/// or as a new list.
+fn send_list(context: ExecutionContext, values: Vec<Value>, in_place: bool) -> CrushResult<()> {
+    let list = context.this.list()?;
+    if in_place {
+        list.replace_cells(values)?;
+        context.output.send(Value::List(list))
+    } else {
+        context
+            .output
+            .send(Value::List(List::new(list.element_type(), values)))
+    }
+}
+
+fn check_comparable(values: &[Value]) -> CrushResult<()> {
+    match values.iter().find(|v| !v.value_type().is_comparable()) {
+        Some(v) => argument_error(
+            format!("Can't sort values of type {}", v.value_type().to_string()).as_str(),
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Sort values by their keys, keeping the original order of values with equal keys.
+fn sort_by_keys(values: Vec<Value>, keys: Vec<Value>) -> CrushResult<Vec<Value>> {
+    check_comparable(&keys)?;
+    let mut pairs: Vec<(Value, Value)> = keys.into_iter().zip(values).collect();
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(pairs.into_iter().map(|(_, v)| v).collect())
+}
+
+#[signature(
+    sort,
+    can_block = true,
+    short = "Sort the elements of this list",
+    long = "Values of different types are ordered by type, and NaN comes after all other floats. The sort is stable.",
+    example = "(list:of \"bb\" \"a\"):sort key={|s| s:len}"
+)]
+struct Sort {
+    #[description("a command that returns the value to sort each element by.")]
+    key: Option<Command>,
+    #[description("sort the elements of this list instead of returning a new list.")]
+    #[default(false)]
+    in_place: bool,
+}
+
+fn sort(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Sort = Sort::parse(context.arguments.clone(), &context.printer)?;
+    let values = context.this.clone().list()?.dump();
+    let keys = match &cfg.key {
+        Some(key) => values
+            .iter()
+            .map(|v| call(key, vec![v.clone()], &context))
+            .collect::<CrushResult<Vec<_>>>()?,
+        None => values.clone(),
+    };
+    let sorted = sort_by_keys(values, keys)?;
+    send_list(context, sorted, cfg.in_place)
+}
+
+#[signature(
+    reverse,
+    can_block = false,
+    short = "Reverse the order of the elements of this list"
+)]
+struct Reverse {
+    #[description("reverse the elements of this list instead of returning a new list.")]
+    #[default(false)]
+    in_place: bool,
+}
+
+fn reverse(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Reverse = Reverse::parse(context.arguments.clone(), &context.printer)?;
+    let mut values = context.this.clone().list()?.dump();
+    values.reverse();
+    send_list(context, values, cfg.in_place)
+}
+
+// Only hashable values are put in the set, and those are immutable.
+#[allow(clippy::mutable_key_type)]
+fn dedup_values(values: Vec<Value>, global: bool) -> CrushResult<Vec<Value>> {
+    let mut res: Vec<Value> = Vec::with_capacity(values.len());
+    if global {
+        let mut seen = HashSet::new();
+        for v in values {
+            v.check_hashable()?;
+            if seen.insert(v.clone()) {
+                res.push(v);
+            }
+        }
+    } else {
+        for v in values {
+            if res.last() != Some(&v) {
+                res.push(v);
+            }
+        }
+    }
+    Ok(res)
+}
+
+#[signature(
+    dedup,
+    can_block = false,
+    short = "Remove duplicate elements from this list",
+    long = "By default, only consecutive duplicates are removed, like the uniq command does. With global, the first occurrence of every element is kept, which requires all elements to be hashable.",
+    example = "(list:of 1 1 2 1):dedup global=true"
+)]
+struct Dedup {
+    #[description("remove all duplicates, not only consecutive ones.")]
+    #[default(false)]
+    global: bool,
+    #[description("remove the duplicates from this list instead of returning a new list.")]
+    #[default(false)]
+    in_place: bool,
+}
+
+fn dedup(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Dedup = Dedup::parse(context.arguments.clone(), &context.printer)?;
+    let values = dedup_values(context.this.clone().list()?.dump(), cfg.global)?;
+    send_list(context, values, cfg.in_place)
+}
+
+fn contains(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let list = context.this.list()?;
+    let needle = context.arguments.value(0)?;
+    context
+        .output
+        .send(Value::Bool(list.dump().contains(&needle)))
+}
+
+fn index_of(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let list = context.this.list()?;
+    let needle = context.arguments.value(0)?;
+    context.output.send(
+        list.dump()
+            .iter()
+            .position(|v| *v == needle)
+            .map(|idx| Value::Integer(idx as i128))
+            .unwrap_or_else(Value::Empty),
+    )
+}
+
+fn sum_values(values: &[Value]) -> CrushResult<Value> {
+    let mut res = match values.first() {
+        None => return Ok(Value::Empty()),
+        Some(Value::Integer(_)) => Value::Integer(0),
+        Some(_) => Value::Float(0.0),
+    };
+    for v in values {
+        res = match (res, v) {
+            (Value::Integer(a), Value::Integer(b)) => Value::Integer(mandate(
+                a.checked_add(*b),
+                "Integer overflow while calculating the sum of the list",
+            )?),
+            (Value::Float(a), Value::Float(b)) => Value::Float(a + b),
+            _ => return error("Invalid list element"),
+        };
+    }
+    Ok(res)
+}
+
+fn avg_values(values: &[Value]) -> CrushResult<Value> {
+    if values.is_empty() {
+        return Ok(Value::Empty());
+    }
+    let mut res = 0.0;
+    for v in values {
+        res += match v {
+            Value::Integer(i) => *i as f64,
+            Value::Float(f) => *f,
+            _ => return error("Invalid list element"),
+        };
+    }
+    Ok(Value::Float(res / values.len() as f64))
+}
+
+/// Pick the element that compares as the extreme in the specified direction, skipping
+/// incomparable elements like NaN.
+fn pick_value(values: &[Value], wanted: Ordering) -> Value {
+    let mut res: Option<&Value> = None;
+    for v in values {
+        if v.partial_cmp(v).is_none() {
+            continue;
+        }
+        res = match res {
+            Some(current) if v.partial_cmp(current) != Some(wanted) => Some(current),
+            _ => Some(v),
+        };
+    }
+    res.cloned().unwrap_or_else(Value::Empty)
+}
+
+/// Calculate an aggregate of a list of integers or floats.
+fn aggregate(
+    context: ExecutionContext,
+    name: &str,
+    f: impl Fn(&[Value]) -> CrushResult<Value>,
+) -> CrushResult<()> {
     context.arguments.check_len(0)?;
-    let o = context.output;
-    context.this.list()?.pop().map(|c| o.send(c));
+    let list = context.this.list()?;
+    match list.element_type() {
+        ValueType::Integer | ValueType::Float => context.output.send(f(&list.dump())?),
+        t => argument_error(
+            format!(
+                "Can't calculate the {} of a list of {}, expected integers or floats",
+                name,
+                t.to_string()
+            )
+            .as_str(),
+        ),
+    }
+}
+
+fn sum(context: ExecutionContext) -> CrushResult<()> {
+    aggregate(context, "sum", sum_values)
+}
+
+fn avg(context: ExecutionContext) -> CrushResult<()> {
+    aggregate(context, "average", avg_values)
+}
+
+fn min(context: ExecutionContext) -> CrushResult<()> {
+    aggregate(context, "minimum", |v| Ok(pick_value(v, Ordering::Less)))
+}
+
+fn max(context: ExecutionContext) -> CrushResult<()> {
+    aggregate(context, "maximum", |v| Ok(pick_value(v, Ordering::Greater)))
+}
+
+/// Pair up the elements of two lists, stopping at the end of the shorter one.
+fn zip_rows(left: Vec<Value>, right: Vec<Value>) -> Vec<Row> {
+    left.into_iter()
+        .zip(right)
+        .map(|(l, r)| Row::new(vec![l, r]))
+        .collect()
+}
+
+fn zip(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let left = context.this.list()?;
+    let right = context.arguments.list(0)?;
+    let types = vec![
+        ColumnType::new("left", left.element_type()),
+        ColumnType::new("right", right.element_type()),
+    ];
+    context.output.send(Value::Table(Table::new(
+        types,
+        zip_rows(left.dump(), right.dump()),
+    )))
+}
+
+fn enumerate(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len_range(0, 1)?;
+    let start = context.arguments.optional_integer(0)?.unwrap_or(0);
+    let list = context.this.list()?;
+    let output = context.output.initialize(vec![
+        ColumnType::new("idx", ValueType::Integer),
+        ColumnType::new("value", list.element_type()),
+    ])?;
+    // The list may shrink while the stream is being read, so stop at the first missing
+    // element instead of failing.
+    for idx in 0..list.len() {
+        match list.get(idx) {
+            Ok(value) => output.send(Row::new(vec![Value::Integer(start + idx as i128), value]))?,
+            Err(_) => break,
+        }
+    }
     Ok(())
 }
 
+/// Remove the optional type argument from the arguments of a call and return it.
+fn type_argument(arguments: &mut Vec<Argument>) -> CrushResult<Option<ValueType>> {
+    match arguments
+        .iter()
+        .position(|a| a.argument_type.as_deref() == Some("type"))
+    {
+        None => Ok(None),
+        Some(idx) => match arguments.remove(idx).value {
+            Value::Type(t) => Ok(Some(t)),
+            v => argument_error(
+                format!(
+                    "Expected the type argument to be a type, got a {}",
+                    v.value_type().to_string()
+                )
+                .as_str(),
+            ),
+        },
+    }
+}
+
+/// The element type of the result of a map, either the declared type, which all values
+/// must be of, or the type of the values.
+fn map_type(declared: Option<ValueType>, values: &[Value]) -> CrushResult<ValueType> {
+    match declared {
+        None => common_type(values),
+        Some(t) => match values.iter().position(|v| !t.is(v)) {
+            None => Ok(t),
+            Some(idx) => argument_error(
+                format!(
+                    "Expected the result for the element at index {} to be of type {}, got a {}",
+                    idx,
+                    t.to_string(),
+                    values[idx].value_type().to_string()
+                )
+                .as_str(),
+            ),
+        },
+    }
+}
+
+fn map(mut context: ExecutionContext) -> CrushResult<()> {
+    let declared = type_argument(&mut context.arguments)?;
+    context.arguments.check_len(1)?;
+    let function = context.arguments.command(0)?;
+    let list = context.this.clone().list()?;
+    let res = list
+        .dump()
+        .into_iter()
+        .map(|v| call(&function, vec![v], &context))
+        .collect::<CrushResult<Vec<_>>>()?;
+    context
+        .output
+        .send(Value::List(List::new(map_type(declared, &res)?, res)))
+}
+
+fn filter(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let condition = context.arguments.command(0)?;
+    let list = context.this.clone().list()?;
+    let mut res = Vec::new();
+    for (idx, v) in list.dump().into_iter().enumerate() {
+        match call(&condition, vec![v.clone()], &context)? {
+            Value::Bool(true) => res.push(v),
+            Value::Bool(false) => {}
+            other => {
+                return argument_error(
+                    format!(
+                        "Expected the condition to return a boolean for the element at index {}, got a {}",
+                        idx,
+                        other.value_type().to_string()
+                    )
+                    .as_str(),
+                )
+            }
+        }
+    }
+    context
+        .output
+        .send(Value::List(List::new(list.element_type(), res)))
+}
+
+fn reduce(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(2)?;
+    let function = context.arguments.command(0)?;
+    let initial = context.arguments.value(1)?;
+    let list = context.this.clone().list()?;
+    let mut res = initial;
+    for v in list.dump() {
+        res = call(&function, vec![res, v], &context)?;
+    }
+    context.output.send(res)
+}
+
+fn pop(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let value = context.this.list()?.pop();
+    context.output.send(value.unwrap_or_else(Value::Empty))
+}
+
 fn peek(context: ExecutionContext) -> CrushResult<()> {
     context.arguments.check_len(0)?;
-    let o = context.output;
-    context.this.list()?.peek().map(|c| o.send(c));
-    Ok(())
+    let value = context.this.list()?.peek();
+    context.output.send(value.unwrap_or_else(Value::Empty))
+}
+
+/// Convert an index argument to a position in the list, rejecting negative indices.
+fn to_index(list: &List, idx: i128) -> CrushResult<usize> {
+    if idx < 0 {
+        index_error(idx, list.len())
+    } else {
+        Ok(idx as usize)
+    }
 }
 
 fn clear(context: ExecutionContext) -> CrushResult<()> {
@@ -310,7 +895,7 @@ fn setitem(mut context: ExecutionContext) -> CrushResult<()> {
     let list = context.this.list()?;
     let key = context.arguments.integer(0)?;
     let value = context.arguments.value(1)?;
-    list.set(key as usize, value)?;
+    list.set(to_index(&list, key)?, value)?;
     context.output.empty()
 }
 
@@ -318,7 +903,7 @@ fn remove(mut context: ExecutionContext) -> CrushResult<()> {
     context.arguments.check_len(1)?;
     let list = context.this.list()?;
     let idx = context.arguments.integer(0)?;
-    list.remove(idx as usize)
+    context.output.send(list.remove(to_index(&list, idx)?)?)
 }
 
 fn insert(mut context: ExecutionContext) -> CrushResult<()> {
@@ -326,14 +911,17 @@ fn insert(mut context: ExecutionContext) -> CrushResult<()> {
     let list = context.this.list()?;
     let idx = context.arguments.integer(0)?;
     let value = context.arguments.value(1)?;
-    list.insert(idx as usize, value)
+    list.insert(to_index(&list, idx)?, value)
 }
 
 fn truncate(mut context: ExecutionContext) -> CrushResult<()> {
     context.arguments.check_len(1)?;
     let list = context.this.list()?;
-    let idx = context.arguments.integer(0)?;
-    list.truncate(idx as usize);
+    let len = context.arguments.integer(0)?;
+    if len < 0 {
+        return argument_error("Can't truncate a list to a negative length");
+    }
+    list.truncate(len as usize);
     Ok(())
 }
 
@@ -348,5 +936,227 @@ fn getitem(mut context: ExecutionContext) -> CrushResult<()> {
     context.arguments.check_len(1)?;
     let list = context.this.list()?;
     let idx = context.arguments.integer(0)?;
-    context.output.send(list.get(idx as usize)?)
+    context.output.send(list.get(to_index(&list, idx)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::types::test_util::call_method;
+
+    #[test]
+    fn test_join() {
+        assert_eq!(
+            join_values(vec![Value::string("a"), Value::string("b")], ", ").unwrap(),
+            "a, b"
+        );
+        assert_eq!(
+            join_values(vec![Value::Integer(1), Value::Bool(true)], "-").unwrap(),
+            "1-true"
+        );
+        assert_eq!(join_values(vec![], "-").unwrap(), "");
+        assert!(join_values(
+            vec![Value::List(List::new(ValueType::Integer, vec![]))],
+            "-"
+        )
+        .is_err());
+    }
+    #[test]
+    fn test_mutation() {
+        let list = List::new(ValueType::Integer, vec![Value::Integer(1)]);
+        let alias = list.clone();
+        list.append(&mut vec![Value::Integer(2), Value::Integer(3)])
+            .unwrap();
+        assert!(list.append(&mut vec![Value::string("four")]).is_err());
+        assert!(list.insert(0, Value::Bool(true)).is_err());
+        list.insert(0, Value::Integer(0)).unwrap();
+        assert_eq!(alias.len(), 4);
+        assert!(alias.remove(1).unwrap() == Value::Integer(1));
+        assert!(list.pop() == Some(Value::Integer(3)));
+        list.truncate(1);
+        assert!(alias.dump() == vec![Value::Integer(0)]);
+        list.clear();
+        assert!(alias.pop().is_none());
+        assert!(alias.peek().is_none());
+    }
+
+    #[test]
+    fn test_index_errors() {
+        let list = List::new(ValueType::Integer, vec![Value::Integer(1)]);
+        let message = |r: CrushResult<Value>| r.err().unwrap().message;
+        assert_eq!(
+            message(list.remove(1)),
+            "Index 1 is out of bounds for a list of length 1"
+        );
+        assert_eq!(
+            message(list.get(5)),
+            "Index 5 is out of bounds for a list of length 1"
+        );
+        assert!(to_index(&list, -1).is_err());
+        assert_eq!(to_index(&list, 3).unwrap(), 3);
+        assert!(list.insert(2, Value::Integer(2)).is_err());
+        assert!(list.set(1, Value::Integer(2)).is_err());
+    }
+
+    #[test]
+    fn test_map_type() {
+        let values = [Value::Integer(1), Value::Integer(2)];
+        assert_eq!(map_type(None, &values).unwrap(), ValueType::Integer);
+        assert_eq!(map_type(None, &[]).unwrap(), ValueType::Any);
+        assert_eq!(
+            map_type(Some(ValueType::String), &[]).unwrap(),
+            ValueType::String
+        );
+        assert_eq!(
+            map_type(Some(ValueType::Any), &values).unwrap(),
+            ValueType::Any
+        );
+        assert!(map_type(Some(ValueType::String), &values).is_err());
+        assert!(map_type(None, &[Value::Integer(1), Value::string("a")]).is_err());
+    }
+
+    #[test]
+    fn test_type_argument() {
+        let mut arguments = vec![
+            Argument::unnamed(Value::Integer(1)),
+            Argument::named("type", Value::Type(ValueType::Integer)),
+        ];
+        assert!(type_argument(&mut arguments).unwrap() == Some(ValueType::Integer));
+        assert_eq!(arguments.len(), 1);
+        assert!(type_argument(&mut arguments).unwrap().is_none());
+        let mut invalid = vec![Argument::named("type", Value::Integer(1))];
+        assert!(type_argument(&mut invalid).is_err());
+    }
+
+    #[test]
+    fn test_sum_values() {
+        let integers = [Value::Integer(1), Value::Integer(2), Value::Integer(3)];
+        assert!(sum_values(&integers).unwrap() == Value::Integer(6));
+        assert!(
+            sum_values(&[Value::Float(0.5), Value::Float(1.25)]).unwrap() == Value::Float(1.75)
+        );
+        assert!(matches!(sum_values(&[]).unwrap(), Value::Empty()));
+        assert!(sum_values(&[Value::Integer(i128::MAX), Value::Integer(1)]).is_err());
+    }
+
+    #[test]
+    fn test_avg_values() {
+        assert!(avg_values(&[Value::Integer(1), Value::Integer(2)]).unwrap() == Value::Float(1.5));
+        assert!(matches!(avg_values(&[]).unwrap(), Value::Empty()));
+    }
+
+    #[test]
+    fn test_pick_value() {
+        let integers = [Value::Integer(3), Value::Integer(1), Value::Integer(2)];
+        assert!(pick_value(&integers, Ordering::Less) == Value::Integer(1));
+        assert!(pick_value(&integers, Ordering::Greater) == Value::Integer(3));
+        let floats = [Value::Float(f64::NAN), Value::Float(2.5), Value::Float(0.5)];
+        assert!(pick_value(&floats, Ordering::Less) == Value::Float(0.5));
+        assert!(matches!(
+            pick_value(&[Value::Float(f64::NAN)], Ordering::Less),
+            Value::Empty()
+        ));
+        assert!(matches!(pick_value(&[], Ordering::Greater), Value::Empty()));
+    }
+
+    #[test]
+    fn test_zip_rows() {
+        let rows = zip_rows(
+            vec![Value::string("a"), Value::string("b")],
+            vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)],
+        );
+        assert_eq!(rows.len(), 2);
+        assert!(rows[1].cells()[0] == Value::string("b"));
+        assert!(rows[1].cells()[1] == Value::Integer(2));
+        assert!(zip_rows(vec![], vec![Value::Integer(1)]).is_empty());
+    }
+
+    #[test]
+    fn test_enumerate() {
+        let list = Value::List(List::new(
+            ValueType::String,
+            vec![Value::string("a"), Value::string("b")],
+        ));
+        let mut stream = call_method(
+            list,
+            "enumerate",
+            vec![Argument::unnamed(Value::Integer(5))],
+        )
+        .unwrap()
+        .stream()
+        .unwrap();
+        assert_eq!(stream.types()[0].name, "idx");
+        assert_eq!(stream.types()[1].cell_type, ValueType::String);
+        let first = stream.read().unwrap();
+        assert!(first.cells()[0] == Value::Integer(5));
+        assert!(first.cells()[1] == Value::string("a"));
+        assert!(stream.read().unwrap().cells()[0] == Value::Integer(6));
+        assert!(stream.read().is_err());
+    }
+
+    #[test]
+    fn test_sort_by_keys() {
+        let values = vec![
+            Value::Integer(3),
+            Value::Empty(),
+            Value::Float(f64::NAN),
+            Value::Integer(1),
+        ];
+        let sorted = sort_by_keys(values.clone(), values).unwrap();
+        assert_eq!(sorted[0].value_type(), ValueType::Empty);
+        assert!(sorted[1] == Value::Integer(1));
+        assert!(sorted[2] == Value::Integer(3));
+        assert_eq!(sorted[3].value_type(), ValueType::Float);
+
+        let words = vec![Value::string("bb"), Value::string("a"), Value::string("c")];
+        let lengths = vec![Value::Integer(2), Value::Integer(1), Value::Integer(1)];
+        assert!(
+            sort_by_keys(words, lengths).unwrap()
+                == vec![Value::string("a"), Value::string("c"), Value::string("bb")]
+        );
+
+        let lists = vec![Value::List(List::new(ValueType::Integer, vec![]))];
+        assert!(sort_by_keys(lists.clone(), lists).is_err());
+    }
+
+    #[test]
+    fn test_dedup_values() {
+        let values = vec![
+            Value::Integer(1),
+            Value::Integer(1),
+            Value::Integer(2),
+            Value::Integer(1),
+        ];
+        assert!(
+            dedup_values(values.clone(), false).unwrap()
+                == vec![Value::Integer(1), Value::Integer(2), Value::Integer(1)]
+        );
+        assert!(dedup_values(values, true).unwrap() == vec![Value::Integer(1), Value::Integer(2)]);
+        let lists = vec![Value::List(List::new(ValueType::Integer, vec![]))];
+        assert!(dedup_values(lists.clone(), false).is_ok());
+        assert!(dedup_values(lists, true).is_err());
+    }
+
+    #[test]
+    fn test_slice_values() {
+        let values = vec![
+            Value::Integer(1),
+            Value::Integer(2),
+            Value::Integer(3),
+            Value::Integer(4),
+        ];
+        assert!(slice_values(&values, 1, Some(3)).unwrap() == values[1..3].to_vec());
+        assert!(slice_values(&values, -2, None).unwrap() == values[2..].to_vec());
+        assert!(slice_values(&values, -10, Some(10)).unwrap() == values);
+        assert!(slice_values(&values, 3, Some(1)).is_err());
+    }
+
+    #[test]
+    fn test_common_type() {
+        assert!(common_type(&[]).unwrap() == ValueType::Any);
+        assert!(
+            common_type(&[Value::Integer(1), Value::Integer(4)]).unwrap() == ValueType::Integer
+        );
+        assert!(common_type(&[Value::Integer(1), Value::string("a")]).is_err());
+    }
 }
@@ -1,16 +1,17 @@
 use crate::lang::argument::{column_names, Argument};
-use crate::lang::command::CrushCommand;
 use crate::lang::command::OutputType::{Known, Unknown};
+use crate::lang::command::{Command, CrushCommand};
 use crate::lang::errors::{argument_error, mandate, CrushResult};
 use crate::lang::execution_context::ArgumentVector;
 use crate::lang::execution_context::{ExecutionContext, This};
 use crate::lang::scope::Scope;
-use crate::lang::stream::black_hole;
+use crate::lang::stream::{black_hole, channels, empty_channel};
 use crate::lang::table::ColumnType;
 use crate::lang::value::ValueType;
 use crate::lang::{r#struct::Struct, value::Value};
 
 pub mod binary;
+pub mod binary_stream;
 pub mod dict;
 pub mod duration;
 pub mod file;
@@ -21,12 +22,52 @@ pub mod list;
 pub mod re;
 pub mod scope;
 pub mod string;
+pub mod r#struct;
 pub mod table;
 pub mod table_stream;
+#[cfg(test)]
+pub mod test_util;
 pub mod time;
 
+/// Call a command with the specified unnamed arguments and return its output.
+pub fn call(
+    function: &Command,
+    arguments: Vec<Value>,
+    context: &ExecutionContext,
+) -> CrushResult<Value> {
+    let (sender, receiver) = channels();
+    function.invoke(ExecutionContext {
+        input: empty_channel(),
+        output: sender,
+        arguments: arguments.into_iter().map(Argument::unnamed).collect(),
+        env: context.env.clone(),
+        this: None,
+        printer: context.printer.clone(),
+    })?;
+    receiver.recv()
+}
+
+/// The element type of a list of values that must all be of the same type.
+pub fn common_type(values: &[Value]) -> CrushResult<ValueType> {
+    let mut types = values.iter().map(|v| v.value_type());
+    match types.next() {
+        None => Ok(ValueType::Any),
+        Some(first) => match types.find(|t| *t != first) {
+            None => Ok(first),
+            Some(other) => argument_error(
+                format!(
+                    "Expected all results to be of type {}, got a {}",
+                    first.to_string(),
+                    other.to_string()
+                )
+                .as_str(),
+            ),
+        },
+    }
+}
+
 fn materialize(context: ExecutionContext) -> CrushResult<()> {
-    context.output.send(context.input.recv()?.materialize())
+    context.output.send(context.input.recv()?.materialize()?)
 }
 
 fn new(mut context: ExecutionContext) -> CrushResult<()> {
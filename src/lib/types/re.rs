@@ -1,11 +1,16 @@
 use crate::lang::argument::ArgumentHandler;
 use crate::lang::command::Command;
-use crate::lang::command::OutputType::Known;
+use crate::lang::command::OutputType::{Known, Passthrough, Unknown};
 use crate::lang::command::TypeMap;
-use crate::lang::errors::{argument_error, CrushResult};
+use crate::lang::errors::{argument_error, mandate, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, This};
-use crate::lang::value::ValueType;
+use crate::lang::list::List;
+use crate::lang::r#struct::Struct;
+use crate::lang::stream::{CrushStream, OutputStream};
+use crate::lang::table::{ColumnType, ColumnVec, Row};
+use crate::lang::value::{Field, ValueType};
 use crate::lang::{execution_context::ExecutionContext, value::Value};
+use crate::util::regex::check_replacement;
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
 use regex::Regex;
@@ -16,6 +21,11 @@ fn full(name: &'static str) -> Vec<&'static str> {
 }
 
 lazy_static! {
+    static ref FIND_ALL_OUTPUT_TYPE: Vec<ColumnType> = vec![
+        ColumnType::new("begin", ValueType::Integer),
+        ColumnType::new("end", ValueType::Integer),
+        ColumnType::new("match", ValueType::String),
+    ];
     pub static ref METHODS: OrderedMap<String, Command> = {
         let mut res: OrderedMap<String, Command> = OrderedMap::new();
         let path = vec!["global", "types", "re"];
@@ -37,6 +47,49 @@ lazy_static! {
             None,
             Known(ValueType::Bool),
         );
+        res.declare(
+            full("find"),
+            find,
+            false,
+            "re:find text:string",
+            "Returns the first match of the pattern in text",
+            Some("    If the pattern does not match, nothing is returned."),
+            Unknown,
+        );
+        res.declare(
+            full("find_all"),
+            find_all,
+            true,
+            "re:find_all text:string",
+            "Returns a stream of all non-overlapping matches of the pattern in text",
+            Some(
+                r#"    The begin and end columns are character offsets into text, the end being
+    exclusive, so that they can be passed to string:substr."#,
+            ),
+            Known(ValueType::TableStream(FIND_ALL_OUTPUT_TYPE.clone())),
+        );
+        let _ = Split::declare_method(&mut res, &path);
+        res.declare(
+            full("captures"),
+            captures,
+            false,
+            "re:captures text:string",
+            "Returns a struct with the capture groups of the first match of the pattern in text",
+            Some(
+                r#"    Named groups become fields with the same name, unnamed groups become the
+    fields g1, g2 and so on, numbered by their position in the pattern. Groups that
+    did not participate in the match are empty. If the pattern does not match,
+    nothing is returned.
+
+    Example:
+
+    re"(?P<key>\w+)=(\w+)":captures "a=b""#,
+            ),
+            Unknown,
+        );
+        let _ = Filter::declare_method(&mut res, &path);
+        let _ = Extract::declare_method(&mut res, &path);
+        let _ = Rewrite::declare_method(&mut res, &path);
         let _ = ReplaceSignature::declare_method(&mut res, &path); // TODO: why unused?
         let _ = ReplaceAllSignature::declare_method(&mut res, &path); // TODO: why unused?
         res.declare(
@@ -73,6 +126,306 @@ fn not_match(mut context: ExecutionContext) -> CrushResult<()> {
     context.output.send(Value::Bool(!re.is_match(&needle)))
 }
 
+fn find(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let re = context.this.re()?.1;
+    let text = context.arguments.string(0)?;
+    match re.find(&text) {
+        Some(m) => context.output.send(Value::string(m.as_str())),
+        None => context.output.send(Value::Empty()),
+    }
+}
+
+/// The non-overlapping matches of re in text, as character offsets and matched text.
+fn matches<'a>(re: &'a Regex, text: &'a str) -> impl Iterator<Item = (usize, usize, &'a str)> {
+    let mut byte = 0;
+    let mut chars = 0;
+    re.find_iter(text).map(move |m| {
+        chars += text[byte..m.start()].chars().count();
+        let begin = chars;
+        chars += m.as_str().chars().count();
+        byte = m.end();
+        (begin, chars, m.as_str())
+    })
+}
+
+fn find_all(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let re = context.this.re()?.1;
+    let text = context.arguments.string(0)?;
+    let output = context.output.initialize(FIND_ALL_OUTPUT_TYPE.clone())?;
+    for (begin, end, m) in matches(&re, &text) {
+        output.send(Row::new(vec![
+            Value::Integer(begin as i128),
+            Value::Integer(end as i128),
+            Value::string(m),
+        ]))?;
+    }
+    Ok(())
+}
+
+#[signature(
+    split,
+    can_block = false,
+    output = Known(ValueType::List(Box::from(ValueType::String))),
+    short = "Split text into the pieces between the matches of this pattern",
+    long = "With a limit, at most that many pieces are returned, and the last piece contains the rest of the text. Empty pieces, including leading and trailing ones, are kept.",
+    example = "re\"\\s+\":split \"a b  c\""
+)]
+struct Split {
+    #[description("the text to split.")]
+    text: String,
+    #[description("the maximum number of pieces.")]
+    limit: Option<usize>,
+}
+
+/// Split text on the matches of re. Regex::split of the regex version in use drops a
+/// trailing empty piece, so the pieces are collected by hand.
+fn split_text(re: &Regex, text: &str, limit: Option<usize>) -> CrushResult<Vec<String>> {
+    if limit == Some(0) {
+        return argument_error("The limit of a split must be at least 1");
+    }
+    let mut res = Vec::new();
+    let mut rest = 0;
+    for m in re.find_iter(text) {
+        if limit == Some(res.len() + 1) {
+            break;
+        }
+        res.push(text[rest..m.start()].to_string());
+        rest = m.end();
+    }
+    res.push(text[rest..].to_string());
+    Ok(res)
+}
+
+fn split(context: ExecutionContext) -> CrushResult<()> {
+    let re = context.this.clone().re()?.1;
+    let cfg: Split = Split::parse(context.arguments, &context.printer)?;
+    context.output.send(Value::List(List::new(
+        ValueType::String,
+        split_text(&re, &cfg.text, cfg.limit)?
+            .into_iter()
+            .map(Value::String)
+            .collect(),
+    )))
+}
+
+/// The names of the capture groups of re. Unnamed groups are called g1, g2 and so on.
+fn group_names(re: &Regex) -> Vec<String> {
+    re.capture_names()
+        .enumerate()
+        .skip(1)
+        .map(|(idx, name)| {
+            name.map(|n| n.to_string())
+                .unwrap_or_else(|| format!("g{}", idx))
+        })
+        .collect()
+}
+
+fn capture_struct(re: &Regex, text: &str) -> Option<Struct> {
+    let groups = re.captures(text)?;
+    Some(Struct::new(
+        group_names(re)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                (
+                    name,
+                    groups
+                        .get(idx + 1)
+                        .map(|m| Value::string(m.as_str()))
+                        .unwrap_or(Value::Empty()),
+                )
+            })
+            .collect(),
+        None,
+    ))
+}
+
+/// The index of the column the regex is applied to, which must contain strings.
+fn string_column(types: &[ColumnType], column: &Field) -> CrushResult<usize> {
+    let idx = types.find(column)?;
+    if types[idx].cell_type != ValueType::String {
+        return argument_error(
+            format!(
+                "Expected column {} to be of type string, got {}",
+                types[idx].name,
+                types[idx].cell_type.to_string()
+            )
+            .as_str(),
+        );
+    }
+    Ok(idx)
+}
+
+fn cell_string(row: &Row, idx: usize) -> CrushResult<&str> {
+    match &row.cells()[idx] {
+        Value::String(s) => Ok(s),
+        v => argument_error(
+            format!("Expected a string, got {}", v.value_type().to_string()).as_str(),
+        ),
+    }
+}
+
+#[signature(
+    filter,
+    can_block = true,
+    output = Passthrough,
+    short = "Filter a stream, keeping the rows where column matches the regex",
+    example = "ls | re\"\\.rs$\":filter ^file"
+)]
+struct Filter {
+    #[description("the column to match against.")]
+    column: Field,
+    #[description("keep the rows that don't match instead.")]
+    #[default(false)]
+    invert: bool,
+}
+
+fn filter_rows(
+    re: &Regex,
+    idx: usize,
+    invert: bool,
+    input: &mut dyn CrushStream,
+    output: OutputStream,
+) -> CrushResult<()> {
+    while let Ok(row) = input.read() {
+        if re.is_match(cell_string(&row, idx)?) != invert {
+            output.send(row)?;
+        }
+    }
+    Ok(())
+}
+
+fn filter(context: ExecutionContext) -> CrushResult<()> {
+    let re = context.this.re()?.1;
+    let cfg: Filter = Filter::parse(context.arguments, &context.printer)?;
+    let mut input = mandate(context.input.recv()?.stream(), "Expected a stream")?;
+    let idx = string_column(input.types(), &cfg.column)?;
+    let output = context.output.initialize(input.types().to_vec())?;
+    filter_rows(&re, idx, cfg.invert, input.as_mut(), output)
+}
+
+#[signature(
+    extract,
+    can_block = true,
+    short = "Append the capture groups of the regex in column as new columns",
+    long = "Named groups become columns with the same name, unnamed groups become the columns g1, g2 and so on. All new columns are strings, and groups that did not participate in the match are empty strings. Rows where the regex does not match are dropped.",
+    example = "cat log | re\"(?P<level>[A-Z]+): (?P<message>.*)\":extract ^line"
+)]
+struct Extract {
+    #[description("the column to match against.")]
+    column: Field,
+}
+
+fn extract_types(re: &Regex, input_type: &[ColumnType]) -> CrushResult<Vec<ColumnType>> {
+    let mut res = input_type.to_vec();
+    for name in group_names(re) {
+        if res.iter().any(|c| c.name == name) {
+            return argument_error(
+                format!("Capture group {} has the same name as a column", name).as_str(),
+            );
+        }
+        res.push(ColumnType::new(&name, ValueType::String));
+    }
+    Ok(res)
+}
+
+fn extract_rows(
+    re: &Regex,
+    idx: usize,
+    input: &mut dyn CrushStream,
+    output: OutputStream,
+) -> CrushResult<()> {
+    while let Ok(row) = input.read() {
+        let groups = match re.captures(cell_string(&row, idx)?) {
+            Some(groups) => groups
+                .iter()
+                .skip(1)
+                .map(|m| Value::string(m.map(|m| m.as_str()).unwrap_or("")))
+                .collect::<Vec<_>>(),
+            None => continue,
+        };
+        let mut cells = row.into_vec();
+        cells.extend(groups);
+        output.send(Row::new(cells))?;
+    }
+    Ok(())
+}
+
+fn extract(context: ExecutionContext) -> CrushResult<()> {
+    let re = context.this.re()?.1;
+    let cfg: Extract = Extract::parse(context.arguments, &context.printer)?;
+    let mut input = mandate(context.input.recv()?.stream(), "Expected a stream")?;
+    let idx = string_column(input.types(), &cfg.column)?;
+    let output = context
+        .output
+        .initialize(extract_types(&re, input.types())?)?;
+    extract_rows(&re, idx, input.as_mut(), output)
+}
+
+#[signature(
+    rewrite,
+    can_block = true,
+    output = Passthrough,
+    short = "Replace the first match of the regex in column with the replacement",
+    long = "The replacement may refer to capture groups, e.g. $1 or ${name}.",
+    example = "ls | re\"\\.rs$\":rewrite ^file \".bak\" --all"
+)]
+struct Rewrite {
+    #[description("the column to rewrite.")]
+    column: Field,
+    #[description("the replacement.")]
+    replacement: String,
+    #[description("replace all matches instead of only the first one.")]
+    #[default(false)]
+    all: bool,
+}
+
+fn rewrite_rows(
+    re: &Regex,
+    idx: usize,
+    replacement: &str,
+    all: bool,
+    input: &mut dyn CrushStream,
+    output: OutputStream,
+) -> CrushResult<()> {
+    while let Ok(row) = input.read() {
+        let rewritten = {
+            let text = cell_string(&row, idx)?;
+            if all {
+                re.replace_all(text, replacement).to_string()
+            } else {
+                re.replace(text, replacement).to_string()
+            }
+        };
+        let mut cells = row.into_vec();
+        cells[idx] = Value::String(rewritten);
+        output.send(Row::new(cells))?;
+    }
+    Ok(())
+}
+
+fn rewrite(context: ExecutionContext) -> CrushResult<()> {
+    let re = context.this.re()?.1;
+    let cfg: Rewrite = Rewrite::parse(context.arguments, &context.printer)?;
+    check_replacement(&re, &cfg.replacement)?;
+    let mut input = mandate(context.input.recv()?.stream(), "Expected a stream")?;
+    let idx = string_column(input.types(), &cfg.column)?;
+    let output = context.output.initialize(input.types().to_vec())?;
+    rewrite_rows(&re, idx, &cfg.replacement, cfg.all, input.as_mut(), output)
+}
+
+fn captures(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let re = context.this.re()?.1;
+    let text = context.arguments.string(0)?;
+    match capture_struct(&re, &text) {
+        Some(s) => context.output.send(Value::Struct(s)),
+        None => context.output.send(Value::Empty()),
+    }
+}
+
 #[signature(
     replace,
     can_block = false,
@@ -102,6 +455,7 @@ struct ReplaceAllSignature {
 fn replace(context: ExecutionContext) -> CrushResult<()> {
     let re = context.this.re()?.1;
     let args: ReplaceSignature = ReplaceSignature::parse(context.arguments, &context.printer)?;
+    check_replacement(&re, &args.replacement)?;
     context.output.send(Value::string(
         re.replace(&args.text, args.replacement.as_str()).as_ref(),
     ))
@@ -111,8 +465,138 @@ fn replace_all(context: ExecutionContext) -> CrushResult<()> {
     let re = context.this.re()?.1;
     let args: ReplaceAllSignature =
         ReplaceAllSignature::parse(context.arguments, &context.printer)?;
+    check_replacement(&re, &args.replacement)?;
     context.output.send(Value::string(
         re.replace_all(&args.text, args.replacement.as_str())
             .as_ref(),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::table::{Table, TableReader};
+    use crate::lib::stream::test_util::collect_rows;
+
+    #[test]
+    fn test_matches() {
+        let re = Regex::new("[0-9]+").unwrap();
+        assert_eq!(
+            matches(&re, "é12-3é456").collect::<Vec<_>>(),
+            vec![(1, 3, "12"), (4, 5, "3"), (6, 9, "456")]
+        );
+        assert_eq!(matches(&re, "abc").count(), 0);
+        let repeated = Regex::new("aa").unwrap();
+        assert_eq!(
+            matches(&repeated, "aaaaa").collect::<Vec<_>>(),
+            vec![(0, 2, "aa"), (2, 4, "aa")]
+        );
+    }
+
+    #[test]
+    fn test_split_text() {
+        let whitespace = Regex::new(r"\s+").unwrap();
+        assert_eq!(
+            split_text(&whitespace, "a b\t\tc ", None).unwrap(),
+            vec!["a", "b", "c", ""]
+        );
+        assert_eq!(
+            split_text(&whitespace, "a b c", Some(2)).unwrap(),
+            vec!["a", "b c"]
+        );
+        let comma = Regex::new(",").unwrap();
+        assert_eq!(
+            split_text(&comma, ",a,,b", Some(10)).unwrap(),
+            vec!["", "a", "", "b"]
+        );
+        assert_eq!(split_text(&comma, "a,b", Some(1)).unwrap(), vec!["a,b"]);
+        assert_eq!(split_text(&comma, "", None).unwrap(), vec![""]);
+        assert!(split_text(&comma, "a,b", Some(0)).is_err());
+    }
+
+    fn lines(lines: &[&str]) -> TableReader {
+        TableReader::new(Table::new(
+            vec![ColumnType::new("line", ValueType::String)],
+            lines
+                .iter()
+                .map(|l| Row::new(vec![Value::string(l)]))
+                .collect(),
+        ))
+    }
+
+    fn cells(rows: &[Row]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.cells().iter().map(|c| c.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_rows() {
+        let re = Regex::new("^a").unwrap();
+        let (_, rows) = collect_rows(|sender| {
+            let mut input = lines(&["ab", "ba", "ac"]);
+            let output = sender.initialize(input.types().to_vec()).unwrap();
+            filter_rows(&re, 0, false, &mut input, output).unwrap()
+        });
+        assert_eq!(cells(&rows), vec![vec!["ab"], vec!["ac"]]);
+        let (_, rows) = collect_rows(|sender| {
+            let mut input = lines(&["ab", "ba", "ac"]);
+            let output = sender.initialize(input.types().to_vec()).unwrap();
+            filter_rows(&re, 0, true, &mut input, output).unwrap()
+        });
+        assert_eq!(cells(&rows), vec![vec!["ba"]]);
+    }
+
+    #[test]
+    fn test_extract_rows() {
+        let re = Regex::new("(?P<key>[a-z]+)=([0-9]+)?").unwrap();
+        let (types, rows) = collect_rows(|sender| {
+            let mut input = lines(&["a=1", "nothing here", "b="]);
+            let output = sender
+                .initialize(extract_types(&re, input.types()).unwrap())
+                .unwrap();
+            extract_rows(&re, 0, &mut input, output).unwrap()
+        });
+        assert_eq!(
+            types.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(),
+            vec!["line", "key", "g2"]
+        );
+        assert_eq!(
+            cells(&rows),
+            vec![vec!["a=1", "a", "1"], vec!["b=", "b", ""]]
+        );
+        let clash = Regex::new("(?P<line>.*)").unwrap();
+        assert!(extract_types(&clash, lines(&[]).types()).is_err());
+    }
+
+    #[test]
+    fn test_rewrite_rows() {
+        let re = Regex::new("([0-9])").unwrap();
+        let (_, rows) = collect_rows(|sender| {
+            let mut input = lines(&["a1b2", "c"]);
+            let output = sender.initialize(input.types().to_vec()).unwrap();
+            rewrite_rows(&re, 0, "<$1>", true, &mut input, output).unwrap()
+        });
+        assert_eq!(cells(&rows), vec![vec!["a<1>b<2>"], vec!["c"]]);
+    }
+
+    #[test]
+    fn test_string_column() {
+        let types = vec![
+            ColumnType::new("name", ValueType::String),
+            ColumnType::new("size", ValueType::Integer),
+        ];
+        assert_eq!(string_column(&types, &vec!["name".to_string()]).unwrap(), 0);
+        assert!(string_column(&types, &vec!["size".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_capture_struct() {
+        let re = Regex::new("(?P<key>[a-z]+)=([0-9]+)(x)?").unwrap();
+        let s = capture_struct(&re, "--ab=12--").unwrap();
+        assert!(s.get("key") == Some(Value::string("ab")));
+        assert!(s.get("g2") == Some(Value::string("12")));
+        assert!(s.get("g3").unwrap().value_type() == ValueType::Empty);
+        assert!(capture_struct(&re, "nothing").is_none());
+    }
+}
@@ -7,13 +7,90 @@ enum FormatState {
     Normal,
     OpenBrace,
     CloseBrace,
-    Index(usize),
-    Name(String),
+    Placeholder(String),
 }
 
-fn format_argument(res: &mut String, arg: Option<&Argument>) -> CrushResult<()> {
-    res.push_str(mandate(arg, "Missing argument")?.value.to_string().as_str());
-    Ok(())
+/// The part of a placeholder after the colon, e.g. 08 or .3 in {:08} and {name:.3}.
+struct Spec {
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+fn parse_number(s: &str) -> (Option<usize>, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    (s[..end].parse().ok(), &s[end..])
+}
+
+fn parse_spec(spec: &str) -> Option<Spec> {
+    let (zero, rest) = match spec.strip_prefix('0') {
+        Some(rest) => (true, rest),
+        None => (false, spec),
+    };
+    let (width, rest) = parse_number(rest);
+    let (precision, rest) = match rest.strip_prefix('.') {
+        Some(rest) => match parse_number(rest) {
+            (Some(precision), rest) => (Some(precision), rest),
+            (None, _) => return None,
+        },
+        None => (None, rest),
+    };
+    if rest.is_empty() {
+        Some(Spec {
+            zero,
+            width: width.unwrap_or(0),
+            precision,
+        })
+    } else {
+        None
+    }
+}
+
+fn format_value(value: &Value, spec: &Spec, placeholder: &str) -> CrushResult<String> {
+    let numeric = matches!(value, Value::Integer(_) | Value::Float(_));
+    let s = match (value, spec.precision) {
+        (Value::Float(f), Some(precision)) => format!("{:.*}", precision, f),
+        (Value::String(s), Some(precision)) => s.chars().take(precision).collect(),
+        (_, Some(_)) => {
+            return argument_error(
+                format!(
+                    "Placeholder {{{}}} has a precision, which is only supported for floats and strings",
+                    placeholder
+                )
+                .as_str(),
+            )
+        }
+        (v, None) => v.to_string(),
+    };
+    if spec.zero && !numeric {
+        return argument_error(
+            format!(
+                "Placeholder {{{}}} is zero padded, which is only supported for numbers",
+                placeholder
+            )
+            .as_str(),
+        );
+    }
+    let len = s.chars().count();
+    if len >= spec.width {
+        return Ok(s);
+    }
+    let padding = spec.width - len;
+    Ok(if spec.zero {
+        match s.strip_prefix('-') {
+            Some(digits) => format!("-{}{}", "0".repeat(padding), digits),
+            None => format!("{}{}", "0".repeat(padding), s),
+        }
+    } else if numeric {
+        format!("{}{}", " ".repeat(padding), s)
+    } else {
+        format!("{}{}", s, " ".repeat(padding))
+    })
+}
+
+/// Positional placeholders only count the unnamed arguments.
+fn argument_by_index(idx: usize, param: &[Argument]) -> Option<&Argument> {
+    param.iter().filter(|a| a.argument_type.is_none()).nth(idx)
 }
 
 fn argument_by_name<'a>(name: &str, param: &'a [Argument]) -> Option<&'a Argument> {
@@ -27,6 +104,42 @@ fn argument_by_name<'a>(name: &str, param: &'a [Argument]) -> Option<&'a Argumen
     None
 }
 
+fn format_placeholder(
+    res: &mut String,
+    placeholder: &str,
+    implicit_idx: &mut usize,
+    param: &[Argument],
+) -> CrushResult<()> {
+    let (key, spec) = match placeholder.find(':') {
+        Some(idx) => (&placeholder[..idx], &placeholder[idx + 1..]),
+        None => (placeholder, ""),
+    };
+    let spec = mandate(
+        parse_spec(spec),
+        format!(
+            "Invalid format specification in placeholder {{{}}}",
+            placeholder
+        )
+        .as_str(),
+    )?;
+    let arg = if key.is_empty() {
+        *implicit_idx += 1;
+        argument_by_index(*implicit_idx - 1, param)
+    } else if key.chars().all(|c| c.is_ascii_digit()) {
+        argument_by_index(key.parse::<usize>().unwrap_or(usize::MAX), param)
+    } else if key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        argument_by_name(key, param)
+    } else {
+        return argument_error(format!("Invalid placeholder {{{}}}", placeholder).as_str());
+    };
+    let arg = mandate(
+        arg,
+        format!("Missing argument for placeholder {{{}}}", placeholder).as_str(),
+    )?;
+    res.push_str(&format_value(&arg.value, &spec, placeholder)?);
+    Ok(())
+}
+
 fn do_format(format: &str, param: Vec<Argument>) -> CrushResult<String> {
     let mut implicit_idx = 0;
     let mut res = String::new();
@@ -56,34 +169,35 @@ fn do_format(format: &str, param: Vec<Argument>) -> CrushResult<String> {
                     Normal
                 }
                 '}' => {
-                    format_argument(&mut res, param.get(implicit_idx))?;
-                    implicit_idx += 1;
+                    format_placeholder(&mut res, "", &mut implicit_idx, &param)?;
                     Normal
                 }
-                '0'..='9' => Index(ch.to_digit(10).unwrap() as usize),
-                'a'..='z' | 'A'..='Z' => Name(ch.to_string()),
-                _ => return argument_error("Invalid format string"),
+                _ => Placeholder(ch.to_string()),
             },
 
-            Index(idx) => match ch {
+            Placeholder(placeholder) => match ch {
                 '}' => {
-                    format_argument(&mut res, param.get(idx))?;
+                    format_placeholder(&mut res, &placeholder, &mut implicit_idx, &param)?;
                     Normal
                 }
-                '0'..='9' => Index(idx * 10 + ch.to_digit(10).unwrap() as usize),
-                _ => return argument_error("Invalid format string"),
-            },
-
-            Name(name) => match ch {
-                '}' => {
-                    format_argument(&mut res, argument_by_name(name.as_str(), &param))?;
-                    Normal
+                '{' => {
+                    return argument_error(
+                        format!("Unexpected opening brace in placeholder {{{}", placeholder)
+                            .as_str(),
+                    )
                 }
-                _ => Name(name + ch.to_string().as_str()),
+                _ => Placeholder(placeholder + ch.to_string().as_str()),
             },
         }
     }
-    Ok(res)
+    match state {
+        Normal => Ok(res),
+        CloseBrace => argument_error("Unmatched closing brace"),
+        OpenBrace => argument_error("Unmatched opening brace"),
+        Placeholder(placeholder) => argument_error(
+            format!("Unmatched opening brace in placeholder {{{}", placeholder).as_str(),
+        ),
+    }
 }
 
 pub fn format(context: ExecutionContext) -> CrushResult<()> {
@@ -92,3 +206,51 @@ pub fn format(context: ExecutionContext) -> CrushResult<()> {
         .output
         .send(Value::String(do_format(&format, context.arguments)?))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> Vec<Argument> {
+        vec![
+            Argument::unnamed(Value::Integer(42)),
+            Argument::unnamed(Value::Float(1.23456)),
+            Argument::named("name", Value::string("crush")),
+            Argument::unnamed(Value::Integer(-7)),
+        ]
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(do_format("{} {}", args()).unwrap(), "42 1.23456");
+        assert_eq!(do_format("{1} {0}", args()).unwrap(), "1.23456 42");
+        assert_eq!(do_format("{} {} {}", args()).unwrap(), "42 1.23456 -7");
+        assert_eq!(do_format("hi {name}!", args()).unwrap(), "hi crush!");
+        assert_eq!(do_format("{{}}", args()).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_specs() {
+        assert_eq!(do_format("{:05}", args()).unwrap(), "00042");
+        assert_eq!(do_format("{2:04}", args()).unwrap(), "-007");
+        assert_eq!(do_format("{0:4}|", args()).unwrap(), "  42|");
+        assert_eq!(do_format("{1:.3}", args()).unwrap(), "1.235");
+        assert_eq!(do_format("{1:08.2}", args()).unwrap(), "00001.23");
+        assert_eq!(do_format("{name:7}|", args()).unwrap(), "crush  |");
+        assert_eq!(do_format("{name:.2}", args()).unwrap(), "cr");
+    }
+
+    #[test]
+    fn test_errors() {
+        let message = |format: &str| do_format(format, args()).err().unwrap().message;
+        assert!(message("{missing}").contains("{missing}"));
+        assert!(message("{7}").contains("{7}"));
+        assert!(message("{3}").contains("{3}"));
+        assert!(message("{name").contains("{name"));
+        assert!(message("{").contains("opening"));
+        assert!(message("}").contains("closing"));
+        assert!(message("{0:.2}").contains("{0:.2}"));
+        assert!(message("{name:05}").contains("{name:05}"));
+        assert!(message("{0:x}").contains("{0:x}"));
+    }
+}
@@ -4,8 +4,13 @@ use crate::lang::command::OutputType::Known;
 use crate::lang::command::TypeMap;
 use crate::lang::errors::{argument_error, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, This};
+use crate::lang::stream::ValueSender;
+use crate::lang::table::{ColumnType, Row};
 use crate::lang::value::Value;
 use crate::lang::{execution_context::ExecutionContext, list::List, value::ValueType};
+use crate::util::index::clamp_index;
+use crate::util::radix::parse_radix;
+use crate::util::regex::check_replacement;
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
 use signature::signature;
@@ -52,9 +57,14 @@ lazy_static! {
                 full("split"),
                 split,
                 false,
-                "string:split separator:string",
+                "string:split separator:string [limit:integer]",
                 "Splits a string using the specifiec separator",
-                None,
+                Some(
+                    r#"    An empty string is split into an empty list. Earlier versions returned a list
+    holding a single empty string instead. An empty separator splits the string
+    into its characters. If limit is given, the string is split at most limit
+    times, and the last element holds the unsplit remainder."#,
+                ),
                 Known(ValueType::List(Box::from(ValueType::String))),
             );
             res.declare(
@@ -66,17 +76,111 @@ lazy_static! {
                 None,
                 Known(ValueType::String),
             );
+            res.declare(
+                full("trim_start"),
+                trim_start,
+                false,
+                "string:trim_start",
+                "Returns a string with all whitespace trimmed from the start",
+                None,
+                Known(ValueType::String),
+            );
+            res.declare(
+                full("trim_end"),
+                trim_end,
+                false,
+                "string:trim_end",
+                "Returns a string with all whitespace trimmed from the end",
+                None,
+                Known(ValueType::String),
+            );
             res.declare(
                 full("format"),
                 format::format,
                 false,
                 "string:format pattern:string [parameters:any]...",
                 "Format arguments into a string",
+                Some(
+                    r#"    Placeholders are {} for the next unnamed argument, {1} for the unnamed argument
+    with that index and {name} for a named argument. A placeholder may end with a
+    specification, like {:8} for a minimum width, {:08} to pad numbers with zeros
+    and {:.3} for the precision of floats or the maximum length of strings.
+
+    Example:
+
+    "{name}: {:6.2}":format 3.14159 name="pi""#,
+                ),
+                Known(ValueType::String),
+            );
+            res.declare(
+                full("len"),
+                len,
+                false,
+                "string:len",
+                "The number of characters in this string",
+                Some(
+                    r#"    Characters are Unicode scalar values, so this may be less than the number
+    of bytes used to store the string, see string:bytes."#,
+                ),
+                Known(ValueType::Integer),
+            );
+            res.declare(
+                full("bytes"),
+                bytes,
+                false,
+                "string:bytes",
+                "The number of bytes in the UTF-8 encoding of this string",
                 None,
+                Known(ValueType::Integer),
+            );
+            res.declare(
+                full("substr"),
+                substr,
+                false,
+                "string:substr from:integer [to:integer]",
+                "Returns the characters in the range [from, to)",
+                Some(
+                    r#"    Indices count characters, not bytes. If to is omitted, the substring
+    extends to the end of the string. Negative indices count from the end of the
+    string, and indices that are out of bounds are clamped to the string. It is
+    an error for from to come after to.
+
+    Example:
+
+    "héllo":substr 1 3"#,
+                ),
                 Known(ValueType::String),
             );
+            res.declare(
+                full("lines"),
+                lines,
+                true,
+                "string:lines",
+                "Returns a stream with one row per line of this string",
+                Some(
+                    r#"    Lines are separated by \n or \r\n, and the separators are not included in
+    the output. A trailing newline does not start a new line."#,
+                ),
+                Known(ValueType::TableStream(vec![ColumnType::new(
+                    "line",
+                    ValueType::String,
+                )])),
+            );
+            res.declare(
+                full("chars"),
+                chars,
+                true,
+                "string:chars",
+                "Returns a stream with one row per character of this string",
+                None,
+                Known(ValueType::TableStream(vec![ColumnType::new(
+                    "char",
+                    ValueType::String,
+                )])),
+            );
             // TODO: why unused?
             let _ = LPad::declare_method(&mut res, &path);
+            let _ = ParseInt::declare_method(&mut res, &path);
             let _ = RPad::declare_method(&mut res, &path);
             res.declare(
                 full("ends_with"),
@@ -96,6 +200,44 @@ lazy_static! {
                 None,
                 Known(ValueType::Bool),
             );
+            res.declare(
+                full("replace"),
+                replace,
+                false,
+                "string:replace pattern:(string|regex) replacement:string",
+                "Replace the first occurrence of pattern in this string with replacement",
+                Some(
+                    r#"    A string pattern is matched literally. If pattern is a regex, the replacement
+    may refer to capture groups, e.g. $1 or ${name}.
+
+    Example:
+
+    "2020-01-31":replace re"(\d+)-(\d+)-(\d+)" "$3/$2/$1""#,
+                ),
+                Known(ValueType::String),
+            );
+            res.declare(
+                full("replace_all"),
+                replace_all,
+                false,
+                "string:replace_all pattern:(string|regex) replacement:string",
+                "Replace all occurrences of pattern in this string with replacement",
+                Some(
+                    r#"    A string pattern is matched literally, and occurrences don't overlap. If
+    pattern is a regex, the replacement may refer to capture groups, e.g. $1 or
+    ${name}."#,
+                ),
+                Known(ValueType::String),
+            );
+            res.declare(
+                full("contains"),
+                contains,
+                false,
+                "string:contains needle:string",
+                "True if needle occurs anywhere in this string",
+                None,
+                Known(ValueType::Bool),
+            );
             res.declare(full("is_alphanumeric"),
             is_alphanumeric, false,
             "string:is_alphanumeric",
@@ -158,6 +300,7 @@ lazy_static! {
             );
             // TODO: why unused?
             let _ = IsDigit::declare_method(&mut res, &path);
+            let _ = FromBase64::declare_method(&mut res, &path);
             res
         };
 }
@@ -176,13 +319,39 @@ fn lower(context: ExecutionContext) -> CrushResult<()> {
         .send(Value::String(context.this.string()?.to_lowercase()))
 }
 
+fn split_string(s: &str, separator: &str, limit: Option<usize>) -> Vec<Value> {
+    let pieces = limit
+        .map(|limit| limit.saturating_add(1))
+        .unwrap_or(usize::MAX);
+    if s.is_empty() {
+        vec![]
+    } else if separator.is_empty() {
+        let mut res: Vec<Value> = s
+            .char_indices()
+            .take(pieces)
+            .map(|(idx, c)| Value::string(&s[idx..idx + c.len_utf8()]))
+            .collect();
+        if res.len() == pieces {
+            let rest = s.char_indices().nth(pieces - 1).unwrap().0;
+            res[pieces - 1] = Value::string(&s[rest..]);
+        }
+        res
+    } else {
+        s.splitn(pieces, separator).map(Value::string).collect()
+    }
+}
+
 fn split(mut context: ExecutionContext) -> CrushResult<()> {
-    context.arguments.check_len(1)?;
+    context.arguments.check_len_range(1, 2)?;
     let this = context.this.string()?;
     let separator = context.arguments.string(0)?;
+    let limit = match context.arguments.optional_integer(1)? {
+        Some(limit) if limit < 0 => return argument_error("The split limit can't be negative"),
+        limit => limit.map(|limit| limit as usize),
+    };
     context.output.send(Value::List(List::new(
         ValueType::String,
-        this.split(&separator).map(|s| Value::string(s)).collect(),
+        split_string(&this, &separator, limit),
     )))
 }
 
@@ -193,6 +362,20 @@ fn trim(context: ExecutionContext) -> CrushResult<()> {
         .send(Value::string(context.this.string()?.trim()))
 }
 
+fn trim_start(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::string(context.this.string()?.trim_start()))
+}
+
+fn trim_end(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::string(context.this.string()?.trim_end()))
+}
+
 #[signature(
     lpad,
     can_block = false,
@@ -206,19 +389,36 @@ struct LPad {
     padding: String,
 }
 
+/// Truncate or pad s to exactly length characters.
+fn pad(s: &str, length: i128, padding: &str, left: bool) -> CrushResult<String> {
+    if length < 0 {
+        return argument_error("Can't pad a string to a negative length");
+    }
+    let length = length as usize;
+    let mut padding_chars = padding.chars();
+    let fill = match (padding_chars.next(), padding_chars.next()) {
+        (Some(c), None) => c,
+        _ => return argument_error("Padding string must be exactly one character long"),
+    };
+    let current = s.chars().count();
+    if length <= current {
+        Ok(s.chars().take(length).collect())
+    } else {
+        let fill = fill.to_string().repeat(length - current);
+        Ok(if left {
+            fill + s
+        } else {
+            s.to_string() + &fill
+        })
+    }
+}
+
 fn lpad(context: ExecutionContext) -> CrushResult<()> {
     let cfg: LPad = LPad::parse(context.arguments, &context.printer)?;
     let s = context.this.string()?;
-    let len = cfg.length as usize;
-    if cfg.padding.len() != 1 {
-        argument_error("Padding string must be exactly one character long")
-    } else if len <= s.len() {
-        context.output.send(Value::string(&s[0..len]))
-    } else {
-        let mut res = cfg.padding.repeat(len - s.len());
-        res += s.as_ref();
-        context.output.send(Value::string(res.as_str()))
-    }
+    context
+        .output
+        .send(Value::String(pad(&s, cfg.length, &cfg.padding, true)?))
 }
 
 #[signature(
@@ -237,23 +437,114 @@ struct RPad {
 fn rpad(context: ExecutionContext) -> CrushResult<()> {
     let cfg: RPad = RPad::parse(context.arguments, &context.printer)?;
     let s = context.this.string()?;
-    let len = cfg.length as usize;
-    if cfg.padding.len() != 1 {
-        argument_error("Padding string must be exactly one character long")
-    } else if len <= s.len() {
-        context.output.send(Value::string(&s[0..len]))
-    } else {
-        let mut res = s.to_string();
-        res += cfg.padding.repeat(len - s.len()).as_str();
-        context.output.send(Value::string(res.as_str()))
+    context
+        .output
+        .send(Value::String(pad(&s, cfg.length, &cfg.padding, false)?))
+}
+
+fn len(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context.output.send(Value::Integer(
+        context.this.string()?.chars().count() as i128
+    ))
+}
+
+fn bytes(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Integer(context.this.string()?.len() as i128))
+}
+
+#[signature(
+    parse_int,
+    can_block = false,
+    output = Known(ValueType::Integer),
+    short = "Parse this string as an integer in the specified radix",
+    long = "Digits of either case are accepted, as is the customary 0x, 0o or 0b prefix for radix 16, 8 and 2. Surrounding whitespace is ignored.",
+    example = "\"0x1f\":parse_int radix=16"
+)]
+struct ParseInt {
+    #[description("the radix to use, between 2 and 36.")]
+    #[default(10)]
+    radix: i128,
+}
+
+fn parse_int(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: ParseInt = ParseInt::parse(context.arguments, &context.printer)?;
+    let s = context.this.string()?;
+    context
+        .output
+        .send(Value::Integer(parse_radix(s.trim(), cfg.radix)?))
+}
+
+/// Output a single column stream with one row per item, without materializing a table.
+fn send_rows<'a>(
+    sender: ValueSender,
+    column: &str,
+    items: impl Iterator<Item = &'a str>,
+) -> CrushResult<()> {
+    let output = sender.initialize(vec![ColumnType::new(column, ValueType::String)])?;
+    for item in items {
+        output.send(Row::new(vec![Value::string(item)]))?;
+    }
+    Ok(())
+}
+
+fn lines(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let s = context.this.string()?;
+    send_rows(context.output, "line", s.lines())
+}
+
+fn chars(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let s = context.this.string()?;
+    send_rows(
+        context.output,
+        "char",
+        s.char_indices().map(|(idx, c)| &s[idx..idx + c.len_utf8()]),
+    )
+}
+
+/// The characters of s in the range [from, to), where indices count characters.
+fn substring(s: &str, from: i128, to: Option<i128>) -> CrushResult<String> {
+    let len = s.chars().count();
+    let from = clamp_index(from, len);
+    let to = to.map(|to| clamp_index(to, len)).unwrap_or(len);
+    if from > to {
+        return argument_error(
+            format!("Substring start {} is after substring end {}", from, to).as_str(),
+        );
     }
+    Ok(s.chars().skip(from).take(to - from).collect())
+}
+
+fn substr(mut context: ExecutionContext) -> CrushResult<()> {
+    let s = context.this.string()?;
+    context.arguments.check_len_range(1, 2)?;
+    let from = context.arguments.integer(0)?;
+    let to = context.arguments.optional_integer(1)?;
+    context.output.send(Value::String(substring(&s, from, to)?))
+}
+
+fn repeat_string(s: &str, times: i128) -> CrushResult<String> {
+    if times < 0 {
+        return argument_error("Can't repeat a string a negative number of times");
+    }
+    if times > usize::MAX as i128 || s.len().checked_mul(times as usize).is_none() {
+        return argument_error("Repeated string would be too long");
+    }
+    Ok(s.repeat(times as usize))
 }
 
 fn repeat(mut context: ExecutionContext) -> CrushResult<()> {
     context.arguments.check_len(1)?;
     let s = context.this.string()?;
-    let times = context.arguments.integer(0)? as usize;
-    context.output.send(Value::string(s.repeat(times).as_str()))
+    let times = context.arguments.integer(0)?;
+    context
+        .output
+        .send(Value::String(repeat_string(&s, times)?))
 }
 
 fn ends_with(mut context: ExecutionContext) -> CrushResult<()> {
@@ -270,6 +561,66 @@ fn starts_with(mut context: ExecutionContext) -> CrushResult<()> {
     context.output.send(Value::Bool(s.starts_with(&pre)))
 }
 
+fn replace_string(s: &str, pattern: &Value, replacement: &str, all: bool) -> CrushResult<String> {
+    match pattern {
+        Value::String(p) => {
+            if p.is_empty() {
+                argument_error("Can't replace an empty pattern")
+            } else if all {
+                Ok(s.replace(p.as_str(), replacement))
+            } else {
+                Ok(s.replacen(p.as_str(), replacement, 1))
+            }
+        }
+        Value::Regex(_, re) => {
+            check_replacement(re, replacement)?;
+            if all {
+                Ok(re.replace_all(s, replacement).to_string())
+            } else {
+                Ok(re.replace(s, replacement).to_string())
+            }
+        }
+        Value::Glob(_) => argument_error(
+            "Can't replace using a glob, convert it to a regex first, e.g. (convert pattern regex)",
+        ),
+        v => argument_error(
+            format!(
+                "Expected the pattern to be a string or a regex, got a {}",
+                v.value_type().to_string()
+            )
+            .as_str(),
+        ),
+    }
+}
+
+fn replace_this(mut context: ExecutionContext, all: bool) -> CrushResult<()> {
+    context.arguments.check_len(2)?;
+    let s = context.this.string()?;
+    let pattern = context.arguments.value(0)?;
+    let replacement = context.arguments.string(1)?;
+    context.output.send(Value::String(replace_string(
+        &s,
+        &pattern,
+        &replacement,
+        all,
+    )?))
+}
+
+fn replace(context: ExecutionContext) -> CrushResult<()> {
+    replace_this(context, false)
+}
+
+fn replace_all(context: ExecutionContext) -> CrushResult<()> {
+    replace_this(context, true)
+}
+
+fn contains(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let s = context.this.string()?;
+    let needle = context.arguments.string(0)?;
+    context.output.send(Value::Bool(s.contains(&needle)))
+}
+
 macro_rules! per_char_method {
     ($name:ident, $test:expr) => {
         fn $name(context: ExecutionContext) -> CrushResult<()> {
@@ -307,3 +658,210 @@ fn is_digit(context: ExecutionContext) -> CrushResult<()> {
         s.chars().all(|ch| ch.is_digit(cfg.radix as u32)),
     ))
 }
+
+#[signature(
+    from_base64,
+    can_block = false,
+    output = Known(ValueType::Binary),
+    short = "Decode this base64 string with padding into a binary",
+    example = "\"aGVsbG8=\":from_base64"
+)]
+struct FromBase64 {
+    #[description("use the URL and filename safe alphabet, with - and _ instead of + and /.")]
+    #[default(false)]
+    url: bool,
+}
+
+fn decode_base64(s: &str, url: bool) -> CrushResult<Vec<u8>> {
+    let config = if url {
+        base64::URL_SAFE
+    } else {
+        base64::STANDARD
+    };
+    match base64::decode_config(s, config) {
+        Ok(res) => Ok(res),
+        Err(base64::DecodeError::InvalidByte(offset, byte))
+        | Err(base64::DecodeError::InvalidLastSymbol(offset, byte)) => argument_error(
+            format!(
+                "Invalid base64 character {:?} at position {}",
+                byte as char, offset
+            )
+            .as_str(),
+        ),
+        Err(base64::DecodeError::InvalidLength) => {
+            argument_error("Invalid base64 data, the length is not a valid encoding")
+        }
+    }
+}
+
+fn from_base64(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: FromBase64 = FromBase64::parse(context.arguments, &context.printer)?;
+    context.output.send(Value::Binary(decode_base64(
+        &context.this.string()?,
+        cfg.url,
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::stream::test_util::collect_rows;
+    use crate::util::glob::Glob;
+    use rand::Rng;
+    use regex::Regex;
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad("abc", 5, "-", true).unwrap(), "--abc");
+        assert_eq!(pad("abc", 5, "-", false).unwrap(), "abc--");
+        assert_eq!(pad("héllo", 7, "é", true).unwrap(), "ééhéllo");
+        assert_eq!(pad("héllo", 2, " ", false).unwrap(), "hé");
+        assert!(pad("abc", 5, "", true).is_err());
+        assert!(pad("abc", 5, "ab", true).is_err());
+        assert!(pad("abc", -1, " ", true).is_err());
+    }
+
+    #[test]
+    fn test_repeat_string() {
+        assert_eq!(repeat_string("ab", 3).unwrap(), "ababab");
+        assert_eq!(repeat_string("ab", 0).unwrap(), "");
+        assert_eq!(repeat_string("", 5).unwrap(), "");
+        assert!(repeat_string("ab", -1).is_err());
+        assert!(repeat_string("ab", i128::MAX).is_err());
+    }
+
+    #[test]
+    fn test_substring() {
+        assert_eq!(substring("héllo", 1, Some(3)).unwrap(), "él");
+        assert_eq!(substring("héllo", 1, None).unwrap(), "éllo");
+        assert_eq!(substring("héllo", -3, Some(-1)).unwrap(), "ll");
+        assert_eq!(substring("héllo", 2, Some(100)).unwrap(), "llo");
+        assert_eq!(substring("héllo", -100, Some(2)).unwrap(), "hé");
+        assert_eq!(substring("", 0, None).unwrap(), "");
+        assert!(substring("héllo", 3, Some(1)).is_err());
+    }
+
+    fn sent_rows<'a>(items: impl Iterator<Item = &'a str>) -> Vec<String> {
+        let (types, rows) = collect_rows(|sender| send_rows(sender, "line", items).unwrap());
+        assert_eq!(types, vec![ColumnType::new("line", ValueType::String)]);
+        rows.iter()
+            .map(|row| match &row.cells()[0] {
+                Value::String(s) => s.clone(),
+                _ => panic!("Expected a string"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_send_rows() {
+        assert_eq!(sent_rows("a\r\nb\n\nc\n".lines()), vec!["a", "b", "", "c"]);
+        assert_eq!(sent_rows("a\r\nb".lines()), vec!["a", "b"]);
+        assert_eq!(sent_rows("a\r\nb\r\n".lines()), vec!["a", "b"]);
+        assert_eq!(sent_rows("\n".lines()), vec![""]);
+        assert!(sent_rows("".lines()).is_empty());
+    }
+
+    #[test]
+    fn test_split() {
+        assert!(
+            split_string("a,b,,c", ",", None)
+                == vec![
+                    Value::string("a"),
+                    Value::string("b"),
+                    Value::string(""),
+                    Value::string("c"),
+                ]
+        );
+        assert!(split_string("", ",", None).is_empty());
+        assert!(
+            split_string("abc", "", None)
+                == vec![Value::string("a"), Value::string("b"), Value::string("c"),]
+        );
+    }
+
+    #[test]
+    fn test_split_limit() {
+        assert!(
+            split_string("a,b,c", ",", Some(1)) == vec![Value::string("a"), Value::string("b,c")]
+        );
+        assert!(split_string("a,b,c", ",", Some(0)) == vec![Value::string("a,b,c")]);
+        assert!(split_string("a,b", ",", Some(5)) == vec![Value::string("a"), Value::string("b")]);
+        assert!(
+            split_string("héllo", "", Some(2))
+                == vec![Value::string("h"), Value::string("é"), Value::string("llo")]
+        );
+        assert!(split_string("ab", "", Some(0)) == vec![Value::string("ab")]);
+        assert!(split_string("ab", "", Some(2)) == vec![Value::string("a"), Value::string("b")]);
+    }
+
+    #[test]
+    fn test_replace() {
+        let text = |s: &str| Value::string(s);
+        assert_eq!(
+            replace_string("aaa", &text("aa"), "b", false).unwrap(),
+            "ba"
+        );
+        assert_eq!(
+            replace_string("aaaa", &text("aa"), "b", true).unwrap(),
+            "bb"
+        );
+        assert_eq!(replace_string("aaa", &text("aa"), "b", true).unwrap(), "ba");
+        assert_eq!(
+            replace_string("a.b.c", &text("."), "", true).unwrap(),
+            "abc"
+        );
+        assert!(replace_string("abc", &text(""), "x", true).is_err());
+
+        let re = Value::Regex(
+            "(\\w+)@(\\w+)".to_string(),
+            Regex::new("(\\w+)@(\\w+)").unwrap(),
+        );
+        assert_eq!(
+            replace_string("a@b c@d", &re, "$2@$1", false).unwrap(),
+            "b@a c@d"
+        );
+        assert_eq!(
+            replace_string("a@b c@d", &re, "$2@$1", true).unwrap(),
+            "b@a d@c"
+        );
+        assert!(replace_string("a@b", &re, "$3", true).is_err());
+        let glob = Value::Glob(Glob::new("*.txt"));
+        assert!(replace_string("a.txt", &glob, "x", true).is_err());
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        let mut rng = rand::thread_rng();
+        for len in 0..64 {
+            let val: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            for url in &[false, true] {
+                let config = if *url {
+                    base64::URL_SAFE
+                } else {
+                    base64::STANDARD
+                };
+                let encoded = base64::encode_config(&val, config);
+                assert_eq!(decode_base64(&encoded, *url).unwrap(), val);
+            }
+        }
+    }
+
+    #[test]
+    fn test_base64_alphabets() {
+        let val = vec![0xfbu8, 0xff];
+        assert_eq!(base64::encode(&val), "+/8=");
+        assert_eq!(decode_base64("+/8=", false).unwrap(), val);
+        assert_eq!(decode_base64("-_8=", true).unwrap(), val);
+        assert!(decode_base64("-_8=", false).is_err());
+        assert!(decode_base64("+/8=", true).is_err());
+    }
+
+    #[test]
+    fn test_base64_errors() {
+        match decode_base64("aGV!bG8=", false) {
+            Err(e) => assert!(e.message.contains("position 3")),
+            Ok(_) => panic!("Expected an error"),
+        }
+        assert!(decode_base64("aGVsb", false).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use crate::lang::argument::ArgumentHandler;
+use crate::lang::command::Command;
+use crate::lang::command::OutputType::Known;
+use crate::lang::command::TypeMap;
+use crate::lang::errors::{argument_error, CrushResult};
+use crate::lang::execution_context::{ArgumentVector, This};
+use crate::lang::list::List;
+use crate::lang::r#struct::Struct;
+use crate::lang::value::ValueType;
+use crate::lang::{execution_context::ExecutionContext, value::Value};
+use lazy_static::lazy_static;
+use ordered_map::OrderedMap;
+use signature::signature;
+
+fn full(name: &'static str) -> Vec<&'static str> {
+    vec!["global", "types", "struct", name]
+}
+
+lazy_static! {
+    pub static ref METHODS: OrderedMap<String, Command> = {
+        let mut res: OrderedMap<String, Command> = OrderedMap::new();
+        let path = vec!["global", "types", "struct"];
+        res.declare(
+            full("to_dict"),
+            to_dict,
+            false,
+            "struct:to_dict",
+            "Create a dict mapping the name of each field of this struct to its value",
+            Some("    Fields inherited from a parent struct are not included."),
+            Known(ValueType::Dict(
+                Box::from(ValueType::String),
+                Box::from(ValueType::Any),
+            )),
+        );
+        res.declare(
+            full("fields"),
+            fields,
+            false,
+            "struct:fields",
+            "Return a list of the names of the fields of this struct",
+            Some("    Fields inherited from a parent struct are not included."),
+            Known(ValueType::List(Box::from(ValueType::String))),
+        );
+        let _ = Get::declare_method(&mut res, &path);
+        res.declare(
+            full("with"),
+            with,
+            false,
+            "struct:with name:string value:any",
+            "Return a copy of this struct with the specified field added or replaced",
+            None,
+            Known(ValueType::Struct),
+        );
+        res.declare(
+            full("without"),
+            without,
+            false,
+            "struct:without name:string",
+            "Return a copy of this struct without the specified field",
+            Some("    It is an error for the field to not be present in this struct."),
+            Known(ValueType::Struct),
+        );
+        res.declare(
+            full("rename"),
+            rename,
+            false,
+            "struct:rename old:string new:string",
+            "Return a copy of this struct with the specified field renamed",
+            Some(
+                r#"    It is an error for the old field to not be present in this struct, or for
+    the new one to already be present."#,
+            ),
+            Known(ValueType::Struct),
+        );
+        res
+    };
+}
+
+fn to_dict(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context.output.send(
+        Value::Struct(context.this.r#struct()?).convert(ValueType::Dict(
+            Box::from(ValueType::String),
+            Box::from(ValueType::Any),
+        ))?,
+    )
+}
+
+fn fields(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context.output.send(Value::List(List::new(
+        ValueType::String,
+        context
+            .this
+            .r#struct()?
+            .local_elements()
+            .into_iter()
+            .map(|(name, _)| Value::String(name))
+            .collect(),
+    )))
+}
+
+#[signature(
+    get,
+    can_block = false,
+    short = "Return the value of the field with the specified name",
+    long = "It is an error for the field to not be present, unless missing_ok is set, in which case nothing is returned. Fields inherited from a parent struct are included.",
+    example = "row:get \"name\" --missing_ok"
+)]
+struct Get {
+    #[description("the name of the field.")]
+    name: String,
+    #[description("return nothing instead of failing if the field is not present.")]
+    #[default(false)]
+    missing_ok: bool,
+}
+
+fn get(context: ExecutionContext) -> CrushResult<()> {
+    let cfg: Get = Get::parse(context.arguments, &context.printer)?;
+    match context.this.r#struct()?.get(&cfg.name) {
+        Some(value) => context.output.send(value),
+        None if cfg.missing_ok => context.output.send(Value::Empty()),
+        None => missing_field(&cfg.name),
+    }
+}
+
+fn missing_field<T>(name: &str) -> CrushResult<T> {
+    argument_error(format!("The struct has no field named {}", name).as_str())
+}
+
+/// The fields of a struct with the specified field added, or replaced if it is present.
+fn with_field(
+    mut elements: Vec<(String, Value)>,
+    name: String,
+    value: Value,
+) -> Vec<(String, Value)> {
+    match elements.iter_mut().find(|(n, _)| *n == name) {
+        Some(element) => element.1 = value,
+        None => elements.push((name, value)),
+    }
+    elements
+}
+
+fn without_field(
+    mut elements: Vec<(String, Value)>,
+    name: &str,
+) -> CrushResult<Vec<(String, Value)>> {
+    match elements.iter().position(|(n, _)| n == name) {
+        Some(idx) => {
+            elements.remove(idx);
+            Ok(elements)
+        }
+        None => missing_field(name),
+    }
+}
+
+fn rename_field(
+    mut elements: Vec<(String, Value)>,
+    old: &str,
+    new: String,
+) -> CrushResult<Vec<(String, Value)>> {
+    if old != new && elements.iter().any(|(n, _)| *n == new) {
+        return argument_error(format!("The struct already has a field named {}", new).as_str());
+    }
+    match elements.iter_mut().find(|(n, _)| n == old) {
+        Some(element) => {
+            element.0 = new;
+            Ok(elements)
+        }
+        None => missing_field(old),
+    }
+}
+
+/// A new struct with the specified fields and the same parent as this struct.
+fn send_modified(
+    context: ExecutionContext,
+    f: impl FnOnce(Vec<(String, Value)>) -> CrushResult<Vec<(String, Value)>>,
+) -> CrushResult<()> {
+    let this = context.this.r#struct()?;
+    context.output.send(Value::Struct(Struct::new(
+        f(this.local_elements())?,
+        this.parent(),
+    )))
+}
+
+fn with(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(2)?;
+    let name = context.arguments.string(0)?;
+    let value = context.arguments.value(1)?;
+    send_modified(context, |elements| Ok(with_field(elements, name, value)))
+}
+
+fn without(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let name = context.arguments.string(0)?;
+    send_modified(context, |elements| without_field(elements, &name))
+}
+
+fn rename(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(2)?;
+    let old = context.arguments.string(0)?;
+    let new = context.arguments.string(1)?;
+    send_modified(context, |elements| rename_field(elements, &old, new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::table::ColumnType;
+
+    fn point() -> Vec<(String, Value)> {
+        vec![
+            ("x".to_string(), Value::Integer(1)),
+            ("y".to_string(), Value::Integer(2)),
+        ]
+    }
+
+    fn signature(elements: Vec<(String, Value)>) -> Vec<ColumnType> {
+        Struct::new(elements, None).local_signature()
+    }
+
+    #[test]
+    fn test_with_field() {
+        assert_eq!(
+            signature(with_field(point(), "y".to_string(), Value::string("a"))),
+            vec![
+                ColumnType::new("x", ValueType::Integer),
+                ColumnType::new("y", ValueType::String),
+            ]
+        );
+        assert_eq!(
+            signature(with_field(point(), "z".to_string(), Value::Float(0.5))),
+            vec![
+                ColumnType::new("x", ValueType::Integer),
+                ColumnType::new("y", ValueType::Integer),
+                ColumnType::new("z", ValueType::Float),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_field() {
+        assert_eq!(
+            signature(without_field(point(), "x").unwrap()),
+            vec![ColumnType::new("y", ValueType::Integer)]
+        );
+        assert!(without_field(point(), "z").is_err());
+    }
+
+    #[test]
+    fn test_rename_field() {
+        let renamed = rename_field(point(), "x", "a".to_string()).unwrap();
+        assert_eq!(
+            signature(renamed.clone()),
+            vec![
+                ColumnType::new("a", ValueType::Integer),
+                ColumnType::new("y", ValueType::Integer),
+            ]
+        );
+        assert!(Struct::new(renamed, None).get("a") == Some(Value::Integer(1)));
+        assert!(rename_field(point(), "z", "a".to_string()).is_err());
+        assert!(rename_field(point(), "x", "y".to_string()).is_err());
+        assert_eq!(
+            signature(rename_field(point(), "x", "x".to_string()).unwrap()),
+            signature(point())
+        );
+        assert!(rename_field(point(), "z", "z".to_string()).is_err());
+    }
+}
@@ -0,0 +1,27 @@
+use crate::lang::argument::Argument;
+use crate::lang::errors::{mandate, CrushResult};
+use crate::lang::execution_context::ExecutionContext;
+use crate::lang::printer;
+use crate::lang::scope::Scope;
+use crate::lang::stream::{channels, empty_channel};
+use crate::lang::value::Value;
+
+/// Call the named method of a value with the specified arguments and return its output.
+pub fn call_method(this: Value, name: &str, arguments: Vec<Argument>) -> CrushResult<Value> {
+    let value_type = this.value_type();
+    let method = mandate(
+        value_type.fields().get(name),
+        format!("Unknown method {}", name).as_str(),
+    )?;
+    let (printer, _) = printer::init();
+    let (sender, receiver) = channels();
+    method.invoke(ExecutionContext {
+        input: empty_channel(),
+        output: sender,
+        arguments,
+        env: Scope::create_root(),
+        this: Some(this),
+        printer,
+    })?;
+    receiver.recv()
+}
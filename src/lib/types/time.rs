@@ -1,17 +1,16 @@
 use crate::lang::argument::ArgumentHandler;
 use crate::lang::command::Command;
-use crate::lang::command::OutputType::Known;
+use crate::lang::command::OutputType::{Known, Unknown};
 use crate::lang::command::TypeMap;
-use crate::lang::errors::{argument_error, to_crush_error, CrushResult};
+use crate::lang::errors::{argument_error, mandate, CrushResult};
 use crate::lang::execution_context::{ArgumentVector, This};
 use crate::lang::value::ValueType;
 use crate::lang::{execution_context::ExecutionContext, value::Value};
-use chrono::{Datelike, Local, Timelike};
+use crate::util::time::{format_time, parse_time_with_format};
+use chrono::{DateTime, Datelike, Local, Timelike};
 use lazy_static::lazy_static;
 use ordered_map::OrderedMap;
 use signature::signature;
-use std::cmp::max;
-use time::strptime;
 
 fn full(name: &'static str) -> Vec<&'static str> {
     vec!["global", "types", "time", name]
@@ -20,7 +19,7 @@ fn full(name: &'static str) -> Vec<&'static str> {
 lazy_static! {
     pub static ref METHODS: OrderedMap<String, Command> = {
         let mut res: OrderedMap<String, Command> = OrderedMap::new();
-        let path = vec!["global", "types", "string"];
+        let path = vec!["global", "types", "time"];
         res.declare(
             full("__add__"),
             add,
@@ -34,10 +33,14 @@ lazy_static! {
             full("__sub__"),
             sub,
             false,
-            "time - delta:duration",
-            "Remove the specified delta from this time",
-            None,
-            Known(ValueType::Time),
+            "time - (delta:duration | time:time)",
+            "Remove the specified delta from this time, or compute the time elapsed since another time",
+            Some(
+                r#"    Subtracting a duration gives a time, subtracting a time gives a duration.
+    Arithmetic is done on absolute time, so adding a day across a daylight saving
+    transition changes the wall clock time by 23 or 25 hours."#,
+            ),
+            Unknown,
         );
         res.declare(
             full("now"),
@@ -48,57 +51,320 @@ lazy_static! {
             None,
             Known(ValueType::Time),
         );
+        res.declare(
+            full("today"),
+            today,
+            false,
+            "time:today",
+            "Midnight at the start of the current day in the local timezone",
+            None,
+            Known(ValueType::Time),
+        );
+        res.declare(
+            full("format"),
+            format,
+            false,
+            "time:format format:string",
+            "Format this time using a chrono-style format string",
+            Some(
+                r#"    Example:
+
+    (time:now):format "%Y-%m-%d %H:%M""#,
+            ),
+            Known(ValueType::String),
+        );
+        res.declare(
+            full("year"),
+            year,
+            false,
+            "time:year",
+            "The year of this time in the local timezone",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("month"),
+            month,
+            false,
+            "time:month",
+            "The month of this time in the local timezone, from 1 to 12",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("day"),
+            day,
+            false,
+            "time:day",
+            "The day of the month of this time in the local timezone, from 1 to 31",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("hour"),
+            hour,
+            false,
+            "time:hour",
+            "The hour of this time in the local timezone, from 0 to 23",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("minute"),
+            minute,
+            false,
+            "time:minute",
+            "The minute of this time, from 0 to 59",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("second"),
+            second,
+            false,
+            "time:second",
+            "The second of this time, from 0 to 59",
+            None,
+            Known(ValueType::Integer),
+        );
+        res.declare(
+            full("weekday"),
+            weekday,
+            false,
+            "time:weekday",
+            "The name of the day of the week of this time in the local timezone, e.g. Monday",
+            None,
+            Known(ValueType::String),
+        );
+        res.declare(
+            full("unix"),
+            unix,
+            false,
+            "time:unix",
+            "The number of seconds between the unix epoch and this time",
+            None,
+            Known(ValueType::Integer),
+        );
         // TODO: why unused?
         let _ = Parse::declare_method(&mut res, &path);
         res
     };
 }
 
-binary_op!(add, time, Duration, Time, |a, b| a + b);
-binary_op!(
-    sub,
-    time,
-    Duration,
-    Time,
-    |a, b| a - b,
-    Time,
-    Duration,
-    |a, b| a - b
-);
+fn add(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let this = context.this.time()?;
+    let delta = context.arguments.duration(0)?;
+    context.output.send(Value::Time(mandate(
+        this.checked_add_signed(delta),
+        "Time overflow",
+    )?))
+}
+
+fn sub(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let this = context.this.time()?;
+    match context.arguments.value(0)? {
+        Value::Duration(d) => context.output.send(Value::Time(mandate(
+            this.checked_sub_signed(d),
+            "Time overflow",
+        )?)),
+        Value::Time(t) => context.output.send(Value::Duration(this - t)),
+        v => argument_error(
+            format!(
+                "Expected a duration or a time, found a {}",
+                v.value_type().to_string()
+            )
+            .as_str(),
+        ),
+    }
+}
 
 fn now(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
     context.output.send(Value::Time(Local::now()))
 }
 
+fn today(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context.output.send(Value::Time(mandate(
+        Local::today().and_hms_opt(0, 0, 0),
+        "Midnight does not exist today in the local timezone",
+    )?))
+}
+
+fn format(mut context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(1)?;
+    let format = context.arguments.string(0)?;
+    context
+        .output
+        .send(Value::String(format_time(&context.this.time()?, &format)?))
+}
+
+fn component(context: ExecutionContext, f: impl Fn(&DateTime<Local>) -> u32) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let t = context.this.time()?;
+    context.output.send(Value::Integer(f(&t) as i128))
+}
+
+fn year(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    let t = context.this.time()?;
+    context.output.send(Value::Integer(t.year() as i128))
+}
+
+fn month(context: ExecutionContext) -> CrushResult<()> {
+    component(context, |t| t.month())
+}
+
+fn day(context: ExecutionContext) -> CrushResult<()> {
+    component(context, |t| t.day())
+}
+
+fn hour(context: ExecutionContext) -> CrushResult<()> {
+    component(context, |t| t.hour())
+}
+
+fn minute(context: ExecutionContext) -> CrushResult<()> {
+    component(context, |t| t.minute())
+}
+
+fn second(context: ExecutionContext) -> CrushResult<()> {
+    component(context, |t| t.second())
+}
+
+/// The full English name of the day of the week of a time.
+fn weekday_name(t: &DateTime<Local>) -> String {
+    t.format("%A").to_string()
+}
+
+fn weekday(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::String(weekday_name(&context.this.time()?)))
+}
+
+fn unix(context: ExecutionContext) -> CrushResult<()> {
+    context.arguments.check_len(0)?;
+    context
+        .output
+        .send(Value::Integer(context.this.time()?.timestamp() as i128))
+}
+
 #[signature(
 parse,
 can_block=false,
 output=Known(ValueType::Time),
-short="Parse a time string using a strptime-style pattern string")]
+short="Parse a time string using a chrono-style format string",
+long="Dates without a time of day are interpreted as midnight in the local timezone.",
+example="time:parse \"24/12/2020 18:30\" format=\"%d/%m/%Y %H:%M\"")]
 struct Parse {
-    #[description("the format of the time.")]
-    format: String,
     #[description("the time string to parse.")]
     time: String,
+    #[description("the format of the time.")]
+    format: String,
 }
 
 fn parse(context: ExecutionContext) -> CrushResult<()> {
     let cfg: Parse = Parse::parse(context.arguments, &context.printer)?;
-    let tm = to_crush_error(strptime(&cfg.time, cfg.format.as_ref()))?;
-    let dt = Local::now()
-        .with_year(tm.tm_year + 1900)
-        .unwrap()
-        .with_month0(tm.tm_mon as u32)
-        .unwrap()
-        .with_day(max(tm.tm_mday as u32, 1))
-        .unwrap()
-        .with_hour(tm.tm_hour as u32)
-        .unwrap()
-        .with_minute(tm.tm_min as u32)
-        .unwrap()
-        .with_second(tm.tm_sec as u32)
-        .unwrap()
-        .with_nanosecond(tm.tm_nsec as u32)
+    context
+        .output
+        .send(Value::Time(parse_time_with_format(&cfg.time, &cfg.format)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::argument::Argument;
+    use crate::lib::types::test_util::call_method;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_components() {
+        let component = |t, name| call_method(Value::Time(t), name, vec![]).unwrap();
+        let t = Local.ymd(2020, 12, 24).and_hms(18, 30, 5);
+        assert!(component(t, "year") == Value::Integer(2020));
+        assert!(component(t, "month") == Value::Integer(12));
+        assert!(component(t, "day") == Value::Integer(24));
+        assert!(component(t, "hour") == Value::Integer(18));
+        assert!(component(t, "minute") == Value::Integer(30));
+        assert!(component(t, "second") == Value::Integer(5));
+        assert!(component(t, "weekday") == Value::string("Thursday"));
+        assert!(
+            component(Local.ymd(2021, 1, 3).and_hms(0, 0, 0), "weekday") == Value::string("Sunday")
+        );
+        assert!(call_method(
+            Value::Time(t),
+            "year",
+            vec![Argument::unnamed(Value::Integer(1))]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let start = Local.timestamp(1_600_000_000, 0);
+        let later = call_method(
+            Value::Time(start),
+            "__add__",
+            vec![Argument::unnamed(Value::Duration(Duration::hours(1)))],
+        )
         .unwrap();
-    context.output.send(Value::Time(dt))
+        assert!(matches!(later, Value::Time(t) if t.timestamp() == 1_600_003_600));
+        let earlier = call_method(
+            Value::Time(start),
+            "__sub__",
+            vec![Argument::unnamed(Value::Duration(Duration::hours(1)))],
+        )
+        .unwrap();
+        assert!(matches!(earlier, Value::Time(t) if t.timestamp() == 1_599_996_400));
+        let elapsed = call_method(
+            Value::Time(start),
+            "__sub__",
+            vec![Argument::unnamed(Value::Time(
+                Local.timestamp(1_600_003_600, 0),
+            ))],
+        )
+        .unwrap();
+        assert!(elapsed == Value::Duration(Duration::hours(-1)));
+        assert!(call_method(
+            Value::Time(start),
+            "__sub__",
+            vec![Argument::unnamed(Value::Integer(1))]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_arithmetic_overflow() {
+        let start = Local.timestamp(1_600_000_000, 0);
+        let overflow = |name: &str| {
+            call_method(
+                Value::Time(start),
+                name,
+                vec![Argument::unnamed(Value::Duration(Duration::max_value()))],
+            )
+            .err()
+            .unwrap()
+            .message
+        };
+        assert_eq!(overflow("__add__"), "Time overflow");
+        assert_eq!(overflow("__sub__"), "Time overflow");
+    }
+
+    #[test]
+    fn test_arithmetic_is_absolute() {
+        // 2020-03-29 00:30 UTC, half an hour before clocks are moved forward in
+        // most of Europe. The elapsed time is the same regardless of the timezone.
+        let before = Local.timestamp(1_585_441_800, 0);
+        let after = before.checked_add_signed(Duration::hours(24)).unwrap();
+        assert_eq!(after.timestamp() - before.timestamp(), 24 * 3600);
+        assert_eq!(after - before, Duration::hours(24));
+        assert_eq!(
+            after.checked_sub_signed(Duration::hours(24)).unwrap(),
+            before
+        );
+        assert!(before.checked_add_signed(Duration::max_value()).is_none());
+    }
 }
@@ -0,0 +1,115 @@
+use crate::lang::errors::{argument_error, CrushResult};
+
+/// Encode an integer as two's complement bytes. Without a width, the shortest encoding
+/// that preserves the sign is used, otherwise the value is sign extended to exactly
+/// width bytes.
+pub fn integer_to_bytes(
+    value: i128,
+    width: Option<usize>,
+    little_endian: bool,
+) -> CrushResult<Vec<u8>> {
+    let full = value.to_be_bytes();
+    let mut start = 0;
+    while start < full.len() - 1 {
+        let redundant = (full[start] == 0x00 && full[start + 1] & 0x80 == 0)
+            || (full[start] == 0xff && full[start + 1] & 0x80 != 0);
+        if !redundant {
+            break;
+        }
+        start += 1;
+    }
+    let minimal = &full[start..];
+
+    let mut res = match width {
+        None => minimal.to_vec(),
+        Some(width) => {
+            if width < minimal.len() {
+                return argument_error(
+                    format!("The integer {} does not fit in {} bytes", value, width).as_str(),
+                );
+            }
+            let fill = if value < 0 { 0xff } else { 0x00 };
+            let mut res = vec![fill; width - minimal.len()];
+            res.extend_from_slice(minimal);
+            res
+        }
+    };
+    if little_endian {
+        res.reverse();
+    }
+    Ok(res)
+}
+
+/// Decode up to 16 bytes of two's complement data into an integer.
+pub fn bytes_to_integer(bytes: &[u8], little_endian: bool) -> CrushResult<i128> {
+    if bytes.is_empty() {
+        return argument_error("Can't convert an empty binary to an integer");
+    }
+    if bytes.len() > 16 {
+        return argument_error(
+            format!(
+                "Can't convert a binary of {} bytes to an integer, the maximum is 16",
+                bytes.len()
+            )
+            .as_str(),
+        );
+    }
+    let mut big_endian = bytes.to_vec();
+    if little_endian {
+        big_endian.reverse();
+    }
+    let fill = if big_endian[0] & 0x80 != 0 {
+        0xff
+    } else {
+        0x00
+    };
+    let mut full = [fill; 16];
+    full[16 - big_endian.len()..].copy_from_slice(&big_endian);
+    Ok(i128::from_be_bytes(full))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimal_encoding() {
+        assert_eq!(integer_to_bytes(0, None, false).unwrap(), vec![0x00]);
+        assert_eq!(integer_to_bytes(127, None, false).unwrap(), vec![0x7f]);
+        assert_eq!(
+            integer_to_bytes(128, None, false).unwrap(),
+            vec![0x00, 0x80]
+        );
+        assert_eq!(integer_to_bytes(-1, None, false).unwrap(), vec![0xff]);
+        assert_eq!(
+            integer_to_bytes(-129, None, false).unwrap(),
+            vec![0xff, 0x7f]
+        );
+        assert_eq!(integer_to_bytes(258, None, true).unwrap(), vec![0x02, 0x01]);
+    }
+
+    #[test]
+    fn test_fixed_width() {
+        assert_eq!(
+            integer_to_bytes(1, Some(4), false).unwrap(),
+            vec![0x00, 0x00, 0x00, 0x01]
+        );
+        assert_eq!(
+            integer_to_bytes(-2, Some(4), true).unwrap(),
+            vec![0xfe, 0xff, 0xff, 0xff]
+        );
+        assert!(integer_to_bytes(128, Some(1), false).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for i in &[0, 1, -1, 255, -256, 65536, i128::MAX, i128::MIN] {
+            for little_endian in &[false, true] {
+                let bytes = integer_to_bytes(*i, None, *little_endian).unwrap();
+                assert_eq!(bytes_to_integer(&bytes, *little_endian).unwrap(), *i);
+            }
+        }
+        assert!(bytes_to_integer(&[], false).is_err());
+        assert!(bytes_to_integer(&[0; 17], false).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+/// The number of inserted, removed, replaced or swapped characters needed to turn a into b.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// The candidate that is at most a couple of typos away from the needle, if any. Shorter
+/// needles allow fewer typos, so that unrelated short names aren't suggested.
+pub fn closest<'a>(candidates: impl Iterator<Item = &'a str>, needle: &str) -> Option<&'a str> {
+    let max_distance = (needle.chars().count() / 3).clamp(1, 2);
+    candidates
+        .map(|candidate| (edit_distance(candidate, needle), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("file", "file"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("usr", "user"), 1);
+        assert_eq!(edit_distance("flie", "file"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("größe", "grösse"), 2);
+    }
+
+    #[test]
+    fn test_closest() {
+        let names = ["user", "file", "size"];
+        assert_eq!(closest(names.iter().cloned(), "usr"), Some("user"));
+        assert_eq!(closest(names.iter().cloned(), "flie"), Some("file"));
+        assert_eq!(closest(names.iter().cloned(), "sizes"), Some("size"));
+        assert_eq!(closest(names.iter().cloned(), "pid"), None);
+        assert_eq!(closest(names.iter().cloned(), "fi"), None);
+    }
+}
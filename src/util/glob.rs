@@ -77,6 +77,21 @@ impl Glob {
         glob_match(&self.pattern, v).matches
     }
 
+    /// A regular expression that matches the same strings as this glob.
+    pub fn to_regex(&self) -> String {
+        let mut res = "^".to_string();
+        for tile in &self.pattern {
+            match tile {
+                Tile::Char(c) => res.push_str(&regex::escape(&c.to_string())),
+                Tile::Single => res.push_str("[^/]"),
+                Tile::Any => res.push_str("[^/]*"),
+                Tile::Recursive => res.push_str(".*"),
+            }
+        }
+        res.push('$');
+        res
+    }
+
     pub fn glob_files(&self, cwd: &Path, out: &mut Vec<PathBuf>) -> CrushResult<()> {
         to_crush_error(glob_files(&self.pattern, cwd, out))
     }
@@ -228,6 +243,21 @@ fn glob_match(pattern: &[Tile], value: &str) -> GlobResult {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_to_regex() {
+        let re = Regex::new(&Glob::new("%.rs").to_regex()).unwrap();
+        assert!(re.is_match("main.rs"));
+        assert!(!re.is_match("src/main.rs"));
+        assert!(!re.is_match("main.rsx"));
+        assert!(!re.is_match("mainXrs"));
+
+        let re = Regex::new(&Glob::new("src/%%/?.rs").to_regex()).unwrap();
+        assert!(re.is_match("src/lang/a.rs"));
+        assert!(re.is_match("src/lang/value/a.rs"));
+        assert!(!re.is_match("src/lang/ab.rs"));
+    }
 
     #[test]
     fn test_glob_match() {
@@ -0,0 +1,71 @@
+use crate::lang::errors::{argument_error, to_crush_error, CrushResult};
+use openssl::hash::{Hasher, MessageDigest};
+use std::io::Read;
+
+/// The names of the supported hash algorithms.
+pub const ALGORITHMS: &[&str] = &["sha256", "sha1", "md5"];
+
+fn message_digest(algorithm: &str) -> CrushResult<MessageDigest> {
+    match algorithm {
+        "sha256" => Ok(MessageDigest::sha256()),
+        "sha1" => Ok(MessageDigest::sha1()),
+        "md5" => Ok(MessageDigest::md5()),
+        _ => argument_error(
+            format!(
+                "Unknown hash algorithm {}, expected one of {}",
+                algorithm,
+                ALGORITHMS.join(", ")
+            )
+            .as_str(),
+        ),
+    }
+}
+
+/// Calculate the digest of everything that can be read from input. The input is consumed in
+/// fixed size pieces, so memory use does not depend on the size of the input.
+pub fn digest(algorithm: &str, input: &mut dyn Read) -> CrushResult<Vec<u8>> {
+    let mut hasher = to_crush_error(Hasher::new(message_digest(algorithm)?))?;
+    let mut buff = vec![0u8; 64 * 1024];
+    loop {
+        let len = to_crush_error(input.read(&mut buff))?;
+        if len == 0 {
+            break;
+        }
+        to_crush_error(hasher.update(&buff[0..len]))?;
+    }
+    Ok(to_crush_error(hasher.finish())?.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::hex;
+
+    fn hex_digest(algorithm: &str, data: &[u8]) -> String {
+        hex::encode(&digest(algorithm, &mut &data[..]).unwrap())
+    }
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(
+            hex_digest("sha256", b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex_digest("sha1", b"abc"),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex_digest("md5", b"abc"),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert!(digest("crc32", &mut &b"abc"[..]).is_err());
+    }
+
+    #[test]
+    fn test_large_input() {
+        let data: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+        let expected = openssl::hash::hash(MessageDigest::sha256(), &data).unwrap();
+        assert_eq!(digest("sha256", &mut &data[..]).unwrap(), expected.to_vec());
+    }
+}
@@ -0,0 +1,52 @@
+use crate::lang::errors::{argument_error, CrushResult};
+
+/// Encode bytes as lowercase hexadecimal, two digits per byte.
+pub fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode hexadecimal digits of either case into bytes. The input must contain an even
+/// number of digits and nothing else.
+pub fn decode(s: &str) -> CrushResult<Vec<u8>> {
+    if s.len() % 2 == 1 {
+        return argument_error("Hexadecimal data must have an even number of digits");
+    }
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| match (digit(pair[0]), digit(pair[1])) {
+            (Some(high), Some(low)) => Ok(high << 4 | low),
+            _ => argument_error(
+                format!(
+                    "Invalid hexadecimal digits {}",
+                    String::from_utf8_lossy(pair)
+                )
+                .as_str(),
+            ),
+        })
+        .collect()
+}
+
+fn digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|d| d as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode(&[]), "");
+        assert_eq!(encode(&[0x00, 0x0f, 0xa0, 0xff]), "000fa0ff");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode(&encode(&all)).unwrap(), all);
+        assert_eq!(decode("DEADbeef").unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+        assert!(decode("abc").is_err());
+        assert!(decode("zz").is_err());
+        assert!(decode("é0").is_err());
+    }
+}
@@ -0,0 +1,21 @@
+/// Resolve a possibly negative index into an offset within a sequence of length len.
+/// Negative indices count from the end, and out of bounds indices are clamped.
+pub fn clamp_index(idx: i128, len: usize) -> usize {
+    let len = len as i128;
+    let idx = if idx < 0 { len + idx } else { idx };
+    idx.max(0).min(len) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_index() {
+        assert_eq!(clamp_index(2, 5), 2);
+        assert_eq!(clamp_index(7, 5), 5);
+        assert_eq!(clamp_index(-1, 5), 4);
+        assert_eq!(clamp_index(-7, 5), 0);
+        assert_eq!(clamp_index(0, 0), 0);
+    }
+}
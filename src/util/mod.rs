@@ -1,6 +1,12 @@
+pub mod bytes;
+pub mod distance;
 pub mod file;
 pub mod glob;
+pub mod hash;
+pub mod hex;
 pub mod identity_arc;
+pub mod index;
+pub mod radix;
 pub mod regex;
 pub mod replace;
 pub mod thread;
@@ -0,0 +1,113 @@
+use crate::lang::errors::{argument_error, CrushResult};
+
+fn check_radix(radix: i128) -> CrushResult<u32> {
+    if (2..=36).contains(&radix) {
+        Ok(radix as u32)
+    } else {
+        argument_error(format!("Invalid radix {}, must be between 2 and 36", radix).as_str())
+    }
+}
+
+/// Format an integer in the specified radix, using lowercase letters for digits above 9.
+pub fn format_radix(value: i128, radix: i128) -> CrushResult<String> {
+    let radix = check_radix(radix)?;
+    if value == 0 {
+        return Ok("0".to_string());
+    }
+    let mut digits = Vec::new();
+    let mut rest = value.unsigned_abs();
+    while rest > 0 {
+        digits.push(std::char::from_digit((rest % radix as u128) as u32, radix).unwrap());
+        rest /= radix as u128;
+    }
+    if value < 0 {
+        digits.push('-');
+    }
+    Ok(digits.iter().rev().collect())
+}
+
+/// Parse an integer in the specified radix. Digits of either case are accepted, as is the
+/// customary 0x, 0o or 0b prefix for radix 16, 8 and 2.
+pub fn parse_radix(s: &str, radix: i128) -> CrushResult<i128> {
+    let radix = check_radix(radix)?;
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let prefix = match radix {
+        16 => Some("0x"),
+        8 => Some("0o"),
+        2 => Some("0b"),
+        _ => None,
+    };
+    let digits = prefix
+        .and_then(|p| {
+            digits
+                .strip_prefix(p)
+                .or_else(|| digits.strip_prefix(&p.to_uppercase()))
+        })
+        .unwrap_or(digits);
+    let invalid = || argument_error(format!("Invalid integer {:?} in radix {}", s, radix).as_str());
+    if digits.is_empty() || digits.starts_with(['+', '-']) {
+        return invalid();
+    }
+    let magnitude = match u128::from_str_radix(digits, radix) {
+        Ok(m) => m,
+        Err(_) => return invalid(),
+    };
+    if negative {
+        if magnitude > i128::MIN.unsigned_abs() {
+            invalid()
+        } else {
+            Ok((magnitude as i128).wrapping_neg())
+        }
+    } else if magnitude > i128::MAX as u128 {
+        invalid()
+    } else {
+        Ok(magnitude as i128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_radix() {
+        assert_eq!(format_radix(255, 16).unwrap(), "ff");
+        assert_eq!(format_radix(-5, 2).unwrap(), "-101");
+        assert_eq!(format_radix(0, 8).unwrap(), "0");
+        assert_eq!(format_radix(35, 36).unwrap(), "z");
+        assert_eq!(
+            format_radix(i128::MIN, 16).unwrap(),
+            "-80000000000000000000000000000000"
+        );
+        assert!(format_radix(10, 1).is_err());
+        assert!(format_radix(10, 37).is_err());
+    }
+
+    #[test]
+    fn test_parse_radix() {
+        assert_eq!(parse_radix("ff", 16).unwrap(), 255);
+        assert_eq!(parse_radix("0xFF", 16).unwrap(), 255);
+        assert_eq!(parse_radix("-0b101", 2).unwrap(), -5);
+        assert_eq!(parse_radix("777", 8).unwrap(), 511);
+        assert!(parse_radix("0x10", 10).is_err());
+        assert!(parse_radix("", 10).is_err());
+        assert!(parse_radix("-", 10).is_err());
+        assert!(parse_radix("--1", 10).is_err());
+        assert!(parse_radix("12", 2).is_err());
+        assert!(parse_radix("1", 0).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for value in &[0, 1, -1, 4096, i128::MAX, i128::MIN] {
+            for radix in &[2, 8, 10, 16, 36] {
+                let s = format_radix(*value, *radix).unwrap();
+                assert_eq!(parse_radix(&s, *radix).unwrap(), *value);
+            }
+        }
+        assert!(parse_radix("170141183460469231731687303715884105728", 10).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::lang::errors::to_crush_error;
+use crate::lang::errors::{argument_error, to_crush_error, CrushResult};
 use crate::lang::printer::Printer;
 use regex::Regex;
 use std::fs::read_dir;
@@ -32,3 +32,61 @@ impl RegexFileMatcher for Regex {
         }
     }
 }
+
+/// Check that every capture group a replacement string refers to, like $1, $name or
+/// ${name}, exists in the regex. The regex crate silently replaces unknown groups with
+/// the empty string, which hides typos like $1a where ${1}a was meant.
+pub fn check_replacement(re: &Regex, replacement: &str) -> CrushResult<()> {
+    let mut rest = replacement;
+    while let Some(idx) = rest.find('$') {
+        rest = &rest[idx + 1..];
+        let name = if let Some(tail) = rest.strip_prefix('$') {
+            rest = tail;
+            continue;
+        } else if let Some(end) = rest.strip_prefix('{').and_then(|tail| tail.find('}')) {
+            let name = &rest[1..end + 1];
+            rest = &rest[end + 2..];
+            name
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let name = &rest[..end];
+            rest = &rest[end..];
+            name
+        };
+        if name.is_empty() {
+            continue;
+        }
+        let exists = match name.parse::<usize>() {
+            Ok(idx) => idx < re.captures_len(),
+            Err(_) => re.capture_names().any(|n| n == Some(name)),
+        };
+        if !exists {
+            return argument_error(
+                format!(
+                    "Replacement refers to unknown capture group {}, use ${{name}} to separate a group name from the text that follows it",
+                    name
+                )
+                .as_str(),
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_replacement() {
+        let re = Regex::new("(?P<key>[a-z]+)=([0-9]+)").unwrap();
+        assert!(check_replacement(&re, "$key:$2").is_ok());
+        assert!(check_replacement(&re, "${key}s ${0} $$3 $").is_ok());
+        assert!(check_replacement(&re, "$3").is_err());
+        assert!(check_replacement(&re, "$keys").is_err());
+        assert!(check_replacement(&re, "$1a").is_err());
+        assert!(check_replacement(&re, "${1}a").is_ok());
+    }
+}
@@ -1,61 +1,438 @@
-use chrono::Duration;
+use crate::lang::errors::{argument_error, CrushResult};
+use chrono::format::{Item, StrftimeItems};
+use chrono::offset::LocalResult;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::fmt::Write;
 
-pub fn duration_format(d: &Duration) -> String {
-    const MICROS_IN_SECOND: i128 = 1_000_000_000;
-    const MICROS_IN_MINUTE: i128 = MICROS_IN_SECOND * 60;
-    const MICROS_IN_HOUR: i128 = MICROS_IN_MINUTE * 60;
-    const MICROS_IN_DAY: i128 = MICROS_IN_HOUR * 24;
-    const MICROS_IN_YEAR: i128 = MICROS_IN_DAY * 365;
-    let mut remaining_nanos = d.num_nanoseconds().map(|v| v as i128).unwrap_or_else(|| {
-        d.num_microseconds()
-            .map(|v| v as i128 * 1000)
-            .unwrap_or(d.num_milliseconds() as i128 * 1000_000)
-    });
+/// The formats tried, in order, after RFC 3339 when converting a string into a time.
+const TIME_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S", "%Y-%m-%d"];
 
-    let mut res = "".to_string();
+pub const NANOS_IN_SECOND: i128 = 1_000_000_000;
+pub const NANOS_IN_MINUTE: i128 = NANOS_IN_SECOND * 60;
+pub const NANOS_IN_HOUR: i128 = NANOS_IN_MINUTE * 60;
+pub const NANOS_IN_DAY: i128 = NANOS_IN_HOUR * 24;
+pub const NANOS_IN_YEAR: i128 = NANOS_IN_DAY * 365;
+
+/// The syntax accepted by parse_duration, used in error messages.
+const DURATION_GRAMMAR: &str =
+    "[-][<years>y][<days>d][[<hours>:]<minutes>:]<seconds>[.<fraction>] or [-](<number>(ms|s|m|h|d))...";
 
-    if d < &Duration::seconds(0) {
-        res.push_str("-");
+/// The components of a duration, as printed by duration_format. Years are 365 days long.
+/// The components are never negative, the sign of the duration is kept separately.
+pub struct DurationParts {
+    pub negative: bool,
+    pub years: i128,
+    pub days: i128,
+    pub hours: i128,
+    pub minutes: i128,
+    pub seconds: i128,
+    pub nanoseconds: i128,
+}
+
+pub fn duration_parts(d: &Duration) -> DurationParts {
+    let nanos = duration_nanos(d);
+    let mut remaining = nanos.abs();
+    let mut take = |unit: i128| {
+        let res = remaining / unit;
+        remaining %= unit;
+        res
+    };
+    DurationParts {
+        negative: nanos < 0,
+        years: take(NANOS_IN_YEAR),
+        days: take(NANOS_IN_DAY),
+        hours: take(NANOS_IN_HOUR),
+        minutes: take(NANOS_IN_MINUTE),
+        seconds: take(NANOS_IN_SECOND),
+        nanoseconds: remaining,
     }
+}
 
-    let years = remaining_nanos / MICROS_IN_YEAR;
-    if years != 0 {
-        remaining_nanos -= years * MICROS_IN_YEAR;
-        res.push_str(format!("{}y", years.abs()).as_str());
+pub fn duration_format(d: &Duration) -> String {
+    let parts = duration_parts(d);
+    let mut res = "".to_string();
+
+    if parts.years != 0 {
+        res.push_str(format!("{}y", parts.years).as_str());
     }
 
-    let days = remaining_nanos / MICROS_IN_DAY;
-    if days != 0 || !res.is_empty() {
-        remaining_nanos -= days * MICROS_IN_DAY;
-        res.push_str(format!("{}d", days.abs()).as_str());
+    if parts.days != 0 || !res.is_empty() {
+        res.push_str(format!("{}d", parts.days).as_str());
     }
 
-    let hours = remaining_nanos / MICROS_IN_HOUR;
-    if hours != 0 || !res.is_empty() {
-        remaining_nanos -= hours * MICROS_IN_HOUR;
-        res.push_str(format!("{}:", hours.abs()).as_str());
+    if parts.hours != 0 || !res.is_empty() {
+        res.push_str(format!("{}:", parts.hours).as_str());
     }
 
-    let minutes = remaining_nanos / MICROS_IN_MINUTE;
-    if minutes != 0 || !res.is_empty() {
-        remaining_nanos -= minutes * MICROS_IN_MINUTE;
+    if parts.minutes != 0 || !res.is_empty() {
         if res.is_empty() {
-            res.push_str(format!("{}:", minutes.abs()).as_str());
+            res.push_str(format!("{}:", parts.minutes).as_str());
         } else {
-            res.push_str(format!("{:02}:", minutes.abs()).as_str());
+            res.push_str(format!("{:02}:", parts.minutes).as_str());
         }
     }
 
-    let seconds = remaining_nanos / MICROS_IN_SECOND;
-    remaining_nanos -= seconds * MICROS_IN_SECOND;
     if res.is_empty() {
-        res.push_str(format!("{}", seconds.abs()).as_str());
+        res.push_str(format!("{}", parts.seconds).as_str());
     } else {
-        res.push_str(format!("{:02}", seconds.abs()).as_str());
+        res.push_str(format!("{:02}", parts.seconds).as_str());
     }
 
-    if (res.len() < 4) && (remaining_nanos != 0) {
-        res.push_str(format!(".{:09}", remaining_nanos.abs()).trim_end_matches('0'));
+    if res.len() < 4 {
+        res.push_str(&fraction(parts.nanoseconds));
+    }
+    if parts.negative {
+        res.insert(0, '-');
     }
     res
 }
+
+/// The ways a duration can be formatted by duration_format_opts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DurationStyle {
+    /// The format produced by duration_format, e.g. 1:30:00.
+    Human,
+    /// An ISO-8601 duration, e.g. PT1H30M. Years are written as 365 days.
+    Iso,
+    /// The number of seconds, e.g. 5400.
+    Seconds,
+}
+
+pub fn duration_format_opts(d: &Duration, style: DurationStyle) -> String {
+    match style {
+        DurationStyle::Human => duration_format(d),
+        DurationStyle::Iso => iso_duration_format(d),
+        DurationStyle::Seconds => {
+            let nanos = duration_nanos(d);
+            format!(
+                "{}{}{}",
+                if nanos < 0 { "-" } else { "" },
+                nanos.abs() / NANOS_IN_SECOND,
+                fraction(nanos.abs() % NANOS_IN_SECOND)
+            )
+        }
+    }
+}
+
+/// The fractional part of a second, with a leading dot and without trailing zeros, or
+/// nothing if it is zero.
+fn fraction(nanoseconds: i128) -> String {
+    if nanoseconds == 0 {
+        "".to_string()
+    } else {
+        format!(".{:09}", nanoseconds)
+            .trim_end_matches('0')
+            .to_string()
+    }
+}
+
+fn iso_duration_format(d: &Duration) -> String {
+    let parts = duration_parts(d);
+    let mut res = if parts.negative { "-P" } else { "P" }.to_string();
+    let days = parts.years * 365 + parts.days;
+    if days != 0 {
+        res.push_str(format!("{}D", days).as_str());
+    }
+    let has_seconds = parts.seconds != 0 || parts.nanoseconds != 0;
+    if parts.hours != 0 || parts.minutes != 0 || has_seconds || days == 0 {
+        res.push('T');
+    }
+    if parts.hours != 0 {
+        res.push_str(format!("{}H", parts.hours).as_str());
+    }
+    if parts.minutes != 0 {
+        res.push_str(format!("{}M", parts.minutes).as_str());
+    }
+    if has_seconds || res.ends_with('T') {
+        res.push_str(format!("{}{}S", parts.seconds, fraction(parts.nanoseconds)).as_str());
+    }
+    res
+}
+
+pub fn parse_time(s: &str) -> CrushResult<DateTime<Local>> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t.with_timezone(&Local));
+    }
+    for format in TIME_FORMATS {
+        if let Ok(t) = parse_time_with_format(s, format) {
+            return Ok(t);
+        }
+    }
+    argument_error(
+        format!(
+            "Could not parse \"{}\" as a time, tried the formats rfc3339, {}",
+            s,
+            TIME_FORMATS
+                .iter()
+                .map(|f| format!("\"{}\"", f))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+        .as_str(),
+    )
+}
+
+/// The time the specified number of seconds after the unix epoch, with microsecond
+/// precision.
+pub fn time_from_epoch_seconds(seconds: f64) -> CrushResult<DateTime<Local>> {
+    if !seconds.is_finite() || seconds.abs() >= i64::MAX as f64 {
+        return argument_error(format!("The epoch time {} is out of range", seconds).as_str());
+    }
+    let whole = seconds.floor();
+    let micros = ((seconds - whole) * 1_000_000.0).round() as i64;
+    time_from_epoch(
+        whole as i64 + micros / 1_000_000,
+        (micros % 1_000_000) as u32 * 1000,
+    )
+}
+
+pub fn time_from_epoch(seconds: i64, nanoseconds: u32) -> CrushResult<DateTime<Local>> {
+    match Local.timestamp_opt(seconds, nanoseconds) {
+        LocalResult::Single(t) => Ok(t),
+        _ => argument_error(format!("The epoch time {} is out of range", seconds).as_str()),
+    }
+}
+
+/// The number of seconds since the unix epoch, with microsecond precision.
+pub fn epoch_seconds(t: &DateTime<Local>) -> f64 {
+    t.timestamp() as f64 + (t.timestamp_subsec_micros() as f64) / 1_000_000.0
+}
+
+fn is_valid_format(format: &str) -> bool {
+    StrftimeItems::new(format).all(|item| item != Item::Error)
+}
+
+/// Check that every directive in a chrono format string is valid, naming the first invalid
+/// one otherwise. Chrono panics when formatting with an invalid directive.
+pub fn check_time_format(format: &str) -> CrushResult<()> {
+    let mut rest = format;
+    while let Some(idx) = rest.find('%') {
+        rest = &rest[idx..];
+        // Directives are at most four characters long, e.g. %.3f, and none of them is a
+        // prefix of another.
+        let ends = rest
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .skip(2)
+            .chain(std::iter::once(rest.len()))
+            .take(3);
+        match ends.clone().find(|end| is_valid_format(&rest[..*end])) {
+            Some(end) => rest = &rest[end..],
+            None => {
+                let modified = rest[1..].starts_with(|c| "-_0#.:369".contains(c));
+                let directive: String = rest.chars().take(if modified { 3 } else { 2 }).collect();
+                return argument_error(
+                    format!(
+                        "Invalid directive \"{}\" in the time format \"{}\"",
+                        directive, format
+                    )
+                    .as_str(),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn format_time(t: &DateTime<Local>, format: &str) -> CrushResult<String> {
+    check_time_format(format)?;
+    let mut res = String::new();
+    match write!(res, "{}", t.format(format)) {
+        Ok(()) => Ok(res),
+        Err(_) => argument_error(
+            format!("Could not format the time using the format \"{}\"", format).as_str(),
+        ),
+    }
+}
+
+pub fn parse_time_with_format(s: &str, format: &str) -> CrushResult<DateTime<Local>> {
+    check_time_format(format)?;
+    let naive = match NaiveDateTime::parse_from_str(s, format) {
+        Ok(t) => t,
+        Err(e) => match NaiveDate::parse_from_str(s, format) {
+            Ok(d) => d.and_hms(0, 0, 0),
+            Err(_) => {
+                return argument_error(
+                    format!(
+                        "Could not parse \"{}\" as a time using the format \"{}\": {}",
+                        s, format, e
+                    )
+                    .as_str(),
+                )
+            }
+        },
+    };
+    match Local.from_local_datetime(&naive) {
+        LocalResult::Single(t) => Ok(t),
+        LocalResult::Ambiguous(_, _) => argument_error(
+            format!("The time \"{}\" is ambiguous in the local timezone", s).as_str(),
+        ),
+        LocalResult::None => argument_error(
+            format!("The time \"{}\" does not exist in the local timezone", s).as_str(),
+        ),
+    }
+}
+
+/// The total number of seconds in a duration, including the fractional part.
+pub fn duration_seconds(d: &Duration) -> f64 {
+    match d.num_nanoseconds() {
+        Some(nanos) => nanos as f64 / NANOS_IN_SECOND as f64,
+        None => d.num_milliseconds() as f64 / 1000.0,
+    }
+}
+
+/// A duration of the specified number of seconds, truncated towards zero to whole nanoseconds.
+pub fn duration_from_seconds(seconds: f64) -> CrushResult<Duration> {
+    if !seconds.is_finite() || seconds.abs() >= (i64::MAX / 1000) as f64 {
+        return argument_error(format!("The duration {} is out of range", seconds).as_str());
+    }
+    let whole = seconds.trunc();
+    let nanos = ((seconds - whole) * NANOS_IN_SECOND as f64).trunc();
+    Ok(Duration::seconds(whole as i64) + Duration::nanoseconds(nanos as i64))
+}
+
+/// The exact number of nanoseconds in a duration.
+pub fn duration_nanos(d: &Duration) -> i128 {
+    let seconds = d.num_seconds();
+    let nanos = (*d - Duration::seconds(seconds))
+        .num_nanoseconds()
+        .unwrap_or(0);
+    seconds as i128 * NANOS_IN_SECOND + nanos as i128
+}
+
+/// A duration of the specified number of nanoseconds, or None if it is out of range.
+pub fn duration_from_nanos(nanos: i128) -> Option<Duration> {
+    let seconds = nanos / NANOS_IN_SECOND;
+    if seconds.abs() > (i64::MAX / 1000) as i128 {
+        return None;
+    }
+    Duration::seconds(seconds as i64)
+        .checked_add(&Duration::nanoseconds((nanos % NANOS_IN_SECOND) as i64))
+}
+
+/// Parse a duration, either in the format produced by duration_format, e.g. "3d0:00:01",
+/// or as a sequence of numbers with unit suffixes, e.g. "90s", "2h" or "1h30m".
+pub fn parse_duration(s: &str) -> CrushResult<Duration> {
+    match parse_duration_nanos(s.trim()) {
+        Some(nanos) => match duration_from_nanos(nanos) {
+            Some(d) => Ok(d),
+            None => argument_error(format!("The duration \"{}\" is out of range", s).as_str()),
+        },
+        None => argument_error(
+            format!(
+                "Could not parse \"{}\" as a duration, expected {}",
+                s, DURATION_GRAMMAR
+            )
+            .as_str(),
+        ),
+    }
+}
+
+fn parse_number(s: &str) -> Option<i128> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_digit()) {
+        None
+    } else {
+        s.parse::<i64>().ok().map(|n| n as i128)
+    }
+}
+
+fn parse_duration_nanos(s: &str) -> Option<i128> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let nanos = parse_suffixed_duration(s).or_else(|| parse_clock_duration(s))?;
+    Some(if negative { -nanos } else { nanos })
+}
+
+fn parse_suffixed_duration(s: &str) -> Option<i128> {
+    if s.is_empty() {
+        return None;
+    }
+    let mut res: i128 = 0;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !c.is_ascii_digit())?;
+        let unit_end = rest[number_end..]
+            .find(|c: char| c.is_ascii_digit())
+            .map(|idx| number_end + idx)
+            .unwrap_or_else(|| rest.len());
+        let unit = match &rest[number_end..unit_end] {
+            "ms" => NANOS_IN_SECOND / 1000,
+            "s" => NANOS_IN_SECOND,
+            "m" => NANOS_IN_MINUTE,
+            "h" => NANOS_IN_HOUR,
+            "d" => NANOS_IN_DAY,
+            _ => return None,
+        };
+        res = res.checked_add(parse_number(&rest[..number_end])?.checked_mul(unit)?)?;
+        rest = &rest[unit_end..];
+    }
+    Some(res)
+}
+
+fn parse_clock_duration(s: &str) -> Option<i128> {
+    let mut res = 0;
+    let mut rest = s;
+    if let Some(idx) = rest.find('y') {
+        res += parse_number(&rest[..idx])? * NANOS_IN_YEAR;
+        rest = &rest[idx + 1..];
+    }
+    if let Some(idx) = rest.find('d') {
+        res += parse_number(&rest[..idx])? * NANOS_IN_DAY;
+        rest = &rest[idx + 1..];
+    }
+
+    let mut parts = rest.split(':').rev();
+    let seconds = parts.next()?;
+    match seconds.find('.') {
+        Some(idx) => {
+            let fraction = &seconds[idx + 1..];
+            if fraction.len() > 9 {
+                return None;
+            }
+            res += parse_number(&seconds[..idx])? * NANOS_IN_SECOND;
+            res += parse_number(fraction)? * 10i128.pow(9 - fraction.len() as u32);
+        }
+        None => res += parse_number(seconds)? * NANOS_IN_SECOND,
+    }
+    for unit in &[NANOS_IN_MINUTE, NANOS_IN_HOUR] {
+        if let Some(part) = parts.next() {
+            res += parse_number(part)? * unit;
+        }
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_time_format() {
+        assert!(check_time_format("%Y-%m-%d %H:%M:%S%.3f %:z %%").is_ok());
+        assert!(check_time_format("%-d/%_m/%#z").is_ok());
+        assert!(check_time_format("no directives").is_ok());
+        let err = |f: &str| check_time_format(f).unwrap_err().message;
+        assert!(err("%Y-%Q").contains("\"%Q\""));
+        assert!(err("%-Q").contains("\"%-Q\""));
+        assert!(err("%.4f").contains("\"%.4\""));
+        assert!(err("100%").contains("\"%\""));
+    }
+
+    #[test]
+    fn test_format_time() {
+        let t = Local.ymd(2020, 12, 24).and_hms(18, 30, 5);
+        assert_eq!(
+            format_time(&t, "%d/%m/%Y %H:%M:%S").unwrap(),
+            "24/12/2020 18:30:05"
+        );
+        assert_eq!(format_time(&t, "%Y-%m-%d").unwrap(), "2020-12-24");
+        assert_eq!(format_time(&t, "%H:%M").unwrap(), "18:30");
+        assert!(format_time(&t, "%Q").is_err());
+        assert!(parse_time_with_format("2020", "%Q").is_err());
+    }
+}